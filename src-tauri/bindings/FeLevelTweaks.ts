@@ -1,3 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Modulation } from "./Modulation";
+import type { ResponseCurve } from "./ResponseCurve";
 
-export type FeLevelTweaks = { minimum_level: number, maximum_level: number, idle_level: number, smooth_rate: number, linear_position_speed: number, rate_tune: number, constant_level: number, };
+export type FeLevelTweaks = { minimum_level: number, maximum_level: number, idle_level: number, smooth_rate: number, linear_position_speed: number, rate_decay_per_second: number, constant_level: number, smooth_half_life_ms: number, envelope_attack_ms: number, envelope_decay_ms: number, envelope_sustain_level: number, envelope_release_ms: number, gate_lower_threshold: number, gate_upper_threshold: number, gate_passthrough: boolean, gate_fixed_level: number, gate_min_on_ms: number, gate_min_off_ms: number, response_curve: ResponseCurve, modulation: Modulation, };
@@ -1,3 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OscForwardTarget } from "./OscForwardTarget";
+import type { OscLevelOutputConfig } from "./OscLevelOutputConfig";
 
-export type FeOSCNetworking = { bind: string, remote: string, osc_query_enabled: boolean, };
+export type FeOSCNetworking = { bind: string, remote: string, osc_query_enabled: boolean, level_output: OscLevelOutputConfig, forwarding: Array<OscForwardTarget>, };
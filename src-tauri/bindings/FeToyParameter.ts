@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { FeProcessingMode } from "./FeProcessingMode";
+import type { IntMapping } from "./IntMapping";
 
-export type FeToyParameter = { parameter: string, processing_mode: FeProcessingMode, };
+export type FeToyParameter = { parameter: string, processing_mode: FeProcessingMode, int_mapping?: IntMapping, weight?: number, pattern?: string, };
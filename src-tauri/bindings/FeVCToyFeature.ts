@@ -3,5 +3,6 @@ import type { FeLevelTweaks } from "./FeLevelTweaks";
 import type { FePenetrationSystem } from "./FePenetrationSystem";
 import type { FeToyParameter } from "./FeToyParameter";
 import type { FeVCFeatureType } from "./FeVCFeatureType";
+import type { ParameterBlendMode } from "./ParameterBlendMode";
 
-export type FeVCToyFeature = { feature_enabled: boolean, feature_type: FeVCFeatureType, osc_parameters: Array<FeToyParameter>, penetration_system: FePenetrationSystem, feature_index: number, flip_input_float: boolean, feature_levels: FeLevelTweaks, smooth_enabled: boolean, rate_enabled: boolean, };
+export type FeVCToyFeature = { feature_enabled: boolean, feature_type: FeVCFeatureType, osc_parameters: Array<FeToyParameter>, parameter_blend: ParameterBlendMode, penetration_system: FePenetrationSystem, feature_index: number, flip_input_float: boolean, feature_levels: FeLevelTweaks, smooth_enabled: boolean, rate_enabled: boolean, };
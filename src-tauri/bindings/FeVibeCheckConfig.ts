@@ -1,4 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ButtplugConnectorConfig } from "./ButtplugConnectorConfig";
+import type { FeLocalApi } from "./FeLocalApi";
 import type { FeOSCNetworking } from "./FeOSCNetworking";
+import type { SafetyConfig } from "./SafetyConfig";
 
-export type FeVibeCheckConfig = { networking: FeOSCNetworking, scan_on_disconnect: boolean, minimize_on_exit: boolean, desktop_notifications: boolean, show_toy_advanced: boolean, show_feature_advanced: boolean, };
+export type FeVibeCheckConfig = { networking: FeOSCNetworking, scan_on_disconnect: boolean, minimize_on_exit: boolean, desktop_notifications: boolean, show_toy_advanced: boolean, show_feature_advanced: boolean, bp_connector: ButtplugConnectorConfig, local_api: FeLocalApi, safety: SafetyConfig, };
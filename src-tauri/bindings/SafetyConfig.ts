@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SafetyConfig = { max_level: number, max_change_per_second: number, run_cap_minutes: number, cooldown_minutes: number, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type VirtualCommand = { "Scalar": { feature_index: number, level: number, actuator_type: string, } } | { "Rotate": { feature_index: number, speed: number, clockwise: boolean, } } | { "Linear": { feature_index: number, duration: number, position: number, } } | "Stop";
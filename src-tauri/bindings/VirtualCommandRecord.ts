@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VirtualCommand } from "./VirtualCommand";
+
+export type VirtualCommandRecord = { device_index: number, device_name: string, elapsed_ms: bigint, command: VirtualCommand, };
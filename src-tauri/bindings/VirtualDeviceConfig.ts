@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type VirtualDeviceConfig = { name: string, vibrators: number, rotators: number, scalar_rotators: number, linears: number, constrict: number, oscillate: number, inflate: number, position: number, battery: boolean, };
@@ -0,0 +1,39 @@
+/*
+ * VibeCheck headless daemon
+ * Usage: vibecheck-headless <config dir> [local api bind (Config.json or 127.0.0.1:9072)]
+ */
+
+use std::{net::SocketAddrV4, str::FromStr};
+
+use log::error as logerr;
+use vibecheck_lib::headless;
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut args = std::env::args().skip(1);
+    let Some(config_dir) = args.next() else {
+        eprintln!("Usage: vibecheck-headless <config dir> [local api bind]");
+        std::process::exit(-1);
+    };
+
+    // The Local API is never exposed beyond this machine
+    let local_api_bind = match args.next() {
+        Some(bind) => match SocketAddrV4::from_str(&bind) {
+            Ok(bind) if bind.ip().is_loopback() => Some(bind),
+            _ => {
+                eprintln!(
+                    "Invalid local api bind (Must be a loopback address): {}",
+                    bind
+                );
+                std::process::exit(-1);
+            }
+        },
+        None => None,
+    };
+
+    if let Err(e) = headless::run(config_dir, local_api_bind) {
+        logerr!("VibeCheck headless failed: {:?}", e);
+        std::process::exit(-1);
+    }
+}
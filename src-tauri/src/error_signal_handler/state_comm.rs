@@ -1,7 +1,9 @@
 use crate::{
     error_signal_handler::VibeCheckError,
     frontend::error::FrontendError,
-    vcore::{errors::VCError, ipc::emit_plane::emit_error, state::VibeCheckState},
+    vcore::{
+        errors::VCError, host::CoreHandle, ipc::emit_plane::emit_error, state::VibeCheckState,
+    },
 };
 use log::error as logerr;
 use parking_lot::lock_api::Mutex;
@@ -9,7 +11,6 @@ use std::sync::{
     mpsc::{Receiver, Sender},
     Arc,
 };
-use tauri::AppHandle;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 /*
@@ -39,7 +40,7 @@ impl ErrorSignalAggregator {
 }*/
 
 pub async fn error_message_handler(
-    app_handle: AppHandle,
+    core_handle: CoreHandle,
     mut error_rx: UnboundedReceiver<VCError>,
 ) {
     loop {
@@ -48,7 +49,7 @@ pub async fn error_message_handler(
                 VCError::HandlingErr(e) => {
                     let s = format!("{}: {}", e.msg, e.id);
                     logerr!("{}", s);
-                    emit_error(&app_handle, FrontendError::Error(s));
+                    emit_error(&core_handle, FrontendError::Error(s));
                 }
             }
         }
@@ -3,26 +3,19 @@
  *
  */
 
-use std::rc::Rc;
-
 use crate::{
-    config::toy::VCToyConfig,
-    frontend::{
-        frontend_types::{
-            FeBrowserLink, FeToyAlter, FeToyEvent, FeVCFeatureType, FeVCToy, FeVibeCheckConfig,
-        },
-        FromFrontend, ToFrontend,
+    frontend::frontend_types::{
+        FeAvatarParameter, FeBrowserLink, FeToyAlter, FeVCFeatureType, FeVCToy, FeVibeCheckConfig,
     },
+    toy_handling::funscript::FunscriptControl,
+    util::virtual_devices::VirtualCommandRecord,
     vcore::{
-        errors::{
-            backend::{self, ToyAlterError},
-            frontend::VCFeError,
-        },
-        ipc::{call_plane, emit_plane::emit_toy_event},
+        errors::{backend, frontend::VCFeError},
+        ipc::call_plane,
         state,
     },
 };
-use log::{error as logerr, trace};
+use log::trace;
 
 /*
  * vibecheck_version
@@ -52,7 +45,7 @@ pub fn vibecheck_version(app_handle: tauri::AppHandle) -> String {
 pub fn vibecheck_enable(vc_state: tauri::State<'_, state::VCStateMutex>) -> Result<(), VCFeError> {
     trace!("vibecheck_enable");
     tauri::async_runtime::block_on(
-        async move { call_plane::native_vibecheck_enable(vc_state).await },
+        async move { call_plane::native_vibecheck_enable(&vc_state).await },
     )
 }
 
@@ -66,7 +59,7 @@ pub fn vibecheck_enable(vc_state: tauri::State<'_, state::VCStateMutex>) -> Resu
 pub fn vibecheck_disable(vc_state: tauri::State<'_, state::VCStateMutex>) -> Result<(), VCFeError> {
     trace!("vibecheck_disable");
     tauri::async_runtime::block_on(
-        async move { call_plane::native_vibecheck_disable(vc_state).await },
+        async move { call_plane::native_vibecheck_disable(&vc_state).await },
     )
 }
 
@@ -82,7 +75,7 @@ pub fn vibecheck_start_bt_scan(
 ) -> Result<(), VCFeError> {
     trace!("vibecheck_start_bt_scan");
     tauri::async_runtime::block_on(async move {
-        call_plane::native_vibecheck_start_bt_scan(vc_state).await
+        call_plane::native_vibecheck_start_bt_scan(&vc_state).await
     })
 }
 
@@ -98,7 +91,7 @@ pub fn vibecheck_stop_bt_scan(
 ) -> Result<(), VCFeError> {
     trace!("vibecheck_stop_bt_scan");
     tauri::async_runtime::block_on(async move {
-        call_plane::native_vibecheck_stop_bt_scan(vc_state).await
+        call_plane::native_vibecheck_stop_bt_scan(&vc_state).await
     })
 }
 
@@ -114,7 +107,7 @@ pub fn vibecheck_stop_bt_scan(
 #[tauri::command(async)]
 pub fn get_vibecheck_config(vc_state: tauri::State<'_, state::VCStateMutex>) -> FeVibeCheckConfig {
     trace!("get_vibecheck_config");
-    call_plane::native_get_vibecheck_config(vc_state)
+    call_plane::native_get_vibecheck_config(&vc_state)
 }
 
 /*
@@ -131,7 +124,13 @@ pub fn set_vibecheck_config(
     fe_vc_config: FeVibeCheckConfig,
 ) -> Result<(), VCFeError> {
     trace!("set_vibecheck_config({:?})", fe_vc_config);
-    call_plane::native_set_vibecheck_config(vc_state, fe_vc_config)
+    let res = call_plane::native_set_vibecheck_config(&vc_state, fe_vc_config);
+
+    let mut vc_lock = vc_state.0.lock();
+    if vc_lock.local_api_outdated() {
+        vc_lock.start_local_api();
+    }
+    res
 }
 
 /*
@@ -143,120 +142,11 @@ pub fn set_vibecheck_config(
 #[tauri::command(async)]
 pub fn alter_toy(
     vc_state: tauri::State<'_, state::VCStateMutex>,
-    app_handle: tauri::AppHandle,
     mutate: FeToyAlter,
 ) -> Result<(), VCFeError> {
     trace!("alter_toy({:#?})", mutate);
 
-    match mutate {
-        // Online toy alter
-        FeToyAlter::Connected(fe_toy) => {
-            if fe_toy.toy_connected {
-                trace!(
-                    "FeToyAlter::Connected: Altering online toy: {}",
-                    fe_toy.toy_name
-                );
-
-                let altered = {
-                    let mut vc_lock = vc_state.0.lock();
-                    if let Some(toy) = vc_lock
-                        .core_toy_manager
-                        .as_mut()
-                        .unwrap()
-                        .online_toys
-                        .get_mut(&fe_toy.toy_id.unwrap())
-                    {
-                        toy.osc_data = fe_toy.osc_data;
-                        toy.bt_update_rate = fe_toy.bt_update_rate;
-
-                        toy.config.as_mut().unwrap().osc_data = fe_toy.osc_data;
-                        toy.config.as_mut().unwrap().bt_update_rate = fe_toy.bt_update_rate;
-                        toy.config
-                            .as_mut()
-                            .unwrap()
-                            .anatomy
-                            .from_fe(fe_toy.toy_anatomy);
-
-                        // Overwrite all features in the state handled toy.
-                        for fe_feature in fe_toy.features {
-                            if !toy.parsed_toy_features.from_frontend(fe_feature.clone()) {
-                                logerr!("Failed to convert FeVCToyFeature to VCToyFeature");
-                                return Err(VCFeError::AlterToyFailure(
-                                    ToyAlterError::NoFeatureIndex,
-                                ));
-                            } else {
-                                // If altering feature map succeeds write the data to the config
-                                toy.config.as_mut().unwrap().features =
-                                    toy.parsed_toy_features.clone();
-                            }
-                        }
-
-                        toy.clone()
-                    } else {
-                        return Err(VCFeError::AlterToyFailure(ToyAlterError::NoToyIndex));
-                    }
-                };
-
-                if call_plane::native_alter_toy(vc_state, app_handle, altered).is_err() {
-                    return Err(VCFeError::AlterToyFailure(ToyAlterError::TMESendFailure));
-                }
-            } else {
-                return Err(VCFeError::AlterToyFailure(ToyAlterError::ToyDisconnected));
-            }
-
-            Ok(())
-        }
-        // Offline toy alter
-        FeToyAlter::Disconnected(mut fe_toy) => {
-            if !fe_toy.toy_connected {
-                trace!(
-                    "FeToyAlter::Disconnected: Altering offline toy: {}",
-                    fe_toy.toy_name
-                );
-                let mut offline_toy_config = match VCToyConfig::load_offline_toy_config(
-                    fe_toy.toy_name.clone(),
-                    &app_handle,
-                ) {
-                    Ok(toy_config) => toy_config,
-                    Err(_e) => {
-                        return Err(VCFeError::AlterToyFailure(
-                            ToyAlterError::OfflineToyNotExist,
-                        ))
-                    }
-                };
-
-                offline_toy_config.osc_data = fe_toy.osc_data;
-                offline_toy_config.bt_update_rate = fe_toy.bt_update_rate;
-                offline_toy_config.anatomy.from_fe(fe_toy.toy_anatomy);
-
-                for f in fe_toy.features {
-                    if !offline_toy_config.features.from_frontend(f) {
-                        return Err(VCFeError::AlterToyFailure(
-                            ToyAlterError::OfflineToyNoFeatureIndex,
-                        ));
-                    }
-                }
-
-                fe_toy.features = offline_toy_config.features.features.to_frontend();
-                fe_toy.osc_data = offline_toy_config.osc_data;
-                fe_toy.bt_update_rate = offline_toy_config.bt_update_rate;
-                fe_toy.toy_anatomy = offline_toy_config.anatomy.to_fe();
-
-                if offline_toy_config
-                    .save_offline_toy_config(&app_handle)
-                    .is_err()
-                {
-                    return Err(VCFeError::SaveOfflineToyConfig);
-                }
-
-                emit_toy_event(&Rc::new(app_handle), FeToyEvent::Update(fe_toy));
-            } else {
-                return Err(VCFeError::AlterToyFailure(ToyAlterError::ToyConnected));
-            }
-
-            Ok(())
-        }
-    }
+    call_plane::native_fe_alter_toy(&vc_state, mutate)
 }
 
 /*
@@ -300,6 +190,7 @@ pub fn clear_osc_config() -> Result<(), backend::VibeCheckFSError> {
 /*
  * Injects motor test values into a device feature directly.
  * Args: toy_id: u32, toy_sub_id: u8, feature_index: u32, float_level: f64, stop: bool
+ * Return: Option<f64> level sent after the feature's response curve and range
  */
 #[tauri::command(async)]
 pub fn simulate_device_feature(
@@ -309,10 +200,10 @@ pub fn simulate_device_feature(
     feature_type: FeVCFeatureType,
     float_level: f64,
     stop: bool,
-) {
+) -> Option<f64> {
     trace!("simulate_device_feature");
     call_plane::native_simulate_device_feature(
-        vc_state,
+        &vc_state,
         toy_id,
         feature_index,
         feature_type,
@@ -343,29 +234,19 @@ pub fn sync_offline_toys(
 ) -> Result<Vec<FeVCToy>, VCFeError> {
     trace!("sync_offline_toys");
 
-    if let Some(toy_manager) = vc_state.0.lock().core_toy_manager.as_mut() {
-        match toy_manager.sync_frontend(refresh_toys) {
-            Ok(toys) => Ok(toys),
-            Err(e) => {
-                logerr!("Toy Manager failed to sync frontend: {}", e);
-                Err(VCFeError::ToyManager(e))
-            }
-        }
-    } else {
-        Err(VCFeError::ToyManagerNotReady)
-    }
+    call_plane::native_sync_offline_toys(&vc_state, refresh_toys)
 }
 
 #[tauri::command(async)]
 pub fn osc_query_start(vc_state: tauri::State<'_, state::VCStateMutex>) -> Result<(), VCFeError> {
     trace!("osc_query_start");
-    call_plane::native_osc_query_start(vc_state)
+    call_plane::native_osc_query_start(&vc_state)
 }
 
 #[tauri::command(async)]
 pub fn osc_query_stop(vc_state: tauri::State<'_, state::VCStateMutex>) -> Result<(), VCFeError> {
     trace!("osc_query_stop");
-    call_plane::native_osc_query_stop(vc_state)
+    call_plane::native_osc_query_stop(&vc_state)
 }
 
 #[tauri::command(async)]
@@ -373,5 +254,133 @@ pub fn osc_query_attempt_force_connect(
     vc_state: tauri::State<'_, state::VCStateMutex>,
 ) -> Result<(), VCFeError> {
     trace!("osc_query_attempt_force_connect");
-    call_plane::native_osc_query_attempt_force(vc_state)
+    call_plane::native_osc_query_attempt_force(&vc_state)
+}
+
+/*
+ * get_avatar_parameters
+ * Finds VRChat over OSCQuery and reads the current avatar's parameters
+ * Args: None
+ * Return: Result<Vec<FeAvatarParameter>, VCFeError>
+ */
+#[tauri::command]
+pub async fn get_avatar_parameters() -> Result<Vec<FeAvatarParameter>, VCFeError> {
+    trace!("get_avatar_parameters");
+    call_plane::native_get_avatar_parameters().await
+}
+
+/*
+ * get_virtual_device_commands
+ * Takes the commands virtual devices have received since the last call
+ * Args: VibeCheck State
+ * Return: Vec<VirtualCommandRecord>
+ */
+#[tauri::command(async)]
+pub fn get_virtual_device_commands(
+    vc_state: tauri::State<'_, state::VCStateMutex>,
+) -> Vec<VirtualCommandRecord> {
+    trace!("get_virtual_device_commands");
+    vc_state.0.lock().virtual_command_log.drain()
+}
+
+/*
+ * emergency_stop
+ * Stops every toy and blocks toy commands until re-armed
+ * Args: VibeCheck State
+ * Return: None
+ */
+#[tauri::command]
+pub fn emergency_stop(vc_state: tauri::State<'_, state::VCStateMutex>) {
+    trace!("emergency_stop");
+    call_plane::native_emergency_stop(&vc_state)
+}
+
+/*
+ * emergency_rearm
+ * Allows toy commands again after an emergency stop
+ * Args: VibeCheck State
+ * Return: None
+ */
+#[tauri::command]
+pub fn emergency_rearm(vc_state: tauri::State<'_, state::VCStateMutex>) {
+    trace!("emergency_rearm");
+    call_plane::native_emergency_rearm(&vc_state)
+}
+
+/*
+ * is_emergency_stopped
+ * Args: VibeCheck State
+ * Return: bool
+ */
+#[tauri::command]
+pub fn is_emergency_stopped(vc_state: tauri::State<'_, state::VCStateMutex>) -> bool {
+    trace!("is_emergency_stopped");
+    call_plane::native_is_emergency_stopped(&vc_state)
+}
+
+/*
+ * get_avatar_id
+ * Args: VibeCheck State
+ * Return: Option<String>
+ */
+#[tauri::command]
+pub fn get_avatar_id(vc_state: tauri::State<'_, state::VCStateMutex>) -> Option<String> {
+    trace!("get_avatar_id");
+    call_plane::native_get_avatar_id(&vc_state)
+}
+
+/*
+ * copy_avatar_profile
+ * Copies the toy profiles of one avatar to another for every online toy
+ * Args: VibeCheck State, from avatar ID (None = default profile), to avatar ID
+ * Return: Result<(), VCFeError>
+ */
+#[tauri::command]
+pub fn copy_avatar_profile(
+    vc_state: tauri::State<'_, state::VCStateMutex>,
+    from_avatar_id: Option<String>,
+    to_avatar_id: String,
+) -> Result<(), VCFeError> {
+    trace!("copy_avatar_profile");
+    call_plane::native_copy_avatar_profile(&vc_state, from_avatar_id, to_avatar_id)
+}
+
+/*
+ * get_patterns
+ * Args: VibeCheck State
+ * Return: Result<Vec<String>, VCFeError>
+ */
+#[tauri::command(async)]
+pub fn get_patterns(
+    vc_state: tauri::State<'_, state::VCStateMutex>,
+) -> Result<Vec<String>, VCFeError> {
+    trace!("get_patterns");
+    call_plane::native_get_patterns(&vc_state)
+}
+
+/*
+ * get_funscripts
+ * Args: VibeCheck State
+ * Return: Result<Vec<String>, VCFeError>
+ */
+#[tauri::command(async)]
+pub fn get_funscripts(
+    vc_state: tauri::State<'_, state::VCStateMutex>,
+) -> Result<Vec<String>, VCFeError> {
+    trace!("get_funscripts");
+    call_plane::native_get_funscripts(&vc_state)
+}
+
+/*
+ * funscript_control
+ * Args: VibeCheck State, FunscriptControl
+ * Return: Result<(), VCFeError>
+ */
+#[tauri::command]
+pub fn funscript_control(
+    vc_state: tauri::State<'_, state::VCStateMutex>,
+    control: FunscriptControl,
+) -> Result<(), VCFeError> {
+    trace!("funscript_control");
+    call_plane::native_funscript_control(&vc_state, control)
 }
@@ -5,11 +5,15 @@ use serde::{Deserialize, Serialize};
 use strum::Display;
 use ts_rs::TS;
 
+use crate::osc::{forwarding::OscForwardTarget, level_output::OscLevelOutputConfig};
 use crate::toy_handling::{
     input_processor::penetration_systems::PenetrationSystemType,
-    toyops::{ProcessingMode, VCFeatureType},
+    modulation::Modulation,
+    safety::SafetyConfig,
+    toyops::{IntMapping, ParameterBlendMode, ProcessingMode, ResponseCurve, VCFeatureType},
     ToyPower,
 };
+use crate::vcore::config::app::ButtplugConnectorConfig;
 
 use super::ToBackend;
 
@@ -22,6 +26,9 @@ pub struct FeVibeCheckConfig {
     pub desktop_notifications: bool,
     pub show_toy_advanced: bool,
     pub show_feature_advanced: bool,
+    pub bp_connector: ButtplugConnectorConfig,
+    pub local_api: FeLocalApi,
+    pub safety: SafetyConfig,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, TS)]
@@ -30,6 +37,15 @@ pub struct FeOSCNetworking {
     pub bind: String,
     pub remote: String,
     pub osc_query_enabled: bool,
+    pub level_output: OscLevelOutputConfig,
+    pub forwarding: Vec<OscForwardTarget>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct FeLocalApi {
+    pub enabled: bool,
+    pub bind: String,
 }
 
 #[derive(Serialize, Clone, TS, Display)]
@@ -62,6 +78,55 @@ pub enum FeStateEvent {
 pub enum FeCoreEvent {
     Scan(FeScanEvent),
     State(FeStateEvent),
+    // Emergency stop latched (true) or re-armed (false)
+    EmergencyStop(bool),
+    Safety(FeSafetyEvent),
+}
+
+#[derive(Serialize, Clone, TS)]
+#[ts(export)]
+pub struct FeSafetyEvent {
+    pub toy_name: String,
+    pub limit: FeSafetyLimit,
+}
+
+#[derive(Serialize, Clone, Debug, TS)]
+#[ts(export)]
+pub enum FeSafetyLimit {
+    Ceiling,
+    SlewLimit,
+    // Forced idle for the cooldown minutes
+    RunCap(u32),
+    CooldownOver,
+}
+
+// A parameter of the current avatar read from VRChat's OSCQuery service
+#[derive(Serialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct FeAvatarParameter {
+    pub address: String,
+    pub param_type: FeAvatarParameterType,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, TS)]
+#[ts(export)]
+pub enum FeAvatarParameterType {
+    Float,
+    Int,
+    Bool,
+    Unknown,
+}
+
+impl FeAvatarParameterType {
+    // OSC type tag of an OSCQuery node
+    pub fn from_osc_type(osc_type: &str) -> Self {
+        match osc_type {
+            "f" => Self::Float,
+            "i" => Self::Int,
+            "T" | "F" => Self::Bool,
+            _ => Self::Unknown,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, TS)]
@@ -116,9 +181,11 @@ pub struct FeVCToy {
     pub osc_data: bool,
     pub bt_update_rate: u64,
     pub sub_id: u8,
+    // Replaces the global safety limits for this toy
+    pub safety: Option<SafetyConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Copy, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[ts(export)]
 pub struct FeLevelTweaks {
     pub minimum_level: f64,
@@ -126,8 +193,21 @@ pub struct FeLevelTweaks {
     pub idle_level: f64,
     pub smooth_rate: f64,
     pub linear_position_speed: u32,
-    pub rate_tune: f64,
+    pub rate_decay_per_second: f64,
     pub constant_level: f64,
+    pub smooth_half_life_ms: f64,
+    pub envelope_attack_ms: f64,
+    pub envelope_decay_ms: f64,
+    pub envelope_sustain_level: f64,
+    pub envelope_release_ms: f64,
+    pub gate_lower_threshold: f64,
+    pub gate_upper_threshold: f64,
+    pub gate_passthrough: bool,
+    pub gate_fixed_level: f64,
+    pub gate_min_on_ms: f64,
+    pub gate_min_off_ms: f64,
+    pub response_curve: ResponseCurve,
+    pub modulation: Modulation,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
@@ -137,6 +217,11 @@ pub enum FeProcessingMode {
     Smooth,
     Rate,
     Constant,
+    Pattern,
+    Ema,
+    Damped,
+    Envelope,
+    Gate,
 }
 
 impl ToBackend<ProcessingMode> for FeProcessingMode {
@@ -148,6 +233,11 @@ impl ToBackend<ProcessingMode> for FeProcessingMode {
             Self::Smooth => ProcessingMode::Smooth,
             Self::Rate => ProcessingMode::Rate,
             Self::Constant => ProcessingMode::Constant,
+            Self::Pattern => ProcessingMode::Pattern,
+            Self::Ema => ProcessingMode::Ema,
+            Self::Damped => ProcessingMode::Damped,
+            Self::Envelope => ProcessingMode::Envelope,
+            Self::Gate => ProcessingMode::Gate,
         }
     }
 }
@@ -157,6 +247,15 @@ impl ToBackend<ProcessingMode> for FeProcessingMode {
 pub struct FeToyParameter {
     pub parameter: String,
     pub processing_mode: FeProcessingMode,
+    #[serde(default)]
+    #[ts(optional)]
+    pub int_mapping: Option<IntMapping>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub pattern: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
@@ -184,6 +283,7 @@ pub struct FeVCToyFeature {
     pub feature_enabled: bool,
     pub feature_type: FeVCFeatureType,
     pub osc_parameters: Vec<FeToyParameter>,
+    pub parameter_blend: ParameterBlendMode,
     pub penetration_system: FePenetrationSystem,
     pub feature_index: u32,
     pub flip_input_float: bool,
@@ -0,0 +1,77 @@
+/*
+ * Hosts the core inside the Tauri app
+ * Events go to the webview and notifications to the desktop
+ */
+
+use log::error as logerr;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    frontend::{
+        error::FrontendError,
+        frontend_types::{FeCoreEvent, FeToyEvent},
+    },
+    util::errors::UtilError,
+    vcore::{host::VibeCheckHost, state::VCStateMutex},
+};
+
+pub struct TauriHost(AppHandle);
+
+impl TauriHost {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self(app_handle.clone())
+    }
+}
+
+impl VibeCheckHost for TauriHost {
+    fn config_dir(&self) -> Result<String, UtilError> {
+        let pb = match self.0.path().app_config_dir() {
+            Ok(path) => path,
+            Err(_) => return Err(UtilError::ConfigDirFS),
+        };
+
+        match pb.to_str() {
+            Some(config_dir) => Ok(config_dir.to_string()),
+            None => Err(UtilError::ConfigDirFS),
+        }
+    }
+
+    fn state(&self) -> &VCStateMutex {
+        self.0.state::<VCStateMutex>().inner()
+    }
+
+    fn emit_toy_event(&self, event: FeToyEvent) {
+        match self.0.emit("fe_toy_event", &event) {
+            Ok(()) => (),
+            Err(e) => logerr!("Emit Toy Event [{}] failed: {}", event, e),
+        }
+    }
+
+    fn emit_core_event(&self, event: FeCoreEvent) {
+        match self.0.emit("fe_core_event", &event) {
+            Ok(()) => (),
+            Err(e) => logerr!("Emit Core Event [{}] failed: {}", event, e),
+        }
+    }
+
+    fn emit_error(&self, event: FrontendError) {
+        match self.0.emit("fe_error", &event) {
+            Ok(()) => (),
+            Err(e) => logerr!("Emit Error Event [{}] failed: {}", event, e),
+        }
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        if let Err(e) = self
+            .0
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+        {
+            logerr!("Failed to show notification: {}", e);
+        }
+    }
+}
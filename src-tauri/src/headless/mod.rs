@@ -0,0 +1,151 @@
+/*
+ * Headless daemon
+ * Runs the core without the Tauri webview from a config directory.
+ * Controlled through the OSC API and the Local API, which is always served.
+ */
+
+use std::{net::SocketAddrV4, sync::Arc, thread};
+
+use futures::executor::block_on;
+use log::{error as logerr, info, trace, warn};
+use parking_lot::Mutex;
+
+use crate::{
+    error_signal_handler::VibeCheckError,
+    frontend::{
+        error::FrontendError,
+        frontend_types::{FeCoreEvent, FeStateEvent, FeToyEvent},
+    },
+    util::errors::UtilError,
+    vcore::{
+        config::app::{config_load, VibeCheckConfig},
+        errors::{backend::VibeCheckConfigError, frontend::VCFeError},
+        host::{CoreHandle, VibeCheckHost},
+        ipc::call_plane,
+        state::{RunningState, VCStateMutex, VibeCheckState},
+    },
+};
+
+pub const HEADLESS_IDENTIFIER: &str = "com.vibecheck.headless";
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    Config(VibeCheckConfigError),
+    Core(VibeCheckError),
+    Signal(std::io::Error),
+}
+
+pub struct HeadlessHost {
+    config_dir: String,
+    state: VCStateMutex,
+}
+
+impl VibeCheckHost for HeadlessHost {
+    fn config_dir(&self) -> Result<String, UtilError> {
+        Ok(self.config_dir.clone())
+    }
+
+    fn state(&self) -> &VCStateMutex {
+        &self.state
+    }
+
+    fn emit_toy_event(&self, event: FeToyEvent) {
+        trace!("Toy event: {}", event);
+    }
+
+    fn emit_core_event(&self, event: FeCoreEvent) {
+        trace!("Core event: {}", event);
+
+        // No frontend to react to state events so handle them here
+        // Runs on its own thread since enabling stops the listener that may have sent this
+        if let FeCoreEvent::State(state_event) = event {
+            let vc_state = VCStateMutex(self.state.0.clone());
+            thread::spawn(move || {
+                let res = match state_event {
+                    FeStateEvent::EnableAndScan => enable_and_scan(&vc_state),
+                    FeStateEvent::Disable => stop_scan_and_disable(&vc_state),
+                };
+                if let Err(e) = res {
+                    logerr!(
+                        "Failed to handle state event: {}",
+                        serde_json::to_string(&e).unwrap_or_default()
+                    );
+                }
+            });
+        }
+    }
+
+    fn emit_error(&self, event: FrontendError) {
+        warn!("{:?}", event);
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        info!("{}: {}", title, body);
+    }
+}
+
+pub fn enable_and_scan(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
+    block_on(call_plane::native_vibecheck_enable(vc_state))?;
+    block_on(call_plane::native_vibecheck_start_bt_scan(vc_state))
+}
+
+pub fn stop_scan_and_disable(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
+    // Not scanning is fine
+    let _ = block_on(call_plane::native_vibecheck_stop_bt_scan(vc_state));
+    block_on(call_plane::native_vibecheck_disable(vc_state))
+}
+
+/*
+ * Starts the core from the given config directory with the Local API enabled
+ * Blocks until Ctrl-C, then disables and returns
+ */
+pub fn run(config_dir: String, local_api_bind: Option<SocketAddrV4>) -> Result<(), HeadlessError> {
+    let vibecheck_state_pointer =
+        Arc::new(Mutex::new(VibeCheckState::new(VibeCheckConfig::default())));
+    trace!("VibeCheckState created");
+
+    let core_handle = CoreHandle::new(HeadlessHost {
+        config_dir,
+        state: VCStateMutex(vibecheck_state_pointer.clone()),
+    });
+
+    let async_rt = {
+        let mut vc_state = vibecheck_state_pointer.lock();
+
+        vc_state.config = config_load(&core_handle).map_err(HeadlessError::Config)?;
+        // The Local API is the only way to configure the daemon
+        vc_state.config.local_api.enabled = true;
+        if let Some(bind) = local_api_bind {
+            vc_state.config.local_api.bind = bind;
+        }
+
+        vc_state.set_state_pointer(vibecheck_state_pointer.clone());
+        trace!("State pointer set");
+        vc_state.set_core_handle(&core_handle);
+        trace!("Core handle set");
+        vc_state.identifier = HEADLESS_IDENTIFIER.to_string();
+
+        vc_state.start_core().map_err(HeadlessError::Core)?;
+        vc_state.async_rt.handle().clone()
+    };
+    info!("VibeCheck core started");
+
+    async_rt
+        .block_on(tokio::signal::ctrl_c())
+        .map_err(HeadlessError::Signal)?;
+    info!("VibeCheck headless shutting down");
+
+    let running = matches!(
+        vibecheck_state_pointer.lock().running,
+        RunningState::Running
+    );
+    if running {
+        if let Err(e) = stop_scan_and_disable(&VCStateMutex(vibecheck_state_pointer)) {
+            logerr!(
+                "Failed to disable: {}",
+                serde_json::to_string(&e).unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+/*
+ * Local API commands
+ * Requests: {"id": <any>, "command": <frontend command name>, "args": {<frontend command args>}}
+ * Replies: {"id": <request id>, "ok": <command result>} or {"id": <request id>, "error": <error>}
+ */
+
+use futures::executor::block_on;
+use log::trace;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    frontend::frontend_types::{FeToyAlter, FeVCFeatureType, FeVibeCheckConfig},
+    toy_handling::funscript::FunscriptControl,
+    vcore::{ipc::call_plane, state::VCStateMutex},
+};
+
+/*
+ * Mirrors the Tauri commands in frontend_native
+ * Argument names are the camelCase names the frontend invokes them with
+ */
+#[derive(Deserialize, Debug)]
+#[serde(tag = "command", content = "args", rename_all = "snake_case")]
+pub enum ApiCommand {
+    VibecheckVersion,
+    VibecheckEnable,
+    VibecheckDisable,
+    VibecheckStartBtScan,
+    VibecheckStopBtScan,
+    GetVibecheckConfig,
+    #[serde(rename_all = "camelCase")]
+    SetVibecheckConfig {
+        fe_vc_config: FeVibeCheckConfig,
+    },
+    AlterToy {
+        mutate: FeToyAlter,
+    },
+    ClearOscConfig,
+    #[serde(rename_all = "camelCase")]
+    SimulateDeviceFeature {
+        toy_id: u32,
+        feature_index: u32,
+        feature_type: FeVCFeatureType,
+        float_level: f64,
+        stop: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    SyncOfflineToys {
+        refresh_toys: bool,
+    },
+    OscQueryStart,
+    OscQueryStop,
+    OscQueryAttemptForceConnect,
+    GetAvatarParameters,
+    GetVirtualDeviceCommands,
+    EmergencyStop,
+    EmergencyRearm,
+    IsEmergencyStopped,
+    GetAvatarId,
+    #[serde(rename_all = "camelCase")]
+    CopyAvatarProfile {
+        from_avatar_id: Option<String>,
+        to_avatar_id: String,
+    },
+    GetPatterns,
+    GetFunscripts,
+    FunscriptControl {
+        control: FunscriptControl,
+    },
+}
+
+fn to_reply<T: Serialize, E: Serialize>(result: Result<T, E>) -> Result<Value, Value> {
+    match result {
+        Ok(ok) => Ok(serde_json::to_value(ok).unwrap_or(Value::Null)),
+        Err(e) => Err(serde_json::to_value(e).unwrap_or(Value::Null)),
+    }
+}
+
+/*
+ * Runs one command
+ * Blocks on async commands so call from a blocking thread
+ */
+pub fn run_command(vc_state: &VCStateMutex, command: ApiCommand) -> Result<Value, Value> {
+    trace!("Local API command: {:?}", command);
+    match command {
+        ApiCommand::VibecheckVersion => Ok(json!(format!(
+            "{} {}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS
+        ))),
+        ApiCommand::VibecheckEnable => {
+            to_reply(block_on(call_plane::native_vibecheck_enable(vc_state)))
+        }
+        ApiCommand::VibecheckDisable => {
+            to_reply(block_on(call_plane::native_vibecheck_disable(vc_state)))
+        }
+        ApiCommand::VibecheckStartBtScan => to_reply(block_on(
+            call_plane::native_vibecheck_start_bt_scan(vc_state),
+        )),
+        ApiCommand::VibecheckStopBtScan => to_reply(block_on(
+            call_plane::native_vibecheck_stop_bt_scan(vc_state),
+        )),
+        ApiCommand::GetVibecheckConfig => to_reply(Ok::<_, ()>(
+            call_plane::native_get_vibecheck_config(vc_state),
+        )),
+        ApiCommand::SetVibecheckConfig { fe_vc_config } => to_reply(
+            call_plane::native_set_vibecheck_config(vc_state, fe_vc_config),
+        ),
+        ApiCommand::AlterToy { mutate } => {
+            to_reply(call_plane::native_fe_alter_toy(vc_state, mutate))
+        }
+        ApiCommand::ClearOscConfig => to_reply(call_plane::native_clear_osc_config()),
+        ApiCommand::SimulateDeviceFeature {
+            toy_id,
+            feature_index,
+            feature_type,
+            float_level,
+            stop,
+        } => Ok(
+            serde_json::to_value(call_plane::native_simulate_device_feature(
+                vc_state,
+                toy_id,
+                feature_index,
+                feature_type,
+                float_level,
+                stop,
+            ))
+            .unwrap_or(Value::Null),
+        ),
+        ApiCommand::SyncOfflineToys { refresh_toys } => {
+            to_reply(call_plane::native_sync_offline_toys(vc_state, refresh_toys))
+        }
+        ApiCommand::OscQueryStart => to_reply(call_plane::native_osc_query_start(vc_state)),
+        ApiCommand::OscQueryStop => to_reply(call_plane::native_osc_query_stop(vc_state)),
+        ApiCommand::OscQueryAttemptForceConnect => {
+            to_reply(call_plane::native_osc_query_attempt_force(vc_state))
+        }
+        ApiCommand::GetAvatarParameters => {
+            to_reply(block_on(call_plane::native_get_avatar_parameters()))
+        }
+        ApiCommand::GetVirtualDeviceCommands => {
+            to_reply(Ok::<_, ()>(vc_state.0.lock().virtual_command_log.drain()))
+        }
+        ApiCommand::EmergencyStop => {
+            call_plane::native_emergency_stop(vc_state);
+            Ok(Value::Null)
+        }
+        ApiCommand::EmergencyRearm => {
+            call_plane::native_emergency_rearm(vc_state);
+            Ok(Value::Null)
+        }
+        ApiCommand::IsEmergencyStopped => {
+            Ok(json!(call_plane::native_is_emergency_stopped(vc_state)))
+        }
+        ApiCommand::GetAvatarId => Ok(json!(call_plane::native_get_avatar_id(vc_state))),
+        ApiCommand::CopyAvatarProfile {
+            from_avatar_id,
+            to_avatar_id,
+        } => to_reply(call_plane::native_copy_avatar_profile(
+            vc_state,
+            from_avatar_id,
+            to_avatar_id,
+        )),
+        ApiCommand::GetPatterns => to_reply(call_plane::native_get_patterns(vc_state)),
+        ApiCommand::GetFunscripts => to_reply(call_plane::native_get_funscripts(vc_state)),
+        ApiCommand::FunscriptControl { control } => {
+            to_reply(call_plane::native_funscript_control(vc_state, control))
+        }
+    }
+}
+
+/*
+ * Parses and runs one request
+ * Returns the reply to send back
+ */
+pub fn handle_request(vc_state: &VCStateMutex, request: &str) -> String {
+    let mut request = match serde_json::from_str::<Value>(request) {
+        Ok(Value::Object(request)) => request,
+        Ok(_) => {
+            return json!({ "id": null, "error": "Request must be a JSON object" }).to_string()
+        }
+        Err(e) => {
+            return json!({ "id": null, "error": format!("Invalid JSON: {}", e) }).to_string()
+        }
+    };
+
+    let id = request.remove("id").unwrap_or(Value::Null);
+
+    let reply = match serde_json::from_value::<ApiCommand>(Value::Object(request)) {
+        Ok(command) => match run_command(vc_state, command) {
+            Ok(ok) => json!({ "id": id, "ok": ok }),
+            Err(e) => json!({ "id": id, "error": e }),
+        },
+        Err(e) => json!({ "id": id, "error": format!("Invalid command: {}", e) }),
+    };
+    reply.to_string()
+}
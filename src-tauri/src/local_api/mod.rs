@@ -0,0 +1,39 @@
+/*
+ * Local API
+ * Opt-in WebSocket JSON API on localhost for stream decks, overlays and scripts.
+ * Takes the same commands as the frontend and streams the same events.
+ */
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::frontend_types::FeLocalApi;
+
+pub mod commands;
+pub mod server;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LocalApiConfig {
+    pub enabled: bool,
+    // Only loopback addresses are accepted
+    pub bind: SocketAddrV4,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 9072),
+        }
+    }
+}
+
+impl LocalApiConfig {
+    pub fn to_fe(&self) -> FeLocalApi {
+        FeLocalApi {
+            enabled: self.enabled,
+            bind: self.bind.to_string(),
+        }
+    }
+}
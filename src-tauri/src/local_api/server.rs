@@ -0,0 +1,169 @@
+/*
+ * Local API WebSocket server
+ * Every client gets command replies and a copy of every event the host receives.
+ */
+
+use std::{
+    net::{SocketAddr, SocketAddrV4},
+    sync::Arc,
+};
+
+use futures::{SinkExt, StreamExt};
+use log::{error as logerr, info, trace, warn};
+use parking_lot::Mutex;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast::error::RecvError, mpsc::unbounded_channel},
+    task::JoinSet,
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::StatusCode,
+        Message,
+    },
+};
+
+use crate::{
+    frontend::error::FrontendError,
+    local_api::commands::handle_request,
+    vcore::{
+        host::CoreHandle,
+        ipc::emit_plane::emit_error,
+        state::{VCStateMutex, VibeCheckState},
+    },
+};
+
+/*
+ * Browsers always send an Origin header and any page could open a socket to localhost
+ * Only clients that aren't web pages are accepted
+ */
+#[allow(clippy::result_large_err)]
+fn reject_browser_origin(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    if request.headers().contains_key("Origin") {
+        warn!("Local API rejected a connection from a web page");
+        let mut error_response = ErrorResponse::new(Some(
+            "Connections from web pages are not allowed".to_string(),
+        ));
+        *error_response.status_mut() = StatusCode::FORBIDDEN;
+        return Err(error_response);
+    }
+    Ok(response)
+}
+
+async fn local_api_client(
+    stream: TcpStream,
+    peer: SocketAddr,
+    vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>,
+    core_handle: CoreHandle,
+) {
+    let ws_stream = match accept_hdr_async(stream, reject_browser_origin).await {
+        Ok(ws_stream) => ws_stream,
+        Err(e) => {
+            warn!("Local API handshake with {} failed: {}", peer, e);
+            return;
+        }
+    };
+    info!("Local API client connected: {}", peer);
+
+    let (mut ws_tx, mut ws_rx) = ws_stream.split();
+    let mut events = core_handle.subscribe();
+    // Replies and whether the Local API has to restart once the reply is sent
+    let (reply_tx, mut reply_rx) = unbounded_channel::<(String, bool)>();
+
+    loop {
+        let (outgoing, restart_local_api) = tokio::select! {
+            msg = ws_rx.next() => match msg {
+                Some(Ok(Message::Text(request))) => {
+                    // Commands lock the state and may block on the Buttplug client
+                    let request = request.to_string();
+                    let vc_state = VCStateMutex(vibecheck_state_pointer.clone());
+                    let reply_tx = reply_tx.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let reply = handle_request(&vc_state, &request);
+                        let restart_local_api = vc_state.0.lock().local_api_outdated();
+                        let _ = reply_tx.send((reply, restart_local_api));
+                    });
+                    continue;
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    warn!("Local API client {} error: {}", peer, e);
+                    break;
+                }
+            },
+            Some(reply) = reply_rx.recv() => reply,
+            event = events.recv() => match event {
+                Ok(event) => match serde_json::to_string(&event) {
+                    Ok(event) => (event, false),
+                    Err(e) => {
+                        logerr!("Failed to serialize event for Local API: {}", e);
+                        continue;
+                    }
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Local API client {} missed {} events", peer, skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        trace!("Local API -> {}: {}", peer, outgoing);
+        if ws_tx.send(Message::text(outgoing)).await.is_err() {
+            break;
+        }
+
+        // The reply is flushed, restarting drops this client with the old server
+        if restart_local_api {
+            let vc_state = VCStateMutex(vibecheck_state_pointer.clone());
+            tokio::task::spawn_blocking(move || {
+                let mut vc_lock = vc_state.0.lock();
+                if vc_lock.local_api_outdated() {
+                    vc_lock.start_local_api();
+                }
+            });
+        }
+    }
+    info!("Local API client disconnected: {}", peer);
+}
+
+pub async fn local_api_listen(
+    bind: SocketAddrV4,
+    vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>,
+    core_handle: CoreHandle,
+) {
+    let listener = match TcpListener::bind(bind).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            logerr!("Local API failed to bind {}: {}", bind, e);
+            emit_error(
+                &core_handle,
+                FrontendError::Error(format!("Local API failed to bind {}: {}", bind, e)),
+            );
+            return;
+        }
+    };
+    info!("Local API listening on ws://{}", bind);
+
+    // Clients are dropped with the listener when the API is disabled
+    let mut clients = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    clients.spawn(local_api_client(
+                        stream,
+                        peer,
+                        vibecheck_state_pointer.clone(),
+                        core_handle.clone(),
+                    ));
+                }
+                Err(e) => warn!("Local API failed to accept a connection: {}", e),
+            },
+            Some(_) = clients.join_next() => (),
+        }
+    }
+}
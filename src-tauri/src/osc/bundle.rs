@@ -0,0 +1,120 @@
+use std::time::{Duration, SystemTime};
+
+use log::{trace, warn};
+use rosc::{OscBundle, OscMessage, OscPacket};
+
+/*
+ * OSC Bundle handling
+ * Bundles (and nested bundles) are unpacked into their messages.
+ * Messages from bundles with a future timetag are held by the scheduler until they are due.
+ */
+
+// Upper bound on held messages so a misbehaving sender can't grow the queue forever
+const MAX_SCHEDULED_MESSAGES: usize = 4096;
+
+// A timetag of 0x00000000_00000001 means "apply immediately"
+fn bundle_is_immediate(bundle: &OscBundle) -> bool {
+    bundle.timetag.seconds == 0 && bundle.timetag.fractional == 1
+}
+
+fn unpack_into(
+    packet: OscPacket,
+    enclosing_time: Option<SystemTime>,
+    out: &mut Vec<(Option<SystemTime>, OscMessage)>,
+) {
+    match packet {
+        OscPacket::Message(msg) => out.push((enclosing_time, msg)),
+        OscPacket::Bundle(bundle) => {
+            let bundle_time = if bundle_is_immediate(&bundle) {
+                None
+            } else {
+                Some(SystemTime::from(bundle.timetag))
+            };
+
+            // A nested bundle can't be applied before the bundle that contains it
+            let due_time = match (bundle_time, enclosing_time) {
+                (Some(bundle_time), Some(enclosing_time)) => Some(bundle_time.max(enclosing_time)),
+                (bundle_time, enclosing_time) => bundle_time.or(enclosing_time),
+            };
+
+            for content in bundle.content {
+                unpack_into(content, due_time, out);
+            }
+        }
+    }
+}
+
+/*
+ * Recursively unpacks an OSC packet into its messages.
+ * Each message is paired with the time it should be applied (None = immediately).
+ */
+pub fn unpack_osc_packet(packet: OscPacket) -> Vec<(Option<SystemTime>, OscMessage)> {
+    let mut out = Vec::new();
+    unpack_into(packet, None, &mut out);
+    out
+}
+
+#[derive(Default)]
+pub struct OscBundleScheduler {
+    // Sorted by due time (earliest first)
+    scheduled: Vec<(SystemTime, OscMessage)>,
+}
+
+impl OscBundleScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn schedule(&mut self, due: SystemTime, msg: OscMessage) {
+        if self.scheduled.len() >= MAX_SCHEDULED_MESSAGES {
+            warn!(
+                "OSC bundle scheduler is full! Dropping scheduled message: {}",
+                msg.addr
+            );
+            return;
+        }
+
+        // Insert after any message due at the same time to keep bundle ordering
+        let position = self.scheduled.partition_point(|(t, _)| *t <= due);
+        self.scheduled.insert(position, (due, msg));
+    }
+
+    /*
+     * Unpacks a received packet.
+     * Returns the messages that should be applied now and holds any future dated messages.
+     */
+    pub fn unpack(&mut self, packet: OscPacket) -> Vec<OscMessage> {
+        let now = SystemTime::now();
+        let mut immediate = Vec::new();
+
+        for (due, msg) in unpack_osc_packet(packet) {
+            match due {
+                Some(due) if due > now => {
+                    trace!("Scheduling bundled OSC message: {}", msg.addr);
+                    self.schedule(due, msg);
+                }
+                _ => immediate.push(msg),
+            }
+        }
+
+        immediate
+    }
+
+    // Takes every held message that is now due
+    pub fn pop_due(&mut self) -> Vec<OscMessage> {
+        let now = SystemTime::now();
+        let due_count = self.scheduled.partition_point(|(t, _)| *t <= now);
+        self.scheduled
+            .drain(..due_count)
+            .map(|(_, msg)| msg)
+            .collect()
+    }
+
+    // Time until the earliest held message is due
+    pub fn time_until_next(&self) -> Option<Duration> {
+        self.scheduled.first().map(|(due, _)| {
+            due.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        })
+    }
+}
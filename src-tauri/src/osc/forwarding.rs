@@ -0,0 +1,212 @@
+/*
+ * OSC forwarding
+ * Copies every packet VibeCheck receives to other OSC apps so VibeCheck can sit first in an OSC chain.
+ */
+
+use std::{
+    net::{Ipv4Addr, SocketAddrV4, UdpSocket},
+    str::FromStr,
+};
+
+use log::{info, warn};
+use rosc::OscPacket;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub struct OscForwardTarget {
+    // IP:PORT the packets are copied to
+    pub target: String,
+    // Only packets with an address starting with this are copied (Empty copies everything)
+    pub address_prefix: String,
+}
+
+impl OscForwardTarget {
+    pub fn is_valid(&self, bind: SocketAddrV4) -> bool {
+        SocketAddrV4::from_str(&self.target).is_ok_and(|target| !loops_back(target, bind))
+            && (self.address_prefix.is_empty() || self.address_prefix.starts_with('/'))
+    }
+}
+
+/*
+ * Forwarding to the address VibeCheck listens on would loop packets back into VibeCheck
+ * An unspecified bind listens on every local address, so any local target on that port loops
+ */
+fn loops_back(target: SocketAddrV4, bind: SocketAddrV4) -> bool {
+    if target.port() != bind.port() {
+        return false;
+    }
+
+    if bind.ip().is_unspecified() {
+        is_local(*target.ip())
+    } else {
+        // Packets sent to 0.0.0.0 are delivered to this machine over loopback
+        target.ip() == bind.ip() || target.ip().is_unspecified() && bind.ip().is_loopback()
+    }
+}
+
+// Only addresses of this machine can be bound to
+fn is_local(ip: Ipv4Addr) -> bool {
+    ip.is_loopback() || ip.is_unspecified() || UdpSocket::bind((ip, 0)).is_ok()
+}
+
+/*
+ * Forwards received packets to the forwarding table of the OSC networking config
+ * Bundles are copied whole when any message in them matches a target's prefix
+ */
+pub struct OscForwarder {
+    sock: Option<UdpSocket>,
+    targets: Vec<(SocketAddrV4, String)>,
+}
+
+impl OscForwarder {
+    pub fn new(forwarding: &[OscForwardTarget]) -> Self {
+        let targets = forwarding
+            .iter()
+            .filter_map(|forward| {
+                SocketAddrV4::from_str(&forward.target)
+                    .ok()
+                    .map(|target| (target, forward.address_prefix.clone()))
+            })
+            .collect::<Vec<(SocketAddrV4, String)>>();
+
+        let sock = if targets.is_empty() {
+            None
+        } else {
+            match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)) {
+                Ok(sock) => {
+                    info!("Forwarding OSC to {} targets", targets.len());
+                    Some(sock)
+                }
+                Err(e) => {
+                    warn!("Failed to bind OSC forwarding socket: {}", e);
+                    None
+                }
+            }
+        };
+
+        Self { sock, targets }
+    }
+
+    /*
+     * Copies a received packet to every matching target
+     * Packets that failed to decode only go to targets without a prefix
+     */
+    pub fn forward(&self, raw: &[u8], packet: Option<&OscPacket>) {
+        let Some(sock) = self.sock.as_ref() else {
+            return;
+        };
+
+        for (target, prefix) in &self.targets {
+            let matched =
+                prefix.is_empty() || packet.is_some_and(|packet| packet_matches(packet, prefix));
+            if !matched {
+                continue;
+            }
+            if let Err(e) = sock.send_to(raw, target) {
+                warn!("Failed to forward OSC packet to {}: {}", target, e);
+            }
+        }
+    }
+}
+
+fn packet_matches(packet: &OscPacket, prefix: &str) -> bool {
+    match packet {
+        OscPacket::Message(msg) => msg.addr.starts_with(prefix),
+        OscPacket::Bundle(bundle) => bundle
+            .content
+            .iter()
+            .any(|packet| packet_matches(packet, prefix)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rosc::{OscBundle, OscMessage, OscTime};
+
+    use super::*;
+
+    fn addr(addr: &str) -> SocketAddrV4 {
+        SocketAddrV4::from_str(addr).unwrap()
+    }
+
+    fn message(addr: &str) -> OscPacket {
+        OscPacket::Message(OscMessage {
+            addr: addr.to_string(),
+            args: Vec::new(),
+        })
+    }
+
+    fn bundle(content: Vec<OscPacket>) -> OscPacket {
+        OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 0,
+                fractional: 1,
+            },
+            content,
+        })
+    }
+
+    #[test]
+    fn loopback_bind_only_loops_to_itself() {
+        let bind = addr("127.0.0.1:9001");
+        assert!(loops_back(addr("127.0.0.1:9001"), bind));
+        assert!(loops_back(addr("0.0.0.0:9001"), bind));
+        assert!(!loops_back(addr("192.168.1.20:9001"), bind));
+        assert!(!loops_back(addr("127.0.0.1:9002"), bind));
+    }
+
+    #[test]
+    fn unspecified_bind_loops_to_local_addresses() {
+        let bind = addr("0.0.0.0:9001");
+        assert!(loops_back(addr("127.0.0.1:9001"), bind));
+        assert!(loops_back(addr("127.0.0.2:9001"), bind));
+        // TEST-NET-1 is never an address of this machine
+        assert!(!loops_back(addr("192.0.2.1:9001"), bind));
+        assert!(!loops_back(addr("127.0.0.1:9002"), bind));
+    }
+
+    #[test]
+    fn address_bind_loops_to_the_same_address() {
+        let bind = addr("192.168.1.5:9001");
+        assert!(loops_back(addr("192.168.1.5:9001"), bind));
+        assert!(!loops_back(addr("127.0.0.1:9001"), bind));
+        assert!(!loops_back(addr("192.168.1.6:9001"), bind));
+    }
+
+    #[test]
+    fn forward_target_validation() {
+        let bind = addr("127.0.0.1:9001");
+        let target = |target: &str, address_prefix: &str| OscForwardTarget {
+            target: target.to_string(),
+            address_prefix: address_prefix.to_string(),
+        };
+
+        assert!(target("127.0.0.1:9010", "").is_valid(bind));
+        assert!(target("192.168.1.20:9001", "/avatar/parameters").is_valid(bind));
+        assert!(!target("127.0.0.1:9001", "").is_valid(bind));
+        assert!(!target("localhost:9010", "").is_valid(bind));
+        assert!(!target("127.0.0.1:9010", "avatar").is_valid(bind));
+    }
+
+    #[test]
+    fn packets_match_prefixes() {
+        assert!(packet_matches(
+            &message("/avatar/parameters/Vibe"),
+            "/avatar/parameters"
+        ));
+        assert!(!packet_matches(
+            &message("/avatar/change"),
+            "/avatar/parameters"
+        ));
+
+        let nested = bundle(vec![
+            message("/avatar/change"),
+            bundle(vec![message("/avatar/parameters/Vibe")]),
+        ]);
+        assert!(packet_matches(&nested, "/avatar/parameters"));
+        assert!(!packet_matches(&nested, "/chatbox"));
+        assert!(!packet_matches(&bundle(Vec::new()), "/avatar"));
+    }
+}
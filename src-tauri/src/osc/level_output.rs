@@ -0,0 +1,171 @@
+/*
+ * Level output
+ * Sends the level every feature of a toy is actually running at back to VRChat.
+ * Enabled per toy by OSC Data, sent by the toy's emitter at a throttled rate.
+ */
+
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddrV4, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use log::{debug, warn};
+use rosc::{encoder, OscMessage, OscPacket, OscType};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::toy_handling::toyops::{VCFeatureType, VCToy};
+
+const MAX_RATE_HZ: f64 = 50.0;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub struct OscLevelOutputConfig {
+    // {toy}, {sub_id}, {feature_type} and {feature_index} are replaced for every feature
+    pub address: String,
+    // Most level updates sent per second for each toy
+    pub rate_hz: f64,
+}
+
+impl Default for OscLevelOutputConfig {
+    fn default() -> Self {
+        Self {
+            address: "/avatar/parameters/vibecheck/osc_data/{toy}/{sub_id}/{feature_type}/{feature_index}/level"
+                .to_string(),
+            rate_hz: 10.0,
+        }
+    }
+}
+
+impl OscLevelOutputConfig {
+    pub fn is_valid(&self) -> bool {
+        self.address.starts_with('/')
+            && !self.address.contains(' ')
+            && self.rate_hz > 0.0
+            && self.rate_hz <= MAX_RATE_HZ
+    }
+}
+
+/*
+ * Level output of one toy
+ * Only levels that changed since the last send are sent
+ */
+pub struct LevelOutput {
+    enabled: bool,
+    config: OscLevelOutputConfig,
+    remote: SocketAddrV4,
+    sock: Option<UdpSocket>,
+    toy_name: String,
+    sub_id: u8,
+    levels: HashMap<(VCFeatureType, u32), f64>,
+    sent_levels: HashMap<(VCFeatureType, u32), f64>,
+    last_send: Option<Instant>,
+}
+
+impl LevelOutput {
+    pub fn new(config: OscLevelOutputConfig, remote: SocketAddrV4, vc_toy: &VCToy) -> Self {
+        Self {
+            enabled: vc_toy.osc_data,
+            config,
+            remote,
+            sock: None,
+            toy_name: vc_toy.osc_name(),
+            sub_id: vc_toy.sub_id,
+            levels: HashMap::new(),
+            sent_levels: HashMap::new(),
+            last_send: None,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled && !enabled {
+            self.send_stopped();
+        }
+        self.enabled = enabled;
+    }
+
+    // Level a feature is running at after clamping, intensity and the safety ceiling
+    pub fn update(&mut self, feature: (VCFeatureType, u32), level: f64) {
+        self.levels.insert(feature, level);
+    }
+
+    // Every feature was stopped
+    pub fn stop_all(&mut self) {
+        self.levels.values_mut().for_each(|level| *level = 0.0);
+    }
+
+    // Leaves the avatar at 0 instead of the last level
+    pub fn send_stopped(&mut self) {
+        self.stop_all();
+        self.send(true);
+    }
+
+    /*
+     * Sends changed levels if enabled and the rate allows it
+     * Force ignores the rate for the last levels before stopping
+     */
+    pub fn send(&mut self, force: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        let interval = Duration::from_secs_f64(1.0 / self.config.rate_hz.clamp(0.1, MAX_RATE_HZ));
+        if !force
+            && self
+                .last_send
+                .is_some_and(|last_send| last_send.elapsed() < interval)
+        {
+            return;
+        }
+
+        let changed = self
+            .levels
+            .iter()
+            .filter(|(feature, level)| self.sent_levels.get(*feature) != Some(*level))
+            .map(|(feature, level)| (*feature, *level))
+            .collect::<Vec<((VCFeatureType, u32), f64)>>();
+        if changed.is_empty() {
+            return;
+        }
+
+        if self.sock.is_none() {
+            match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)) {
+                Ok(sock) => self.sock = Some(sock),
+                Err(e) => {
+                    warn!("Failed to bind level output socket: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(sock) = self.sock.as_ref() else {
+            return;
+        };
+
+        for ((feature_type, feature_index), level) in changed {
+            let Ok(msg) = encoder::encode(&OscPacket::Message(OscMessage {
+                addr: self.address(feature_type, feature_index),
+                args: vec![OscType::Float(level as f32)],
+            })) else {
+                continue;
+            };
+            if let Err(e) = sock.send_to(&msg, self.remote) {
+                warn!("Failed to send level output to {}: {}", self.remote, e);
+                return;
+            }
+            self.sent_levels
+                .insert((feature_type, feature_index), level);
+        }
+        debug!("Sent level output for toy {}", self.toy_name);
+        self.last_send = Some(Instant::now());
+    }
+
+    fn address(&self, feature_type: VCFeatureType, feature_index: u32) -> String {
+        self.config
+            .address
+            .replace("{toy}", &self.toy_name)
+            .replace("{sub_id}", &self.sub_id.to_string())
+            .replace("{feature_type}", feature_type.api_name())
+            .replace("{feature_index}", &feature_index.to_string())
+    }
+}
@@ -5,7 +5,6 @@ use rosc::encoder;
 use rosc::OscType;
 use rosc::{self, OscMessage, OscPacket};
 
-use tauri::AppHandle;
 use tokio::net::UdpSocket as tUdpSocket;
 
 use tokio::sync::broadcast::Sender as BSender;
@@ -20,15 +19,19 @@ use crate::frontend::frontend_types::FeCoreEvent;
 use crate::frontend::frontend_types::FeToyEvent;
 use crate::frontend::frontend_types::FeVCToy;
 use crate::frontend::ToFrontend;
-use crate::osc_api::api::vibecheck_osc_api;
+use crate::osc_api::api::{avatar_id, vibecheck_osc_api};
 use crate::toy_handling::ToyPower;
 use crate::toy_handling::ToySig;
+use crate::vcore::host::CoreHandle;
+use crate::vcore::ipc::call_plane::native_apply_avatar_profiles;
 use crate::vcore::ipc::call_plane::TmSig;
 use crate::vcore::ipc::call_plane::ToyManagementEvent;
 use crate::vcore::ipc::emit_plane::emit_core_event;
 use crate::vcore::ipc::emit_plane::emit_toy_event;
 use crate::vcore::state::VibeCheckState;
 
+use super::bundle::{unpack_osc_packet, OscBundleScheduler};
+use super::forwarding::OscForwarder;
 use super::OSCNetworking;
 
 /*
@@ -40,7 +43,7 @@ use super::OSCNetworking;
 pub fn toy_input_routine(
     toy_bcst_tx: BSender<ToySig>,
     tme_send: UnboundedSender<ToyManagementEvent>,
-    app_handle: AppHandle,
+    core_handle: CoreHandle,
     vc_config: OSCNetworking,
 ) {
     let bind_sock =
@@ -62,19 +65,29 @@ pub fn toy_input_routine(
     bind_sock.set_nonblocking(false).unwrap();
     let _ = bind_sock.set_read_timeout(Some(Duration::from_secs(1)));
 
+    // Holds messages from future dated bundles
+    let mut bundle_scheduler = OscBundleScheduler::new();
+    let forwarder = OscForwarder::new(&vc_config.forwarding);
+
     loop {
         // try recv OSC packet
         // parse OSC packet
         // Send address and arg to broadcast channel
         // Die when channel disconnects
 
-        if !vibecheck_osc_api(&bind_sock, &app_handle, &toy_bcst_tx) {
+        if !vibecheck_osc_api(
+            &bind_sock,
+            &core_handle,
+            &toy_bcst_tx,
+            &mut bundle_scheduler,
+            &forwarder,
+        ) {
             return;
         }
     }
 }
 
-pub async fn vc_disabled_osc_command_listen(app_handle: AppHandle, vc_config: OSCNetworking) {
+pub async fn vc_disabled_osc_command_listen(core_handle: CoreHandle, vc_config: OSCNetworking) {
     info!("Listening for OSC commands while disabled");
     let mut retries = 3;
     let sock;
@@ -99,6 +112,7 @@ pub async fn vc_disabled_osc_command_listen(app_handle: AppHandle, vc_config: OS
             }
         };
     }
+    let forwarder = OscForwarder::new(&vc_config.forwarding);
 
     loop {
         let mut buf = [0u8; rosc::decoder::MTU];
@@ -118,23 +132,33 @@ pub async fn vc_disabled_osc_command_listen(app_handle: AppHandle, vc_config: OS
                 Ok(pkt) => pkt,
                 Err(_e) => {
                     logerr!("Failed to parse OSC packet");
+                    forwarder.forward(&buf[..br], None);
                     continue;
                 }
             };
+            forwarder.forward(&buf[..br], Some(&pkt.1));
 
-            match pkt.1 {
-                OscPacket::Message(mut msg) => {
-                    if msg.addr == "/avatar/parameters/vibecheck/state" {
-                        if let Some(state_bool) = msg.args.pop().unwrap().bool() {
-                            if state_bool {
-                                info!("Sending EnableAndScan event");
-                                emit_core_event(&app_handle, FeCoreEvent::State(crate::frontend::frontend_types::FeStateEvent::EnableAndScan));
-                            }
+            // Bundle timetags don't matter while disabled so everything is handled immediately
+            for (_, mut msg) in unpack_osc_packet(pkt.1) {
+                if msg.addr == "/avatar/parameters/vibecheck/state" {
+                    if let Some(state_bool) = msg.args.pop().and_then(|arg| arg.bool()) {
+                        if state_bool {
+                            info!("Sending EnableAndScan event");
+                            emit_core_event(
+                                &core_handle,
+                                FeCoreEvent::State(
+                                    crate::frontend::frontend_types::FeStateEvent::EnableAndScan,
+                                ),
+                            );
                         }
                     }
-                }
-                _ => {
-                    info!("Didn't get OscPacket::Message, skipping..");
+                } else if msg.addr.starts_with("/avatar/change") {
+                    // Keep toy profiles in step with the avatar while disabled
+                    native_apply_avatar_profiles(
+                        core_handle.state(),
+                        core_handle.clone(),
+                        avatar_id(msg),
+                    );
                 }
             }
         }
@@ -142,7 +166,7 @@ pub async fn vc_disabled_osc_command_listen(app_handle: AppHandle, vc_config: OS
 }
 
 #[inline]
-pub fn recv_osc_cmd(sock: &UdpSocket) -> Option<OscMessage> {
+pub fn recv_osc_cmd(sock: &UdpSocket, forwarder: &OscForwarder) -> Option<OscPacket> {
     let mut buf = [0u8; rosc::decoder::MTU];
 
     let (br, _a) = match sock.recv_from(&mut buf) {
@@ -155,11 +179,12 @@ pub fn recv_osc_cmd(sock: &UdpSocket) -> Option<OscMessage> {
     if br == 0 {
         return None;
     }
-    let pkt = rosc::decoder::decode_udp(&buf).ok()?;
-    match pkt.1 {
-        OscPacket::Message(msg) => Some(msg),
-        _ => None,
-    }
+    let Ok(pkt) = rosc::decoder::decode_udp(&buf) else {
+        forwarder.forward(&buf[..br], None);
+        return None;
+    };
+    forwarder.forward(&buf[..br], Some(&pkt.1));
+    Some(pkt.1)
 }
 
 /* FUTURE MAYBE
@@ -172,7 +197,7 @@ pub fn recv_osc_cmd(sock: &UdpSocket) -> Option<OscMessage> {
 
 pub async fn toy_refresh(
     vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>,
-    app_handle: AppHandle,
+    core_handle: CoreHandle,
 ) {
     loop {
         Delay::new(Duration::from_secs(15)).await;
@@ -223,7 +248,7 @@ pub async fn toy_refresh(
             toy.toy_power = toy_power.clone();
 
             emit_toy_event(
-                &app_handle,
+                &core_handle,
                 FeToyEvent::Update({
                     FeVCToy {
                         toy_id: Some(toy.toy_id),
@@ -236,6 +261,7 @@ pub async fn toy_refresh(
                         osc_data: toy.osc_data,
                         bt_update_rate: toy.bt_update_rate,
                         sub_id: toy.sub_id,
+                        safety: toy.config.as_ref().unwrap().safety,
                     }
                 }),
             );
@@ -246,7 +272,7 @@ pub async fn toy_refresh(
                 let battery_level_msg = encoder::encode(&OscPacket::Message(OscMessage {
                     addr: format!(
                         "/avatar/parameters/vibecheck/osc_data/{}/{}/battery",
-                        toy.toy_name.replace(" ", "_").to_lowercase(),
+                        toy.osc_name(),
                         toy.sub_id
                     ),
                     args: vec![OscType::Float(toy_power.to_float() as f32)],
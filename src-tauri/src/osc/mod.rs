@@ -4,14 +4,28 @@ use serde::{Deserialize, Serialize};
 
 use crate::frontend::frontend_types::FeOSCNetworking;
 
+use self::{forwarding::OscForwardTarget, level_output::OscLevelOutputConfig};
+
+pub mod bundle;
 pub mod errors;
+pub mod forwarding;
+pub mod level_output;
 pub mod logic;
+pub mod query_client;
+pub mod query_host;
+pub mod query_tree;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct OSCNetworking {
     pub bind: SocketAddrV4,
     pub remote: SocketAddrV4,
     pub osc_query_enabled: bool,
+    // Feature levels sent back to VRChat for toys with OSC Data on
+    #[serde(default)]
+    pub level_output: OscLevelOutputConfig,
+    // Apps every received packet is copied to
+    #[serde(default)]
+    pub forwarding: Vec<OscForwardTarget>,
 }
 
 impl Default for OSCNetworking {
@@ -20,6 +34,8 @@ impl Default for OSCNetworking {
             bind: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 9001),
             remote: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 9000),
             osc_query_enabled: true,
+            level_output: OscLevelOutputConfig::default(),
+            forwarding: Vec::new(),
         }
     }
 }
@@ -30,6 +46,8 @@ impl OSCNetworking {
             bind: self.bind.to_string(),
             remote: self.remote.to_string(),
             osc_query_enabled: self.osc_query_enabled,
+            level_output: self.level_output.clone(),
+            forwarding: self.forwarding.clone(),
         }
     }
 }
@@ -0,0 +1,102 @@
+/*
+ * OSCQuery client
+ * Finds VRChat's OSCQuery service over mDNS and reads the parameters of the current avatar.
+ */
+
+use std::{
+    net::SocketAddrV4,
+    time::{Duration, Instant},
+};
+
+use log::{debug, info, warn};
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use serde_json::Value;
+
+use crate::frontend::frontend_types::{FeAvatarParameter, FeAvatarParameterType};
+
+const OSC_JSON_SERVICE: &str = "_oscjson._tcp.local.";
+const VRCHAT_SERVICE_PREFIX: &str = "VRChat-Client-";
+const AVATAR_PARAMETERS_PATH: &str = "/avatar/parameters";
+
+/*
+ * Browses mDNS for VRChat's OSCQuery HTTP service
+ * Returns None if VRChat did not answer within the timeout
+ */
+pub fn discover_vrchat(timeout: Duration) -> Option<SocketAddrV4> {
+    let mdns = match ServiceDaemon::new() {
+        Ok(mdns) => mdns,
+        Err(e) => {
+            warn!("Failed to start mDNS daemon: {}", e);
+            return None;
+        }
+    };
+    let receiver = match mdns.browse(OSC_JSON_SERVICE) {
+        Ok(receiver) => receiver,
+        Err(e) => {
+            warn!("Failed to browse for {}: {}", OSC_JSON_SERVICE, e);
+            let _ = mdns.shutdown();
+            return None;
+        }
+    };
+
+    let started = Instant::now();
+    let mut found = None;
+    while let Some(remaining) = timeout.checked_sub(started.elapsed()) {
+        let Ok(event) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+        let ServiceEvent::ServiceResolved(service) = event else {
+            continue;
+        };
+        if !service.get_fullname().starts_with(VRCHAT_SERVICE_PREFIX) {
+            continue;
+        }
+        if let Some(ip) = service.get_addresses().iter().next() {
+            found = Some(SocketAddrV4::new(*ip, service.get_port()));
+            info!("Found VRChat OSCQuery service: {}", service.get_fullname());
+            break;
+        }
+    }
+
+    let _ = mdns.stop_browse(OSC_JSON_SERVICE);
+    let _ = mdns.shutdown();
+    found
+}
+
+/*
+ * Fetches the avatar parameter tree from VRChat's OSCQuery service
+ * Blocking so it must not be called from an async runtime thread
+ */
+pub fn fetch_avatar_parameters(host: SocketAddrV4) -> Result<Vec<FeAvatarParameter>, String> {
+    let url = format!("http://{}{}", host, AVATAR_PARAMETERS_PATH);
+    let body = reqwest::blocking::get(&url)
+        .and_then(|res| res.error_for_status())
+        .and_then(|res| res.text())
+        .map_err(|e| e.to_string())?;
+    let tree = serde_json::from_str::<Value>(&body).map_err(|e| e.to_string())?;
+
+    let mut parameters = Vec::new();
+    collect_parameters(&tree, &mut parameters);
+    parameters.sort_by(|a, b| a.address.cmp(&b.address));
+    debug!("Avatar has {} parameters", parameters.len());
+    Ok(parameters)
+}
+
+// Every node with a type is a parameter, containers only hold other nodes
+fn collect_parameters(node: &Value, parameters: &mut Vec<FeAvatarParameter>) {
+    if let (Some(address), Some(osc_type)) = (
+        node.get("FULL_PATH").and_then(Value::as_str),
+        node.get("TYPE").and_then(Value::as_str),
+    ) {
+        parameters.push(FeAvatarParameter {
+            address: address.to_string(),
+            param_type: FeAvatarParameterType::from_osc_type(osc_type),
+        });
+    }
+
+    if let Some(contents) = node.get("CONTENTS").and_then(Value::as_object) {
+        for child in contents.values() {
+            collect_parameters(child, parameters);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+/*
+ * OSCQuery HTTP host
+ * Serves VibeCheck's host tree and host info on the OSCQuery HTTP port.
+ * VRCOSCQuery advertises the service over mDNS but its HTTP server cannot hold custom nodes.
+ */
+
+use std::{net::SocketAddrV4, sync::Arc, time::Duration};
+
+use log::{debug, info, warn};
+use parking_lot::RwLock;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Handle,
+    task::JoinHandle,
+    time::timeout,
+};
+
+use super::query_tree::host_tree;
+
+const HOST_NAME: &str = "VibeCheck";
+// Requests are a single GET line with a few headers
+const MAX_REQUEST_SIZE: usize = 4096;
+// Connections that do not finish their request in time are dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct OscQueryHost {
+    http: SocketAddrV4,
+    osc: SocketAddrV4,
+    tree: Arc<RwLock<Value>>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl OscQueryHost {
+    pub fn new(http: SocketAddrV4, osc: SocketAddrV4) -> Self {
+        Self {
+            http,
+            osc,
+            tree: Arc::new(RwLock::new(host_tree(&Default::default()))),
+            server: None,
+        }
+    }
+
+    // Replaces the served host tree, requests after this get the new tree
+    pub fn set_tree(&self, tree: Value) {
+        *self.tree.write() = tree;
+    }
+
+    pub fn start(&mut self, async_rt: &Handle) {
+        if self.server.is_some() {
+            return;
+        }
+
+        let (http, osc, tree) = (self.http, self.osc, self.tree.clone());
+        self.server = Some(async_rt.spawn(async move {
+            let listener = match TcpListener::bind(http).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!("Failed to bind OSCQuery host to {}: {}", http, e);
+                    return;
+                }
+            };
+            info!("OSCQuery host listening on {}", http);
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(respond(stream, osc, tree.clone()));
+                    }
+                    Err(e) => debug!("OSCQuery host accept failed: {}", e),
+                }
+            }
+        }));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(server) = self.server.take() {
+            server.abort();
+        }
+    }
+}
+
+impl Drop for OscQueryHost {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/*
+ * Answers one OSCQuery request and closes the connection
+ */
+async fn respond(mut stream: TcpStream, osc: SocketAddrV4, tree: Arc<RwLock<Value>>) {
+    let Ok(Some(request)) = timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await else {
+        return;
+    };
+    let response = response(&request, osc, &tree.read());
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/*
+ * Reads until the end of the request headers
+ * The request may arrive over several reads, a closed or full buffer ends it early
+ */
+async fn read_request(stream: &mut TcpStream) -> Option<String> {
+    let mut buf = vec![0u8; MAX_REQUEST_SIZE];
+    let mut filled = 0;
+    while filled < buf.len() && !headers_complete(&buf[..filled]) {
+        match stream.read(&mut buf[filled..]).await {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) => {
+                debug!("OSCQuery host read failed: {}", e);
+                return None;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&buf[..filled]).into_owned())
+}
+
+fn headers_complete(buf: &[u8]) -> bool {
+    buf.windows(4).any(|window| window == b"\r\n\r\n")
+}
+
+/*
+ * ?HOST_INFO gets the host info, any other path gets its node of the host tree
+ */
+fn response(request: &str, osc: SocketAddrV4, tree: &Value) -> String {
+    let target = request.lines().next().and_then(|line| {
+        let mut parts = line.split_whitespace();
+        (parts.next() == Some("GET"))
+            .then(|| parts.next())
+            .flatten()
+    });
+    let body = target.and_then(|target| {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        if query == "HOST_INFO" {
+            Some(host_info(osc))
+        } else {
+            find_node(tree, path).cloned()
+        }
+    });
+
+    match body {
+        Some(body) => {
+            let body = body.to_string();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        None => {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        }
+    }
+}
+
+fn host_info(osc: SocketAddrV4) -> Value {
+    json!({
+        "NAME": HOST_NAME,
+        "OSC_IP": osc.ip().to_string(),
+        "OSC_PORT": osc.port(),
+        "OSC_TRANSPORT": "UDP",
+        "EXTENSIONS": {
+            "ACCESS": true,
+            "CLIPMODE": false,
+            "RANGE": false,
+            "TYPE": true,
+            "VALUE": false,
+        },
+    })
+}
+
+// Walks CONTENTS from the root node along the path
+fn find_node<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('/')
+        .filter(|part| !part.is_empty())
+        .try_fold(root, |node, part| node.get("CONTENTS")?.get(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, net::Ipv4Addr};
+
+    use crate::osc::query_tree::ConsumedAddress;
+
+    use super::*;
+
+    const OSC: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 9001);
+
+    fn tree() -> Value {
+        host_tree(&BTreeMap::from([(
+            "/avatar/parameters/Vibe".to_string(),
+            ConsumedAddress::Value("f"),
+        )]))
+    }
+
+    fn body(response: &str) -> Value {
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    fn response_for(request: &str) -> String {
+        response(request, OSC, &tree())
+    }
+
+    #[test]
+    fn headers_end_with_a_blank_line() {
+        assert!(!headers_complete(b"GET / HTTP/1.1\r\nHost: localhost"));
+        assert!(!headers_complete(b"GET / HTTP/1.1\r\n"));
+        assert!(headers_complete(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        ));
+    }
+
+    #[test]
+    fn serves_tree_nodes() {
+        let response = response_for("GET /avatar/parameters/Vibe HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(body(&response)["TYPE"], "f");
+
+        let root = body(&response_for("GET / HTTP/1.1\r\n\r\n"));
+        assert_eq!(root["FULL_PATH"], "/");
+    }
+
+    #[test]
+    fn serves_host_info() {
+        let info = body(&response_for("GET /?HOST_INFO HTTP/1.1\r\n\r\n"));
+        assert_eq!(info["NAME"], HOST_NAME);
+        assert_eq!(info["OSC_PORT"], 9001);
+        assert_eq!(info["EXTENSIONS"]["TYPE"], true);
+    }
+
+    #[test]
+    fn unknown_requests_are_not_found() {
+        for request in [
+            "GET /avatar/parameters/Other HTTP/1.1\r\n\r\n",
+            "POST / HTTP/1.1\r\n\r\n",
+            "",
+        ] {
+            assert!(response_for(request).starts_with("HTTP/1.1 404 Not Found"));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+/*
+ * OSCQuery host tree
+ * The addresses VibeCheck consumes so OSCQuery aware senders only route what is needed.
+ */
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::toy_handling::{
+    input_processor::penetration_systems::PenetrationSystemType, toyops::VCToyFeature,
+};
+
+// OSCQuery ACCESS value for addresses that can only be sent to
+const ACCESS_WRITE_ONLY: u8 = 2;
+
+// Containers that are routed as a whole
+const API_PREFIX: &str = "/avatar/parameters/vibecheck/api";
+const SPS_PREFIX: &str = "/avatar/parameters/OGB";
+const TPS_PREFIX: &str = "/avatar/parameters/TPS_Internal";
+
+// OSC type tags of consumed values
+const TYPE_FLOAT: &str = "f";
+const TYPE_BOOL: &str = "T";
+const TYPE_STRING: &str = "s";
+
+// Addresses VibeCheck always handles
+const STATIC_ADDRESSES: [(&str, &str); 2] = [
+    ("/avatar/change", TYPE_STRING),
+    ("/avatar/parameters/vibecheck/state", TYPE_BOOL),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsumedAddress {
+    // A value with an OSC type tag
+    Value(&'static str),
+    // Holds addresses that are not known up front
+    Container,
+}
+
+/*
+ * Every address assigned to an enabled feature
+ * Penetration systems and the VibeCheck API are added as containers
+ */
+pub fn consumed_addresses<'a>(
+    features: impl IntoIterator<Item = &'a VCToyFeature>,
+) -> BTreeMap<String, ConsumedAddress> {
+    let mut addresses = BTreeMap::from([(API_PREFIX.to_string(), ConsumedAddress::Container)]);
+    addresses.extend(
+        STATIC_ADDRESSES
+            .iter()
+            .map(|(address, osc_type)| (address.to_string(), ConsumedAddress::Value(*osc_type))),
+    );
+
+    for feature in features
+        .into_iter()
+        .filter(|feature| feature.feature_enabled)
+    {
+        // Features are driven by float parameters, Bools and Ints are still accepted
+        for param in feature
+            .osc_parameters
+            .iter()
+            .filter(|param| param.parameter.starts_with('/'))
+        {
+            addresses
+                .entry(param.parameter.trim_end_matches('/').to_string())
+                .or_insert(ConsumedAddress::Value(TYPE_FLOAT));
+        }
+
+        let prefix = match feature.penetration_system.pen_system_type {
+            PenetrationSystemType::Sps => SPS_PREFIX,
+            PenetrationSystemType::Tps => TPS_PREFIX,
+            PenetrationSystemType::None => continue,
+        };
+        addresses.insert(prefix.to_string(), ConsumedAddress::Container);
+    }
+
+    addresses
+}
+
+/*
+ * Builds the OSCQuery root node from the consumed addresses
+ * Every address becomes a write only node with its parents as containers
+ */
+pub fn host_tree(addresses: &BTreeMap<String, ConsumedAddress>) -> Value {
+    let mut root = TreeNode::default();
+    for (address, consumed) in addresses {
+        let mut node = &mut root;
+        for part in address.split('/').filter(|part| !part.is_empty()) {
+            node = node.contents.entry(part.to_string()).or_default();
+        }
+        node.consumed = Some(*consumed);
+    }
+    root.to_json("/")
+}
+
+#[derive(Default)]
+struct TreeNode {
+    consumed: Option<ConsumedAddress>,
+    contents: BTreeMap<String, TreeNode>,
+}
+
+impl TreeNode {
+    fn to_json(&self, full_path: &str) -> Value {
+        let mut node = Map::new();
+        node.insert("FULL_PATH".to_string(), json!(full_path));
+        node.insert(
+            "ACCESS".to_string(),
+            json!(if self.consumed.is_some() {
+                ACCESS_WRITE_ONLY
+            } else {
+                0
+            }),
+        );
+        if let Some(ConsumedAddress::Value(osc_type)) = self.consumed {
+            node.insert("TYPE".to_string(), json!(osc_type));
+        }
+
+        // Containers keep their CONTENTS even when nothing below them is known
+        if !self.contents.is_empty() || self.consumed == Some(ConsumedAddress::Container) {
+            let contents = self
+                .contents
+                .iter()
+                .map(|(part, child)| {
+                    let child_path = if full_path == "/" {
+                        format!("/{}", part)
+                    } else {
+                        format!("{}/{}", full_path, part)
+                    };
+                    (part.clone(), child.to_json(&child_path))
+                })
+                .collect::<Map<String, Value>>();
+            node.insert("CONTENTS".to_string(), Value::Object(contents));
+        }
+
+        Value::Object(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::toy_handling::{
+        input_processor::penetration_systems::PenetrationSystem,
+        toyops::{
+            IntMapping, LevelTweaks, ParameterBlendMode, ProcessingMode, ProcessingModeValues,
+            ToyParameter, VCFeatureType,
+        },
+    };
+
+    use super::*;
+
+    fn feature(parameters: &[&str], pen_system_type: PenetrationSystemType) -> VCToyFeature {
+        VCToyFeature {
+            feature_index: 0,
+            feature_enabled: true,
+            feature_type: VCFeatureType::Vibrator,
+            osc_parameters: parameters
+                .iter()
+                .map(|parameter| ToyParameter {
+                    parameter: parameter.to_string(),
+                    processing_mode: ProcessingMode::Raw,
+                    int_mapping: IntMapping::default(),
+                    weight: 1.0,
+                    pattern: String::new(),
+                    processing_mode_values: ProcessingModeValues::default(),
+                    latest_level: None,
+                })
+                .collect(),
+            parameter_blend: ParameterBlendMode::default(),
+            penetration_system: PenetrationSystem {
+                pen_system_type,
+                ..Default::default()
+            },
+            flip_input_float: false,
+            feature_levels: LevelTweaks::default(),
+            smooth_enabled: true,
+            rate_enabled: false,
+        }
+    }
+
+    #[test]
+    fn consumed_addresses_of_enabled_features() {
+        let mut disabled = feature(&["/avatar/parameters/Disabled"], PenetrationSystemType::Tps);
+        disabled.feature_enabled = false;
+        let features = [
+            feature(
+                &["/avatar/parameters/Vibe/", "NotAnAddress"],
+                PenetrationSystemType::Sps,
+            ),
+            disabled,
+        ];
+
+        let addresses = consumed_addresses(&features);
+        assert_eq!(
+            addresses,
+            BTreeMap::from([
+                ("/avatar/change".to_string(), ConsumedAddress::Value("s")),
+                (SPS_PREFIX.to_string(), ConsumedAddress::Container),
+                (
+                    "/avatar/parameters/Vibe".to_string(),
+                    ConsumedAddress::Value("f")
+                ),
+                (API_PREFIX.to_string(), ConsumedAddress::Container),
+                (
+                    "/avatar/parameters/vibecheck/state".to_string(),
+                    ConsumedAddress::Value("T")
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn host_tree_nodes() {
+        let tree = host_tree(&consumed_addresses(&[feature(
+            &["/avatar/parameters/Vibe"],
+            PenetrationSystemType::None,
+        )]));
+        let parameters = &tree["CONTENTS"]["avatar"]["CONTENTS"]["parameters"];
+
+        assert_eq!(tree["FULL_PATH"], "/");
+        assert_eq!(parameters["FULL_PATH"], "/avatar/parameters");
+        assert_eq!(parameters["ACCESS"], 0);
+        assert!(parameters.get("TYPE").is_none());
+
+        let vibe = &parameters["CONTENTS"]["Vibe"];
+        assert_eq!(vibe["FULL_PATH"], "/avatar/parameters/Vibe");
+        assert_eq!(vibe["ACCESS"], ACCESS_WRITE_ONLY);
+        assert_eq!(vibe["TYPE"], "f");
+        assert!(vibe.get("CONTENTS").is_none());
+
+        // The API container is consumed and holds the state value
+        let vibecheck = &parameters["CONTENTS"]["vibecheck"]["CONTENTS"];
+        assert_eq!(vibecheck["api"]["ACCESS"], ACCESS_WRITE_ONLY);
+        assert_eq!(vibecheck["api"]["CONTENTS"], json!({}));
+        assert!(vibecheck["api"].get("TYPE").is_none());
+        assert_eq!(vibecheck["state"]["TYPE"], "T");
+    }
+}
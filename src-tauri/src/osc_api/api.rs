@@ -1,49 +1,48 @@
-use crate::{osc::logic::recv_osc_cmd, toy_handling::ToySig};
+use crate::{
+    osc::{bundle::OscBundleScheduler, forwarding::OscForwarder, logic::recv_osc_cmd},
+    toy_handling::ToySig,
+    vcore::{host::CoreHandle, ipc::call_plane::native_apply_avatar_profiles},
+};
 use log::{info, trace};
-use std::net::UdpSocket;
-use tauri::{AppHandle, Manager};
+use rosc::{OscMessage, OscType};
+use std::{net::UdpSocket, time::Duration};
 use tokio::sync::broadcast::Sender as BSender;
 
 use super::APIProcessor;
 
+// Longest the listener blocks on the socket before checking its channels again
+const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub fn vibecheck_osc_api(
     bind_sock: &UdpSocket,
-    app_handle: &AppHandle,
+    core_handle: &CoreHandle,
     toy_bcst_tx: &BSender<ToySig>,
+    bundle_scheduler: &mut OscBundleScheduler,
+    forwarder: &OscForwarder,
 ) -> bool {
-    match recv_osc_cmd(bind_sock) {
-        Some(msg) => {
-            // Stop toys on avatar change
-            if msg.addr.starts_with("/avatar/change") {
-                info!("Avatar Changed: Halting toy actions");
-                {
-                    let vc_pointer = app_handle
-                        .state::<crate::vcore::state::VCStateMutex>()
-                        .0
-                        .clone();
-                    let vc_lock = vc_pointer.lock();
-                    vc_lock
-                        .async_rt
-                        .block_on(async {
-                            vc_lock.bp_client.as_ref().unwrap().stop_all_devices().await
-                        })
-                        .unwrap();
-                }
-                true
-            } else if msg.addr.starts_with("/avatar/parameters/vibecheck/api/") {
-                trace!("[*] VibeCheck API: {:?}", msg);
-                APIProcessor::parse(msg, app_handle);
-                true
-            } else {
-                // Not a vibecheck OSC command, broadcast to toys
-                if toy_bcst_tx.send(ToySig::OSCMsg(msg)).is_err() {
-                    info!("BCST TX is disconnected. Shutting down toy input routine!");
-                    // Shutting down handler_routine
-                    false
-                } else {
-                    true
+    // Handle messages from bundles that have come due
+    for msg in bundle_scheduler.pop_due() {
+        if !handle_osc_message(msg, core_handle, toy_bcst_tx) {
+            return false;
+        }
+    }
+
+    // Wake up in time for the next scheduled bundle
+    let recv_timeout = bundle_scheduler
+        .time_until_next()
+        .map_or(RECV_TIMEOUT, |until_next| {
+            until_next.clamp(Duration::from_millis(1), RECV_TIMEOUT)
+        });
+    let _ = bind_sock.set_read_timeout(Some(recv_timeout));
+
+    match recv_osc_cmd(bind_sock, forwarder) {
+        Some(packet) => {
+            for msg in bundle_scheduler.unpack(packet) {
+                if !handle_osc_message(msg, core_handle, toy_bcst_tx) {
+                    return false;
                 }
             }
+            true
         }
         None => {
             if toy_bcst_tx.receiver_count() == 0 {
@@ -55,3 +54,47 @@ pub fn vibecheck_osc_api(
         }
     }
 }
+
+fn handle_osc_message(
+    msg: OscMessage,
+    core_handle: &CoreHandle,
+    toy_bcst_tx: &BSender<ToySig>,
+) -> bool {
+    // Stop toys and switch toy profiles on avatar change
+    if msg.addr.starts_with("/avatar/change") {
+        info!("Avatar Changed: Halting toy actions");
+        {
+            let vc_pointer = core_handle.state().0.clone();
+            let vc_lock = vc_pointer.lock();
+            // No client while reconnecting to a Buttplug server
+            if let Some(bp_client) = vc_lock.bp_client.as_ref() {
+                let _ = vc_lock
+                    .async_rt
+                    .block_on(async { bp_client.stop_all_devices().await });
+            }
+        }
+        native_apply_avatar_profiles(core_handle.state(), core_handle.clone(), avatar_id(msg));
+        true
+    } else if msg.addr.starts_with("/avatar/parameters/vibecheck/api/") {
+        trace!("[*] VibeCheck API: {:?}", msg);
+        APIProcessor::parse(msg, core_handle);
+        true
+    } else {
+        // Not a vibecheck OSC command, broadcast to toys
+        if toy_bcst_tx.send(ToySig::OSCMsg(msg)).is_err() {
+            info!("BCST TX is disconnected. Shutting down toy input routine!");
+            // Shutting down handler_routine
+            false
+        } else {
+            true
+        }
+    }
+}
+
+// VRChat sends the new avatar ID as the only argument of /avatar/change
+pub fn avatar_id(mut msg: OscMessage) -> Option<String> {
+    match msg.args.pop() {
+        Some(OscType::String(avatar_id)) if !avatar_id.is_empty() => Some(avatar_id),
+        _ => None,
+    }
+}
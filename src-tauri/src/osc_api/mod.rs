@@ -1,13 +1,23 @@
-use std::rc::Rc;
+use std::time::Duration;
 
 use log::{debug, info, trace};
-use rosc::OscMessage;
-use tauri::{AppHandle, Manager};
+use rosc::{OscMessage, OscType};
 
 use crate::{
     config::toy::VCToyAnatomy,
     frontend::frontend_types::FeCoreEvent,
-    vcore::ipc::{call_plane::native_alter_toy, emit_plane::emit_core_event},
+    toy_handling::{
+        funscript::FunscriptControl,
+        output_control::MAX_PAUSE,
+        toyops::{ProcessingMode, ProcessingModeValues, VCFeatureType, VCToy},
+    },
+    vcore::{
+        host::CoreHandle,
+        ipc::{
+            call_plane::{native_alter_toy, native_emergency_stop, native_funscript_control},
+            emit_plane::emit_core_event,
+        },
+    },
 };
 
 #[derive(Debug)]
@@ -18,7 +28,7 @@ pub mod api;
 struct APIProcessor;
 
 impl APIProcessor {
-    pub fn parse(mut endpoint: OscMessage, app_handle: &AppHandle) {
+    pub fn parse(mut endpoint: OscMessage, core_handle: &CoreHandle) {
         let mut api_tokenize = endpoint
             .addr
             .split('/')
@@ -26,61 +36,268 @@ impl APIProcessor {
             .collect::<Vec<String>>();
         api_tokenize.retain(|token| !token.is_empty());
         debug!("[*] API tokenization: {:?}", api_tokenize);
-        if api_tokenize.len() == 5
-            && api_tokenize[2] == "vibecheck"
-            && api_tokenize[3] == "api"
-            && api_tokenize[4] == "state"
-        {
+
+        if api_tokenize.len() < 5 || api_tokenize[2] != "vibecheck" || api_tokenize[3] != "api" {
+            return;
+        }
+
+        let Some(arg) = endpoint.args.pop() else {
+            return;
+        };
+
+        let api_tokens = api_tokenize[4..]
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+
+        match api_tokens.as_slice() {
             // /avatar/parameters/vibecheck/api/state
-            let Some(false) = endpoint.args.pop().unwrap().bool() else {
-                return;
-            };
-            info!("State false: Sending Disable event");
-
-            emit_core_event(
-                &Rc::new(app_handle),
-                FeCoreEvent::State(crate::frontend::frontend_types::FeStateEvent::Disable),
-            );
-        } else if api_tokenize.len() == 7
-            && api_tokenize[4] == "anatomy"
-            && api_tokenize[6] == "enabled"
-        {
+            ["state"] => {
+                let Some(false) = arg.bool() else {
+                    return;
+                };
+                info!("State false: Sending Disable event");
+
+                emit_core_event(
+                    core_handle,
+                    FeCoreEvent::State(crate::frontend::frontend_types::FeStateEvent::Disable),
+                );
+            }
             // /avatar/parameters/vibecheck/api/anatomy/Anal/enabled
-            trace!("[*] Checking anatomy token: {}", api_tokenize[5]);
-            let anatomy = VCToyAnatomy::get_anatomy(&api_tokenize[5]);
-            let mut altered_toys = Vec::new();
+            ["anatomy", anatomy, "enabled"] => {
+                trace!("[*] Checking anatomy token: {}", anatomy);
+                let anatomy = VCToyAnatomy::get_anatomy(&anatomy.to_string());
 
-            if let Some(state_bool) = endpoint.args.pop().unwrap().bool() {
-                let vc_pointer = app_handle
-                    .state::<crate::vcore::state::VCStateMutex>()
-                    .0
-                    .clone();
-                let mut vc_lock = vc_pointer.lock();
-
-                vc_lock
-                    .core_toy_manager
-                    .as_mut()
-                    .unwrap()
-                    .online_toys
-                    .iter_mut()
-                    .for_each(|toy| {
-                        if toy.1.mutate_state_by_anatomy(&anatomy, state_bool) {
+                if let Some(state_bool) = arg.bool() {
+                    alter_online_toys(core_handle, |toy| {
+                        if toy.mutate_state_by_anatomy(&anatomy, state_bool) {
                             trace!(
                                 "[*] Mutating feature state from anatomy for toy: {}",
-                                toy.1.toy_name
+                                toy.toy_name
                             );
-                            altered_toys.push(toy.1.clone());
+                            true
+                        } else {
+                            false
                         }
                     });
+                }
             }
-
-            altered_toys.iter().for_each(|toy| {
-                let _ = native_alter_toy(
-                    app_handle.state::<crate::vcore::state::VCStateMutex>(),
-                    app_handle.clone(),
-                    toy.clone(),
-                );
-            });
+            // /avatar/parameters/vibecheck/api/intensity
+            ["intensity"] => {
+                let Some(intensity) = osc_float(&arg).filter(|i| i.is_finite()) else {
+                    return;
+                };
+                info!("Global intensity: {:.2}", intensity);
+                core_handle
+                    .state()
+                    .0
+                    .lock()
+                    .output_control
+                    .set_intensity(intensity);
+            }
+            // /avatar/parameters/vibecheck/api/stop
+            ["stop"] => {
+                if let Some(true) = arg.bool() {
+                    info!("Stop all toys");
+                    stop_all_toys(core_handle);
+                }
+            }
+            // /avatar/parameters/vibecheck/api/emergency_stop
+            // Latches until re-armed from the app
+            ["emergency_stop"] => {
+                if let Some(true) = arg.bool() {
+                    native_emergency_stop(core_handle.state());
+                }
+            }
+            // /avatar/parameters/vibecheck/api/pause
+            ["pause"] => {
+                let Some(seconds) = osc_float(&arg) else {
+                    return;
+                };
+                // Negative and NaN resume, anything too long for a Duration is the longest pause
+                let duration = Duration::try_from_secs_f64(seconds.max(0.0))
+                    .unwrap_or(MAX_PAUSE)
+                    .min(MAX_PAUSE);
+                info!("Pausing toys for {} seconds", duration.as_secs_f64());
+                core_handle
+                    .state()
+                    .0
+                    .lock()
+                    .output_control
+                    .pause_for(duration);
+                if !duration.is_zero() {
+                    stop_all_toys(core_handle);
+                }
+            }
+            // /avatar/parameters/vibecheck/api/toy/<toy name>/<sub id>/enabled
+            ["toy", toy_name, sub_id, "enabled"] => {
+                let Some(state_bool) = arg.bool() else {
+                    return;
+                };
+                alter_online_toys(core_handle, |toy| {
+                    if !is_api_toy(toy, toy_name, sub_id) {
+                        return false;
+                    }
+                    toy.parsed_toy_features
+                        .features
+                        .iter_mut()
+                        .for_each(|feature| feature.feature_enabled = state_bool);
+                    true
+                });
+            }
+            // /avatar/parameters/vibecheck/api/toy/<toy name>/<sub id>/feature/<feature type>/<feature index>/enabled
+            ["toy", toy_name, sub_id, "feature", feature_type, feature_index, "enabled"] => {
+                let Some(state_bool) = arg.bool() else {
+                    return;
+                };
+                let Some(feature) = parse_api_feature(feature_type, feature_index) else {
+                    return;
+                };
+                alter_online_toys(core_handle, |toy| {
+                    if !is_api_toy(toy, toy_name, sub_id) {
+                        return false;
+                    }
+                    let mut altered = false;
+                    toy.parsed_toy_features
+                        .features
+                        .iter_mut()
+                        .filter(|f| (f.feature_type, f.feature_index) == feature)
+                        .for_each(|f| {
+                            f.feature_enabled = state_bool;
+                            altered = true;
+                        });
+                    altered
+                });
+            }
+            // /avatar/parameters/vibecheck/api/toy/<toy name>/<sub id>/feature/<feature type>/<feature index>/mode
+            ["toy", toy_name, sub_id, "feature", feature_type, feature_index, "mode"] => {
+                let Some(mode) = osc_int(&arg).and_then(ProcessingMode::from_api_index) else {
+                    return;
+                };
+                let Some(feature) = parse_api_feature(feature_type, feature_index) else {
+                    return;
+                };
+                alter_online_toys(core_handle, |toy| {
+                    if !is_api_toy(toy, toy_name, sub_id) {
+                        return false;
+                    }
+                    let mut altered = false;
+                    toy.parsed_toy_features
+                        .features
+                        .iter_mut()
+                        .filter(|f| (f.feature_type, f.feature_index) == feature)
+                        .flat_map(|f| f.osc_parameters.iter_mut())
+                        .for_each(|param| {
+                            param.processing_mode_values = ProcessingModeValues::new_from(&mode);
+                            param.processing_mode = mode.clone();
+                            param.latest_level = None;
+                            altered = true;
+                        });
+                    altered
+                });
+            }
+            // /avatar/parameters/vibecheck/api/funscript/<script name>/play
+            // False stops the playing script
+            ["funscript", name, "play"] => {
+                let Some(play) = arg.bool() else {
+                    return;
+                };
+                let control = if play {
+                    FunscriptControl::Play(name.to_string())
+                } else {
+                    FunscriptControl::Stop
+                };
+                let _ = native_funscript_control(core_handle.state(), control);
+            }
+            // /avatar/parameters/vibecheck/api/funscript/stop
+            ["funscript", "stop"] => {
+                if let Some(true) = arg.bool() {
+                    let _ = native_funscript_control(core_handle.state(), FunscriptControl::Stop);
+                }
+            }
+            // /avatar/parameters/vibecheck/api/funscript/seek
+            ["funscript", "seek"] => {
+                let Some(seconds) = osc_float(&arg) else {
+                    return;
+                };
+                let _ =
+                    native_funscript_control(core_handle.state(), FunscriptControl::Seek(seconds));
+            }
+            // /avatar/parameters/vibecheck/api/funscript/speed
+            ["funscript", "speed"] => {
+                let Some(speed) = osc_float(&arg) else {
+                    return;
+                };
+                let _ =
+                    native_funscript_control(core_handle.state(), FunscriptControl::Speed(speed));
+            }
+            _ => debug!("Unknown VibeCheck API endpoint: {}", endpoint.addr),
         }
     }
 }
+
+/*
+ * Applies the mutation to every online toy
+ * Toys the mutation returns true for are sent to the toy handlers and the frontend
+ */
+fn alter_online_toys(core_handle: &CoreHandle, mut mutate: impl FnMut(&mut VCToy) -> bool) {
+    let mut altered_toys = Vec::new();
+    {
+        let vc_pointer = core_handle.state().0.clone();
+        let mut vc_lock = vc_pointer.lock();
+        let Some(toy_manager) = vc_lock.core_toy_manager.as_mut() else {
+            return;
+        };
+
+        toy_manager.online_toys.values_mut().for_each(|toy| {
+            if mutate(toy) {
+                altered_toys.push(toy.clone());
+            }
+        });
+    }
+
+    altered_toys.into_iter().for_each(|toy| {
+        let _ = native_alter_toy(core_handle.state(), core_handle.clone(), toy);
+    });
+}
+
+fn stop_all_toys(core_handle: &CoreHandle) {
+    let vc_pointer = core_handle.state().0.clone();
+    let vc_lock = vc_pointer.lock();
+    // No client while reconnecting to a Buttplug server
+    if let Some(bp_client) = vc_lock.bp_client.as_ref() {
+        let _ = vc_lock
+            .async_rt
+            .block_on(async { bp_client.stop_all_devices().await });
+    }
+}
+
+// Toys are addressed the same way as in vibecheck/osc_data
+fn is_api_toy(toy: &VCToy, toy_name: &str, sub_id: &str) -> bool {
+    toy.osc_name() == toy_name.to_lowercase() && sub_id.parse::<u8>() == Ok(toy.sub_id)
+}
+
+fn parse_api_feature(feature_type: &str, feature_index: &str) -> Option<(VCFeatureType, u32)> {
+    Some((
+        VCFeatureType::from_api_name(feature_type)?,
+        feature_index.parse::<u32>().ok()?,
+    ))
+}
+
+fn osc_float(arg: &OscType) -> Option<f64> {
+    match *arg {
+        OscType::Float(f) => Some(f as f64),
+        OscType::Double(d) => Some(d),
+        OscType::Int(i) => Some(i as f64),
+        OscType::Long(l) => Some(l as f64),
+        _ => None,
+    }
+}
+
+fn osc_int(arg: &OscType) -> Option<i64> {
+    match *arg {
+        OscType::Int(i) => Some(i as i64),
+        OscType::Long(l) => Some(l),
+        _ => None,
+    }
+}
@@ -0,0 +1,403 @@
+/*
+ * Funscripts
+ * Position/time scripts loaded from the Funscripts directory next to ToyConfigs.
+ * Scripts are played on every enabled Linear feature of every listening toy.
+ */
+
+use std::{collections::HashMap, ffi::OsStr, fs, sync::Arc, time::Instant};
+
+use log::{debug, error as logerr, info, warn};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    util::fs::{build_path_dir, file_exists, get_config_dir, path_exists},
+    vcore::host::CoreHandle,
+};
+
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 4.0;
+
+/*
+ * Playback controls from the frontend, the Local API and the OSC API
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub enum FunscriptControl {
+    // Plays a script by name from the start
+    Play(String),
+    Stop,
+    // Jumps to a time in seconds
+    Seek(f64),
+    // Playback speed multiplier
+    Speed(f64),
+}
+
+// Only the parts of the .funscript format used for playback
+#[derive(Deserialize, Debug)]
+struct FunscriptFile {
+    #[serde(default)]
+    inverted: bool,
+    actions: Vec<FunscriptFileAction>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FunscriptFileAction {
+    // Milliseconds since the script started
+    at: f64,
+    // 0 - 100
+    pos: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FunscriptAction {
+    pub at_ms: f64,
+    // 0.0 - 1.0
+    pub position: f64,
+}
+
+#[derive(Debug)]
+pub struct Funscript {
+    // Sorted by time
+    pub actions: Vec<FunscriptAction>,
+}
+
+impl Funscript {
+    fn from_file(file: FunscriptFile) -> Option<Self> {
+        let mut actions = file
+            .actions
+            .into_iter()
+            .filter(|action| action.at >= 0.0)
+            .map(|action| {
+                let position = (action.pos / 100.0).clamp(0.0, 1.0);
+                FunscriptAction {
+                    at_ms: action.at,
+                    position: if file.inverted {
+                        1.0 - position
+                    } else {
+                        position
+                    },
+                }
+            })
+            .collect::<Vec<FunscriptAction>>();
+        if actions.is_empty() {
+            return None;
+        }
+        actions.sort_by(|a, b| a.at_ms.total_cmp(&b.at_ms));
+        Some(Self { actions })
+    }
+}
+
+/*
+ * Every loaded funscript by file name without the extension
+ * Cheap to clone so every toy thread gets its own handle
+ */
+#[derive(Clone, Default)]
+pub struct FunscriptLibrary(Arc<HashMap<String, Arc<Funscript>>>);
+
+impl FunscriptLibrary {
+    pub fn load(core_handle: &CoreHandle) -> Self {
+        let config_dir = match get_config_dir(core_handle) {
+            Ok(d) => d,
+            Err(_) => {
+                logerr!("Failed to get config directory for funscripts");
+                return Self::default();
+            }
+        };
+
+        let funscript_dir = build_path_dir(&[&config_dir, "Funscripts"]);
+        if !path_exists(&funscript_dir) {
+            if let Err(e) = fs::create_dir_all(&funscript_dir) {
+                logerr!("Failed to create funscript directory: {}", e);
+                return Self::default();
+            }
+            info!("Created funscript directory.");
+        }
+
+        let funscript_files = match fs::read_dir(&funscript_dir) {
+            Ok(funscript_files) => funscript_files,
+            Err(e) => {
+                logerr!("Failed to read funscript directory: {}", e);
+                return Self::default();
+            }
+        };
+
+        let mut funscripts = HashMap::new();
+        for f in funscript_files.filter_map(|dir| dir.ok()) {
+            let path = f.path();
+            if !file_exists(&path) || path.extension() != Some(OsStr::new("funscript")) {
+                continue;
+            }
+            let Some(name) = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+
+            let Ok(con) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            match serde_json::from_str::<FunscriptFile>(&con).map(Funscript::from_file) {
+                Ok(Some(funscript)) => {
+                    debug!(
+                        "Loaded funscript: {} ({} actions)",
+                        name,
+                        funscript.actions.len()
+                    );
+                    funscripts.insert(name, Arc::new(funscript));
+                }
+                Ok(None) => warn!("Funscript {} has no actions", name),
+                Err(e) => warn!("Failed to parse funscript {:?}: {}", path, e),
+            }
+        }
+
+        info!("Loaded {} funscripts", funscripts.len());
+        Self(Arc::new(funscripts))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Funscript>> {
+        self.0.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.0.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        names
+    }
+}
+
+#[derive(Debug)]
+struct FunscriptPlayback {
+    funscript: Arc<Funscript>,
+    // Script time when started was set
+    offset_ms: f64,
+    started: Instant,
+    // Action the toy was last sent towards
+    target: Option<usize>,
+}
+
+impl FunscriptPlayback {
+    fn script_time_ms(&self, speed: f64, now: Instant) -> f64 {
+        self.offset_ms + now.duration_since(self.started).as_secs_f64() * 1000.0 * speed
+    }
+}
+
+/*
+ * Plays one funscript for a toy thread
+ * The script clock keeps running while output is held so synced scripts stay in sync
+ */
+#[derive(Debug)]
+pub struct FunscriptPlayer {
+    playback: Option<FunscriptPlayback>,
+    speed: f64,
+}
+
+impl Default for FunscriptPlayer {
+    fn default() -> Self {
+        Self {
+            playback: None,
+            speed: 1.0,
+        }
+    }
+}
+
+impl FunscriptPlayer {
+    pub fn control(&mut self, control: FunscriptControl, funscripts: &FunscriptLibrary) {
+        self.control_at(control, funscripts, Instant::now());
+    }
+
+    fn control_at(
+        &mut self,
+        control: FunscriptControl,
+        funscripts: &FunscriptLibrary,
+        now: Instant,
+    ) {
+        match control {
+            FunscriptControl::Play(name) => {
+                let Some(funscript) = funscripts.get(&name) else {
+                    warn!("Funscript {} is not loaded", name);
+                    return;
+                };
+                info!("Playing funscript {}", name);
+                self.playback = Some(FunscriptPlayback {
+                    funscript,
+                    offset_ms: 0.0,
+                    started: now,
+                    target: None,
+                });
+            }
+            FunscriptControl::Stop => self.playback = None,
+            FunscriptControl::Seek(seconds) => {
+                if let Some(playback) = self.playback.as_mut() {
+                    playback.offset_ms = seconds.max(0.0) * 1000.0;
+                    playback.started = now;
+                    playback.target = None;
+                }
+            }
+            FunscriptControl::Speed(speed) => {
+                if !speed.is_finite() {
+                    return;
+                }
+                let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+                // Rebase the clock so the script continues from where it is now
+                if let Some(playback) = self.playback.as_mut() {
+                    playback.offset_ms = playback.script_time_ms(self.speed, now);
+                    playback.started = now;
+                    playback.target = None;
+                }
+                self.speed = speed;
+            }
+        }
+    }
+
+    /*
+     * Returns the next stroke as (position, duration in ms) when the script reaches a new action
+     * Playback stops after the last action
+     */
+    pub fn tick(&mut self) -> Option<(f64, u32)> {
+        self.tick_at(Instant::now())
+    }
+
+    fn tick_at(&mut self, now: Instant) -> Option<(f64, u32)> {
+        let playback = self.playback.as_mut()?;
+        let script_time_ms = playback.script_time_ms(self.speed, now);
+
+        let Some(target) = playback
+            .funscript
+            .actions
+            .iter()
+            .position(|action| action.at_ms > script_time_ms)
+        else {
+            debug!("Funscript finished");
+            self.playback = None;
+            return None;
+        };
+        if playback.target == Some(target) {
+            return None;
+        }
+        playback.target = Some(target);
+
+        // Remaining time to the action so seeking and late ticks keep the script in time
+        let action = playback.funscript.actions[target];
+        let duration_ms = ((action.at_ms - script_time_ms) / self.speed).max(1.0);
+        Some((action.position, duration_ms as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn action(at: f64, pos: f64) -> FunscriptFileAction {
+        FunscriptFileAction { at, pos }
+    }
+
+    // Up at 1s and back down at 2s
+    fn library() -> FunscriptLibrary {
+        let funscript = Funscript::from_file(FunscriptFile {
+            inverted: false,
+            actions: vec![action(0.0, 0.0), action(1000.0, 100.0), action(2000.0, 0.0)],
+        })
+        .unwrap();
+        FunscriptLibrary(Arc::new(HashMap::from([(
+            "Stroke".to_string(),
+            Arc::new(funscript),
+        )])))
+    }
+
+    fn playing(start: Instant) -> FunscriptPlayer {
+        let mut player = FunscriptPlayer::default();
+        player.control_at(
+            FunscriptControl::Play("Stroke".to_string()),
+            &library(),
+            start,
+        );
+        player
+    }
+
+    fn after(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn parse_sorts_and_inverts() {
+        let funscript = Funscript::from_file(FunscriptFile {
+            inverted: true,
+            actions: vec![action(500.0, 100.0), action(-10.0, 50.0), action(0.0, 25.0)],
+        })
+        .unwrap();
+
+        let actions = funscript
+            .actions
+            .iter()
+            .map(|action| (action.at_ms, action.position))
+            .collect::<Vec<(f64, f64)>>();
+        assert_eq!(actions, vec![(0.0, 0.75), (500.0, 0.0)]);
+    }
+
+    #[test]
+    fn parse_without_actions_fails() {
+        let funscript = Funscript::from_file(FunscriptFile {
+            inverted: false,
+            actions: vec![action(-1.0, 50.0)],
+        });
+        assert!(funscript.is_none());
+    }
+
+    #[test]
+    fn tick_strokes_to_each_action_once() {
+        let start = Instant::now();
+        let mut player = playing(start);
+
+        assert_eq!(player.tick_at(start), Some((1.0, 1000)));
+        assert_eq!(player.tick_at(after(start, 100)), None);
+        assert_eq!(player.tick_at(after(start, 1000)), Some((0.0, 1000)));
+    }
+
+    #[test]
+    fn seek_jumps_to_the_next_action() {
+        let start = Instant::now();
+        let mut player = playing(start);
+        assert!(player.tick_at(start).is_some());
+
+        player.control_at(FunscriptControl::Seek(1.5), &library(), after(start, 200));
+        assert_eq!(player.tick_at(after(start, 200)), Some((0.0, 500)));
+    }
+
+    #[test]
+    fn speed_change_continues_from_the_current_time() {
+        let start = Instant::now();
+        let mut player = playing(start);
+
+        // 500ms in at 1x, then 250ms at 2x is 1s of script time
+        player.control_at(FunscriptControl::Speed(2.0), &library(), after(start, 500));
+        assert_eq!(player.tick_at(after(start, 750)), Some((0.0, 500)));
+
+        player.control_at(
+            FunscriptControl::Speed(f64::NAN),
+            &library(),
+            after(start, 750),
+        );
+        assert_eq!(player.speed, 2.0);
+        player.control_at(
+            FunscriptControl::Speed(100.0),
+            &library(),
+            after(start, 750),
+        );
+        assert_eq!(player.speed, MAX_SPEED);
+    }
+
+    #[test]
+    fn playback_stops_after_the_last_action() {
+        let start = Instant::now();
+        let mut player = playing(start);
+
+        assert_eq!(player.tick_at(after(start, 2500)), None);
+        assert!(player.playback.is_none());
+        assert_eq!(player.tick_at(after(start, 3000)), None);
+    }
+}
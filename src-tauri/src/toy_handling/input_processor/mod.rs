@@ -4,6 +4,7 @@ use std::fmt::Debug;
 use super::mode_processor::core::ModeProcessorInputType;
 
 pub mod penetration_systems;
+pub mod stroke_generator;
 
 /*
  * Penetration System Architecture
@@ -11,7 +11,7 @@ use crate::{
 
 use self::{sps::SPSProcessor, tps::TPSProcessor};
 
-use super::InputProcessor;
+use super::{stroke_generator::StrokeGenerator, InputProcessor};
 
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -30,6 +30,9 @@ pub struct PenetrationSystem {
     pub pen_system_input_filter: Option<Vec<String>>,
     #[serde(skip)]
     pub pen_system_processing_mode_values: ProcessingModeValues,
+    // Motion for Linear features
+    #[serde(skip)]
+    pub stroke_generator: StrokeGenerator,
 }
 
 impl Default for PenetrationSystem {
@@ -40,6 +43,7 @@ impl Default for PenetrationSystem {
             pen_system_processing_mode: ProcessingMode::Raw,
             pen_system_input_filter: None,
             pen_system_processing_mode_values: ProcessingModeValues::Raw,
+            stroke_generator: StrokeGenerator::default(),
         }
     }
 }
@@ -77,6 +81,7 @@ impl FromFrontend<FePenetrationSystem> for PenetrationSystem {
         let backend_pspm = frontend_type.pen_system_processing_mode.to_backend();
         self.pen_system_processing_mode_values = ProcessingModeValues::new_from(&backend_pspm);
         self.pen_system_processing_mode = backend_pspm;
+        self.stroke_generator = StrokeGenerator::default();
 
         // Backend expects None if no tags (In the future make tuti send null for input filter from frontend)
         if frontend_type.pen_system_input_filter.as_ref().is_some_and(|v| v.is_empty()) {
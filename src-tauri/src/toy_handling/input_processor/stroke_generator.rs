@@ -0,0 +1,188 @@
+use std::time::Instant;
+
+use log::trace;
+
+use crate::toy_handling::toyops::LevelTweaks;
+
+// Expected time between depth readings until it has been measured
+const DEFAULT_INPUT_INTERVAL_MS: f64 = 100.0;
+const MIN_INPUT_INTERVAL_MS: f64 = 10.0;
+// Longer gaps between readings start tracking over
+const MAX_INPUT_INTERVAL_MS: f64 = 500.0;
+// How much of each new reading goes into the tracked velocity and input interval
+const VELOCITY_SMOOTHING: f64 = 0.5;
+const INTERVAL_SMOOTHING: f64 = 0.2;
+
+/*
+ * Depth to stroke motion generator for Linear features driven by a penetration system
+ * Tracks the depth velocity and moves towards where the depth will be at the next reading,
+ * timed by how often readings come in, instead of where the depth was.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct StrokeGenerator {
+    // Latest depth reading and when it came in
+    last_depth: Option<(f64, Instant)>,
+    // Depth change per millisecond
+    velocity: f64,
+    // Average time between depth readings in milliseconds
+    input_interval_ms: Option<f64>,
+    // Position of the last move
+    last_position: f64,
+}
+
+impl StrokeGenerator {
+    /*
+     * Returns the next move as (position, duration in ms) for a depth reading (0.0 - 1.0)
+     * Depth is mapped into the stroke range between the minimum and maximum level
+     * A full stroke never takes less than linear_position_speed ms
+     */
+    pub fn update(&mut self, depth: f64, levels: &LevelTweaks) -> (f64, u32) {
+        self.update_at(depth, levels, Instant::now())
+    }
+
+    /*
+     * Returns the move to a level produced between depth readings (Processing modes on the toy tick)
+     * The level is not a reading so the velocity and input interval are left alone
+     */
+    pub fn follow(&mut self, depth: f64, levels: &LevelTweaks) -> (f64, u32) {
+        self.stroke_to(depth.clamp(0.0, 1.0), levels)
+    }
+
+    fn update_at(&mut self, depth: f64, levels: &LevelTweaks, now: Instant) -> (f64, u32) {
+        let depth = depth.clamp(0.0, 1.0);
+
+        match self.last_depth {
+            Some((last_depth, last_time)) => {
+                let elapsed_ms = now.duration_since(last_time).as_secs_f64() * 1000.0;
+                if elapsed_ms > MAX_INPUT_INTERVAL_MS {
+                    self.velocity = 0.0;
+                } else {
+                    let elapsed_ms = elapsed_ms.max(MIN_INPUT_INTERVAL_MS);
+                    let velocity = (depth - last_depth) / elapsed_ms;
+                    self.velocity += (velocity - self.velocity) * VELOCITY_SMOOTHING;
+
+                    let input_interval_ms = self.input_interval_ms.unwrap_or(elapsed_ms);
+                    self.input_interval_ms = Some(
+                        input_interval_ms + (elapsed_ms - input_interval_ms) * INTERVAL_SMOOTHING,
+                    );
+                }
+            }
+            None => self.velocity = 0.0,
+        }
+        self.last_depth = Some((depth, now));
+
+        // Nothing to predict once out
+        if depth == 0.0 {
+            self.velocity = 0.0;
+        }
+
+        let predicted_depth = (depth + self.velocity * self.input_interval_ms()).clamp(0.0, 1.0);
+        trace!(
+            "Stroke: depth {:.3} predicted {:.3}",
+            depth,
+            predicted_depth
+        );
+        self.stroke_to(predicted_depth, levels)
+    }
+
+    fn input_interval_ms(&self) -> f64 {
+        self.input_interval_ms.unwrap_or(DEFAULT_INPUT_INTERVAL_MS)
+    }
+
+    // Moves to a depth over the input interval, or slower if the stroke speed needs more time
+    fn stroke_to(&mut self, depth: f64, levels: &LevelTweaks) -> (f64, u32) {
+        let position = levels.minimum_level + depth * (levels.maximum_level - levels.minimum_level);
+
+        let stroke_limit_ms =
+            (position - self.last_position).abs() * levels.linear_position_speed as f64;
+        let duration_ms = self.input_interval_ms().max(stroke_limit_ms);
+        self.last_position = position;
+
+        trace!("Stroke: position {:.3} over {:.0}ms", position, duration_ms);
+        (position, duration_ms.round() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn levels(linear_position_speed: u32) -> LevelTweaks {
+        LevelTweaks {
+            minimum_level: 0.0,
+            maximum_level: 1.0,
+            linear_position_speed,
+            ..Default::default()
+        }
+    }
+
+    fn assert_move(actual: (f64, u32), position: f64, duration_ms: u32) {
+        assert!(
+            (actual.0 - position).abs() < 1e-9 && actual.1 == duration_ms,
+            "Expected {:?} but got {:?}",
+            (position, duration_ms),
+            actual
+        );
+    }
+
+    #[test]
+    fn predicts_the_next_reading() {
+        let start = Instant::now();
+        let after = |ms| start + Duration::from_millis(ms);
+        let mut generator = StrokeGenerator::default();
+
+        assert_move(generator.update_at(0.2, &levels(100), start), 0.2, 100);
+        // Half of the measured velocity over one 100ms input interval
+        assert_move(generator.update_at(0.4, &levels(100), after(100)), 0.5, 100);
+    }
+
+    #[test]
+    fn stroke_speed_limits_the_duration() {
+        let start = Instant::now();
+        let mut generator = StrokeGenerator::default();
+
+        assert_move(generator.update_at(0.5, &levels(1000), start), 0.5, 500);
+        // Short moves take the input interval
+        let later = start + Duration::from_secs(1);
+        assert_move(generator.update_at(0.55, &levels(1000), later), 0.55, 100);
+    }
+
+    #[test]
+    fn stroke_range_maps_depth() {
+        let mut generator = StrokeGenerator::default();
+        let levels = LevelTweaks {
+            minimum_level: 0.2,
+            maximum_level: 0.6,
+            ..levels(0)
+        };
+        assert_move(generator.update_at(0.5, &levels, Instant::now()), 0.4, 100);
+    }
+
+    #[test]
+    fn gaps_and_pulling_out_stop_predicting() {
+        let start = Instant::now();
+        let after = |ms| start + Duration::from_millis(ms);
+        let mut generator = StrokeGenerator::default();
+
+        generator.update_at(0.2, &levels(0), start);
+        generator.update_at(0.4, &levels(0), after(100));
+        assert_move(generator.update_at(0.5, &levels(0), after(1000)), 0.5, 100);
+
+        generator.update_at(0.4, &levels(0), after(1100));
+        assert_move(generator.update_at(0.0, &levels(0), after(1200)), 0.0, 100);
+    }
+
+    #[test]
+    fn following_levels_keeps_the_estimates() {
+        let start = Instant::now();
+        let after = |ms| start + Duration::from_millis(ms);
+        let mut generator = StrokeGenerator::default();
+
+        generator.update_at(0.2, &levels(0), start);
+        // Levels between readings move without a prediction
+        assert_move(generator.follow(0.3, &levels(0)), 0.3, 100);
+        assert_move(generator.update_at(0.4, &levels(0), after(100)), 0.5, 100);
+    }
+}
@@ -3,11 +3,18 @@ use ts_rs::TS;
 
 use crate::vcore::ipc::call_plane::ToyUpdate;
 
+use self::funscript::FunscriptControl;
+
 pub mod errors;
+pub mod funscript;
 pub mod input_processor;
 pub mod mode_processor;
+pub mod modulation;
 pub mod osc_processor;
+pub mod output_control;
+pub mod patterns;
 pub mod runtime;
+pub mod safety;
 pub mod toy_command_processor;
 pub mod toy_manager;
 pub mod toyops;
@@ -17,6 +24,7 @@ pub enum ToySig {
     //ToyCommand(ToyFeature),
     UpdateToy(ToyUpdate),
     OSCMsg(rosc::OscMessage),
+    Funscript(FunscriptControl),
 }
 
 #[derive(Debug, Serialize, Deserialize, TS, Clone)]
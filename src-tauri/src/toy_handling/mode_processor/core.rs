@@ -1,3 +1,6 @@
+use crate::toy_handling::patterns::Pattern;
+use crate::toy_handling::patterns::PatternLibrary;
+use crate::toy_handling::toyops::IntMapping;
 use crate::toy_handling::toyops::LevelTweaks;
 use crate::toy_handling::toyops::ProcessingModeValues;
 use crate::toy_handling::toyops::ToyParameter;
@@ -7,9 +10,20 @@ use serde::Deserialize;
 use serde::Serialize;
 use ts_rs::TS;
 
-use std::time::Instant;
+use std::sync::Arc;
 
+use super::mode_envelope::parse_envelope;
+use super::mode_envelope::tick_envelope;
+use super::mode_filter::parse_filter;
+use super::mode_filter::tick_filter;
+use super::mode_filter::FilterKind;
+use super::mode_gate::parse_gate;
+use super::mode_gate::tick_gate;
+use super::mode_pattern::parse_pattern;
+use super::mode_pattern::tick_pattern;
 use super::mode_rate::parse_rate;
+use super::mode_rate::parse_rate_trigger;
+use super::mode_rate::tick_rate;
 use super::mode_rate::RateParser;
 use super::mode_smooth::parse_smoothing;
 use super::mode_smooth::SmoothParser;
@@ -24,6 +38,7 @@ pub enum ModeProcessorInput<'processor> {
 pub enum ModeProcessorInputType {
     Float(f64),
     Boolean(bool),
+    Int(i64),
 }
 
 impl ModeProcessorInputType {
@@ -40,12 +55,20 @@ impl ModeProcessorInputType {
             _ => None,
         }
     }
+
+    pub fn try_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
 }
 
 pub async fn mode_processor(
     input: ModeProcessorInput<'_>,
-    feature_levels: LevelTweaks,
+    feature_levels: &LevelTweaks,
     flip_input: bool,
+    patterns: &PatternLibrary,
 ) -> Option<f64> {
     // Parse if input is from an Input Processor or raw input
     debug!("Got Mode Processor Input: {:#?}", input);
@@ -60,6 +83,7 @@ pub async fn mode_processor(
                         processing_mode_values,
                         feature_levels,
                         flip_input,
+                        None,
                     )
                     .await
                 }
@@ -70,9 +94,22 @@ pub async fn mode_processor(
                         processing_mode_values,
                         feature_levels,
                         flip_input,
+                        None,
                     )
                     .await
                 } // Input Processor & Boolean
+                ModeProcessorInputType::Int(i_input) => {
+                    // Input Processor & Int
+                    // Input processors have no parameter mapping so use the default mapping
+                    mode_processor_logic(
+                        IntMapping::default().map_int(i_input),
+                        processing_mode_values,
+                        feature_levels,
+                        flip_input,
+                        None,
+                    )
+                    .await
+                }
             }
         }
         // Input is from parameter parsing
@@ -85,6 +122,7 @@ pub async fn mode_processor(
                         &mut toy_parameter.processing_mode_values,
                         feature_levels,
                         flip_input,
+                        patterns.get(&toy_parameter.pattern),
                     )
                     .await
                 }
@@ -95,9 +133,22 @@ pub async fn mode_processor(
                         &mut toy_parameter.processing_mode_values,
                         feature_levels,
                         flip_input,
+                        patterns.get(&toy_parameter.pattern),
                     )
                     .await
                 } // Raw Input & Boolean
+                ModeProcessorInputType::Int(i_input) => {
+                    // Raw Input & Int
+                    // Map the int to a float or boolean with the parameter's int mapping
+                    mode_processor_logic(
+                        toy_parameter.int_mapping.map_int(i_input),
+                        &mut toy_parameter.processing_mode_values,
+                        feature_levels,
+                        flip_input,
+                        patterns.get(&toy_parameter.pattern),
+                    )
+                    .await
+                }
             }
         }
     }
@@ -106,8 +157,9 @@ pub async fn mode_processor(
 async fn mode_processor_logic(
     input: ModeProcessorInputType,
     processor: &mut ProcessingModeValues,
-    feature_levels: LevelTweaks,
+    feature_levels: &LevelTweaks,
     flip_input: bool,
+    pattern: Option<Arc<Pattern>>,
 ) -> Option<f64> {
     // Process logic for each mode processing type
     match processor {
@@ -124,6 +176,7 @@ async fn mode_processor_logic(
                     Some(0.0)
                 }
             }
+            ModeProcessorInputType::Int(_i) => None, // Ints are mapped before mode processing
         },
         // Smoothing Mode Handling
         // Smooth = do smoothing logic with input and processor
@@ -148,33 +201,31 @@ async fn mode_processor_logic(
                     }
                 }
                 ModeProcessorInputType::Boolean(_b) => None, // No support for Smoothing mode and Boolean
+                ModeProcessorInputType::Int(_i) => None, // Ints are mapped before mode processing
             }
             // Return processed input
         }
         // Rate Mode Handling
-        ProcessingModeValues::Rate(values) => {
-            //trace!("parse_rate()");
-            // Need to set rate_timestamp when feature enabled
-            if values.rate_timestamp.is_none() {
-                values.rate_timestamp = Some(Instant::now());
-            }
-
-            match input {
-                ModeProcessorInputType::Float(float_level) => {
-                    match parse_rate(values, feature_levels.rate_tune, float_level, flip_input) {
-                        RateParser::SkipZero => Some(0.), // Skip zero and send to toy
-                        RateParser::RateCalculated(f_out, reset_timer) => {
-                            // Rate calculated reset timer and send calculated value to toy
-                            if reset_timer {
-                                values.rate_timestamp = Some(Instant::now())
-                            }
-                            Some(f_out)
-                        }
-                    }
+        // The rate level decays on the toy tick (mode_processor_tick)
+        ProcessingModeValues::Rate(values) => match input {
+            ModeProcessorInputType::Float(float_level) => {
+                match parse_rate(
+                    values,
+                    feature_levels.rate_decay_per_second,
+                    float_level,
+                    flip_input,
+                ) {
+                    RateParser::SkipZero => Some(0.), // Skip zero and send to toy
+                    RateParser::RateCalculated(f_out) => Some(f_out),
                 }
-                ModeProcessorInputType::Boolean(_b) => None, // No support for Rate and Boolean
             }
-        }
+            ModeProcessorInputType::Boolean(b) => Some(parse_rate_trigger(
+                values,
+                feature_levels.rate_decay_per_second,
+                b,
+            )),
+            ModeProcessorInputType::Int(_i) => None, // Ints are mapped before mode processing
+        },
         // Constant Mode Handling
         ProcessingModeValues::Constant => match input {
             ModeProcessorInputType::Float(float_level) => {
@@ -191,6 +242,49 @@ async fn mode_processor_logic(
                     Some(0.0)
                 }
             }
+            ModeProcessorInputType::Int(_i) => None, // Ints are mapped before mode processing
         },
+        // Pattern Mode Handling
+        ProcessingModeValues::Pattern(values) => parse_pattern(values, pattern, input),
+        // Time based smoothing Handling
+        ProcessingModeValues::Ema(values) => {
+            parse_filter(values, FilterKind::Ema, feature_levels, input, flip_input)
+        }
+        ProcessingModeValues::Damped(values) => parse_filter(
+            values,
+            FilterKind::Damped,
+            feature_levels,
+            input,
+            flip_input,
+        ),
+        // Envelope Mode Handling
+        // The envelope moves between stages on the toy tick (mode_processor_tick)
+        ProcessingModeValues::Envelope(values) => parse_envelope(values, feature_levels, input),
+        // Gate Mode Handling
+        // Switches held back by the minimum on/off times happen on the toy tick
+        ProcessingModeValues::Gate(values) => parse_gate(values, feature_levels, input, flip_input),
+    }
+}
+
+/*
+ * Advances modes that change level without new input
+ * Returns the mode's level for this tick or None if it has nothing to send
+ */
+pub fn mode_processor_tick(
+    processor: &mut ProcessingModeValues,
+    feature_levels: &LevelTweaks,
+) -> Option<f64> {
+    match processor {
+        ProcessingModeValues::Pattern(values) => tick_pattern(values),
+        ProcessingModeValues::Rate(values) => {
+            tick_rate(values, feature_levels.rate_decay_per_second)
+        }
+        ProcessingModeValues::Ema(values) => tick_filter(values, FilterKind::Ema, feature_levels),
+        ProcessingModeValues::Damped(values) => {
+            tick_filter(values, FilterKind::Damped, feature_levels)
+        }
+        ProcessingModeValues::Envelope(values) => tick_envelope(values, feature_levels),
+        ProcessingModeValues::Gate(values) => tick_gate(values, feature_levels),
+        _ => None,
     }
 }
@@ -1,3 +1,7 @@
 pub mod core;
+pub mod mode_envelope;
+pub mod mode_filter;
+pub mod mode_gate;
+pub mod mode_pattern;
 pub mod mode_rate;
 pub mod mode_smooth;
@@ -0,0 +1,217 @@
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use crate::toy_handling::{
+    mode_processor::core::ModeProcessorInputType,
+    toyops::{EnvelopeProcessingValues, EnvelopeStage, LevelTweaks},
+};
+
+/*
+ * Starts the attack on a trigger and the release when the trigger ends
+ * Floats trigger at 0.5 like Constant mode
+ * Returns the envelope level right now
+ */
+#[inline(always)]
+pub fn parse_envelope(
+    processor: &mut EnvelopeProcessingValues,
+    feature_levels: &LevelTweaks,
+    input: ModeProcessorInputType,
+) -> Option<f64> {
+    let triggered = match input {
+        ModeProcessorInputType::Float(float_level) => float_level >= 0.5,
+        ModeProcessorInputType::Boolean(b) => b,
+        ModeProcessorInputType::Int(_i) => return None, // Ints are mapped before mode processing
+    };
+
+    let now = Instant::now();
+    advance_envelope(processor, feature_levels, now);
+    match (triggered, processor.stage) {
+        // Retriggering during the release attacks from the current level
+        (true, EnvelopeStage::Idle | EnvelopeStage::Release) => {
+            start_stage(processor, EnvelopeStage::Attack, now)
+        }
+        (false, EnvelopeStage::Attack | EnvelopeStage::Decay | EnvelopeStage::Sustain) => {
+            start_stage(processor, EnvelopeStage::Release, now)
+        }
+        _ => {}
+    }
+    Some(processor.level)
+}
+
+/*
+ * Moves the envelope along on the toy tick
+ * Returns None while idle or holding the sustain level
+ */
+#[inline(always)]
+pub fn tick_envelope(
+    processor: &mut EnvelopeProcessingValues,
+    feature_levels: &LevelTweaks,
+) -> Option<f64> {
+    if let EnvelopeStage::Idle | EnvelopeStage::Sustain = processor.stage {
+        return None;
+    }
+    advance_envelope(processor, feature_levels, Instant::now());
+    trace!("Envelope {:?}: {:.3}", processor.stage, processor.level);
+    Some(processor.level)
+}
+
+fn start_stage(processor: &mut EnvelopeProcessingValues, stage: EnvelopeStage, started: Instant) {
+    processor.stage = stage;
+    processor.stage_start_level = processor.level;
+    processor.stage_started = started;
+}
+
+fn advance_envelope(
+    processor: &mut EnvelopeProcessingValues,
+    feature_levels: &LevelTweaks,
+    now: Instant,
+) {
+    let sustain_level = feature_levels.envelope_sustain_level.clamp(0.0, 1.0);
+
+    // Late ticks can finish more than one stage
+    loop {
+        let (length_ms, target, next_stage) = match processor.stage {
+            EnvelopeStage::Idle => {
+                processor.level = 0.0;
+                return;
+            }
+            EnvelopeStage::Sustain => {
+                processor.level = sustain_level;
+                return;
+            }
+            EnvelopeStage::Attack => (feature_levels.envelope_attack_ms, 1.0, EnvelopeStage::Decay),
+            EnvelopeStage::Decay => (
+                feature_levels.envelope_decay_ms,
+                sustain_level,
+                EnvelopeStage::Sustain,
+            ),
+            EnvelopeStage::Release => {
+                (feature_levels.envelope_release_ms, 0.0, EnvelopeStage::Idle)
+            }
+        };
+        let length_ms = length_ms.max(0.0);
+
+        let elapsed_ms = now.duration_since(processor.stage_started).as_secs_f64() * 1000.0;
+        if elapsed_ms < length_ms {
+            let progress = elapsed_ms / length_ms;
+            processor.level =
+                processor.stage_start_level + (target - processor.stage_start_level) * progress;
+            return;
+        }
+
+        // The next stage starts when this one ended, not on this tick
+        processor.level = target;
+        let stage_ended = processor.stage_started + Duration::from_secs_f64(length_ms / 1000.0);
+        start_stage(processor, next_stage, stage_ended);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels() -> LevelTweaks {
+        LevelTweaks {
+            envelope_attack_ms: 100.0,
+            envelope_decay_ms: 100.0,
+            envelope_sustain_level: 0.5,
+            envelope_release_ms: 200.0,
+            ..Default::default()
+        }
+    }
+
+    fn in_stage(stage: EnvelopeStage, level: f64, started: Instant) -> EnvelopeProcessingValues {
+        let mut processor = EnvelopeProcessingValues {
+            level,
+            ..Default::default()
+        };
+        start_stage(&mut processor, stage, started);
+        processor
+    }
+
+    fn after(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    fn assert_level(processor: &EnvelopeProcessingValues, stage: EnvelopeStage, level: f64) {
+        assert_eq!(processor.stage, stage);
+        assert!(
+            (processor.level - level).abs() < 1e-9,
+            "Expected {} but got {}",
+            level,
+            processor.level
+        );
+    }
+
+    #[test]
+    fn attack_ramps_up() {
+        let start = Instant::now();
+        let mut processor = in_stage(EnvelopeStage::Attack, 0.0, start);
+
+        advance_envelope(&mut processor, &levels(), after(start, 50));
+        assert_level(&processor, EnvelopeStage::Attack, 0.5);
+    }
+
+    #[test]
+    fn late_tick_finishes_stages_in_time() {
+        let start = Instant::now();
+        let mut processor = in_stage(EnvelopeStage::Attack, 0.0, start);
+
+        // The decay started when the attack ended at 100ms
+        advance_envelope(&mut processor, &levels(), after(start, 150));
+        assert_level(&processor, EnvelopeStage::Decay, 0.75);
+
+        let mut processor = in_stage(EnvelopeStage::Attack, 0.0, start);
+        advance_envelope(&mut processor, &levels(), after(start, 250));
+        assert_level(&processor, EnvelopeStage::Sustain, 0.5);
+    }
+
+    #[test]
+    fn release_falls_to_idle() {
+        let start = Instant::now();
+        let mut processor = in_stage(EnvelopeStage::Release, 0.5, start);
+
+        advance_envelope(&mut processor, &levels(), after(start, 100));
+        assert_level(&processor, EnvelopeStage::Release, 0.25);
+        advance_envelope(&mut processor, &levels(), after(start, 300));
+        assert_level(&processor, EnvelopeStage::Idle, 0.0);
+    }
+
+    #[test]
+    fn triggers_switch_stages() {
+        let mut processor = EnvelopeProcessingValues::default();
+
+        parse_envelope(
+            &mut processor,
+            &levels(),
+            ModeProcessorInputType::Float(1.0),
+        );
+        assert_eq!(processor.stage, EnvelopeStage::Attack);
+        parse_envelope(
+            &mut processor,
+            &levels(),
+            ModeProcessorInputType::Float(0.0),
+        );
+        assert_eq!(processor.stage, EnvelopeStage::Release);
+
+        // Retriggering attacks from the level it was released at
+        let mut processor = in_stage(EnvelopeStage::Release, 0.4, Instant::now());
+        parse_envelope(
+            &mut processor,
+            &levels(),
+            ModeProcessorInputType::Boolean(true),
+        );
+        assert_eq!(processor.stage, EnvelopeStage::Attack);
+        assert!(processor.stage_start_level <= 0.4 && processor.stage_start_level > 0.3);
+    }
+
+    #[test]
+    fn tick_is_quiet_while_idle_or_sustaining() {
+        let mut processor = EnvelopeProcessingValues::default();
+        assert_eq!(tick_envelope(&mut processor, &levels()), None);
+
+        let mut processor = in_stage(EnvelopeStage::Sustain, 0.5, Instant::now());
+        assert_eq!(tick_envelope(&mut processor, &levels()), None);
+    }
+}
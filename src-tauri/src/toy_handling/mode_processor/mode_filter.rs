@@ -0,0 +1,244 @@
+use std::time::Instant;
+
+use log::debug;
+
+use crate::toy_handling::{
+    mode_processor::core::ModeProcessorInputType,
+    toyops::{FilterProcessingValues, LevelTweaks},
+};
+
+// Longest step a filter takes at once so a late tick does not jump to the target
+const MAX_STEP_MS: f64 = 250.0;
+// Filters snap to the target once this close
+const SETTLE_DISTANCE: f64 = 0.001;
+
+#[derive(Clone, Copy, Debug)]
+pub enum FilterKind {
+    // Exponential moving average with a half-life
+    Ema,
+    // Critically damped spring with a smoothing time
+    Damped,
+}
+
+/*
+ * Sets a new target for the filter
+ * Returns the filtered level right now. Like Smooth mode, 0.0 input stops right away
+ */
+#[inline(always)]
+pub fn parse_filter(
+    processor: &mut FilterProcessingValues,
+    kind: FilterKind,
+    feature_levels: &LevelTweaks,
+    input: ModeProcessorInputType,
+    flip_float: bool,
+) -> Option<f64> {
+    let target = match input {
+        ModeProcessorInputType::Float(float_level) => float_level,
+        ModeProcessorInputType::Boolean(b) => {
+            if b {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        ModeProcessorInputType::Int(_i) => return None, // Ints are mapped before mode processing
+    };
+
+    if !flip_float && target <= 0.0 || flip_float && target >= 1.0 {
+        debug!("Bypassing filter: {:.5}", target);
+        *processor = FilterProcessingValues {
+            level: target,
+            ..Default::default()
+        };
+        return Some(target);
+    }
+
+    // Catch up to now towards the old target before following the new one
+    advance_filter(processor, kind, feature_levels, Instant::now());
+    processor.target = Some(target);
+    processor.settled = false;
+    Some(processor.level)
+}
+
+/*
+ * Moves the filter towards its target on the toy tick
+ * Returns None once the target is reached so idle features send nothing
+ */
+#[inline(always)]
+pub fn tick_filter(
+    processor: &mut FilterProcessingValues,
+    kind: FilterKind,
+    feature_levels: &LevelTweaks,
+) -> Option<f64> {
+    if processor.settled || processor.target.is_none() {
+        return None;
+    }
+    advance_filter(processor, kind, feature_levels, Instant::now());
+    Some(processor.level)
+}
+
+fn advance_filter(
+    processor: &mut FilterProcessingValues,
+    kind: FilterKind,
+    feature_levels: &LevelTweaks,
+    now: Instant,
+) {
+    let last_update = processor.last_update.replace(now);
+    let Some(target) = processor.target else {
+        return;
+    };
+    let Some(last_update) = last_update else {
+        return;
+    };
+
+    let step_ms = (now.duration_since(last_update).as_secs_f64() * 1000.0).min(MAX_STEP_MS);
+    let smooth_ms = feature_levels.smooth_half_life_ms.max(1.0);
+
+    match kind {
+        FilterKind::Ema => {
+            let alpha = 1.0 - 0.5_f64.powf(step_ms / smooth_ms);
+            processor.level += (target - processor.level) * alpha;
+        }
+        FilterKind::Damped => {
+            let omega = 2.0 / smooth_ms;
+            let x = omega * step_ms;
+            let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+            let change = processor.level - target;
+            let temp = (processor.velocity + omega * change) * step_ms;
+            processor.velocity = (processor.velocity - omega * temp) * decay;
+            processor.level = target + (change + temp) * decay;
+        }
+    }
+
+    if (target - processor.level).abs() < SETTLE_DISTANCE
+        && processor.velocity.abs() * step_ms < SETTLE_DISTANCE
+    {
+        processor.level = target;
+        processor.velocity = 0.0;
+        processor.settled = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn levels() -> LevelTweaks {
+        LevelTweaks {
+            smooth_half_life_ms: 100.0,
+            ..Default::default()
+        }
+    }
+
+    // Following a target from 0.0 since start
+    fn following(target: f64, start: Instant) -> FilterProcessingValues {
+        FilterProcessingValues {
+            target: Some(target),
+            last_update: Some(start),
+            ..Default::default()
+        }
+    }
+
+    fn after(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn zero_input_bypasses_the_filter() {
+        let mut processor = following(1.0, Instant::now());
+        processor.level = 0.5;
+
+        let level = parse_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            ModeProcessorInputType::Float(0.0),
+            false,
+        );
+        assert_eq!(level, Some(0.0));
+        assert_eq!(processor.target, None);
+        assert_eq!(
+            tick_filter(&mut processor, FilterKind::Ema, &levels()),
+            None
+        );
+
+        // Flipped inputs stop at 1.0
+        let level = parse_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            ModeProcessorInputType::Float(1.0),
+            true,
+        );
+        assert_eq!(level, Some(1.0));
+    }
+
+    #[test]
+    fn new_input_starts_from_the_current_level() {
+        let mut processor = FilterProcessingValues::default();
+        let level = parse_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            ModeProcessorInputType::Float(1.0),
+            false,
+        );
+        assert_eq!(level, Some(0.0));
+        assert_eq!(processor.target, Some(1.0));
+    }
+
+    #[test]
+    fn ema_halves_the_distance_every_half_life() {
+        let start = Instant::now();
+        let mut processor = following(1.0, start);
+
+        advance_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            after(start, 100),
+        );
+        assert_eq!(processor.level, 0.5);
+        advance_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            after(start, 200),
+        );
+        assert_eq!(processor.level, 0.75);
+    }
+
+    #[test]
+    fn late_ticks_take_a_limited_step() {
+        let start = Instant::now();
+        let mut processor = following(1.0, start);
+
+        advance_filter(
+            &mut processor,
+            FilterKind::Ema,
+            &levels(),
+            after(start, 1000),
+        );
+        let max_step = 1.0 - 0.5_f64.powf(MAX_STEP_MS / 100.0);
+        assert!((processor.level - max_step).abs() < 1e-9);
+    }
+
+    #[test]
+    fn filters_settle_on_the_target() {
+        for kind in [FilterKind::Ema, FilterKind::Damped] {
+            let start = Instant::now();
+            let mut processor = following(1.0, start);
+
+            let mut ms = 0;
+            while !processor.settled && ms < 10_000 {
+                ms += 20;
+                advance_filter(&mut processor, kind, &levels(), after(start, ms));
+            }
+            assert!(processor.settled, "{:?} did not settle", kind);
+            assert_eq!(processor.level, 1.0);
+            assert_eq!(tick_filter(&mut processor, kind, &levels()), None);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::Instant;
+
+use log::debug;
+
+use crate::toy_handling::{
+    mode_processor::core::ModeProcessorInputType,
+    toyops::{GateProcessingValues, LevelTweaks},
+};
+
+/*
+ * Opens the gate when the input reaches the upper threshold and closes it below the lower one
+ * Floats are compared after flipping so a flipped input opens the gate when it falls
+ * Returns the gate output right now
+ */
+#[inline(always)]
+pub fn parse_gate(
+    processor: &mut GateProcessingValues,
+    feature_levels: &LevelTweaks,
+    input: ModeProcessorInputType,
+    flip_float: bool,
+) -> Option<f64> {
+    let activity = match input {
+        ModeProcessorInputType::Float(float_level) => {
+            processor.input = float_level;
+            processor.closed_level = if flip_float { 1.0 } else { 0.0 };
+            if flip_float {
+                1.0 - float_level
+            } else {
+                float_level
+            }
+        }
+        ModeProcessorInputType::Boolean(b) => {
+            let level = if b { 1.0 } else { 0.0 };
+            processor.input = level;
+            processor.closed_level = 0.0;
+            level
+        }
+        ModeProcessorInputType::Int(_i) => return None, // Ints are mapped before mode processing
+    };
+
+    let upper = feature_levels.gate_upper_threshold;
+    let lower = feature_levels.gate_lower_threshold.min(upper);
+    processor.wanted_open = if processor.open {
+        activity >= lower
+    } else {
+        activity >= upper
+    };
+
+    switch_gate(processor, feature_levels, Instant::now());
+    Some(gate_output(processor, feature_levels))
+}
+
+/*
+ * Switches the gate on the toy tick once a hold time that blocked an input has passed
+ * Returns None when the gate did not switch
+ */
+#[inline(always)]
+pub fn tick_gate(
+    processor: &mut GateProcessingValues,
+    feature_levels: &LevelTweaks,
+) -> Option<f64> {
+    if !switch_gate(processor, feature_levels, Instant::now()) {
+        return None;
+    }
+    Some(gate_output(processor, feature_levels))
+}
+
+// Returns true if the gate switched
+fn switch_gate(
+    processor: &mut GateProcessingValues,
+    feature_levels: &LevelTweaks,
+    now: Instant,
+) -> bool {
+    if processor.open == processor.wanted_open {
+        return false;
+    }
+
+    let hold_ms = if processor.open {
+        feature_levels.gate_min_on_ms
+    } else {
+        feature_levels.gate_min_off_ms
+    };
+    if let Some(switched) = processor.switched {
+        if now.duration_since(switched).as_secs_f64() * 1000.0 < hold_ms {
+            return false;
+        }
+    }
+
+    processor.open = processor.wanted_open;
+    processor.switched = Some(now);
+    debug!("Gate {}", if processor.open { "opened" } else { "closed" });
+    true
+}
+
+fn gate_output(processor: &GateProcessingValues, feature_levels: &LevelTweaks) -> f64 {
+    if !processor.open {
+        processor.closed_level
+    } else if feature_levels.gate_passthrough {
+        processor.input
+    } else {
+        feature_levels.gate_fixed_level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn levels(min_on_ms: f64, min_off_ms: f64) -> LevelTweaks {
+        LevelTweaks {
+            gate_lower_threshold: 0.3,
+            gate_upper_threshold: 0.6,
+            gate_passthrough: false,
+            gate_fixed_level: 0.8,
+            gate_min_on_ms: min_on_ms,
+            gate_min_off_ms: min_off_ms,
+            ..Default::default()
+        }
+    }
+
+    fn gate(
+        processor: &mut GateProcessingValues,
+        feature_levels: &LevelTweaks,
+        level: f64,
+    ) -> Option<f64> {
+        parse_gate(
+            processor,
+            feature_levels,
+            ModeProcessorInputType::Float(level),
+            false,
+        )
+    }
+
+    #[test]
+    fn hysteresis() {
+        let feature_levels = levels(0.0, 0.0);
+        let mut processor = GateProcessingValues::default();
+
+        assert_eq!(gate(&mut processor, &feature_levels, 0.5), Some(0.0));
+        assert_eq!(gate(&mut processor, &feature_levels, 0.6), Some(0.8));
+        // Stays open between the thresholds
+        assert_eq!(gate(&mut processor, &feature_levels, 0.4), Some(0.8));
+        assert_eq!(gate(&mut processor, &feature_levels, 0.2), Some(0.0));
+        assert_eq!(gate(&mut processor, &feature_levels, 0.5), Some(0.0));
+    }
+
+    #[test]
+    fn passthrough_and_flip() {
+        let feature_levels = LevelTweaks {
+            gate_passthrough: true,
+            ..levels(0.0, 0.0)
+        };
+        let mut processor = GateProcessingValues::default();
+
+        assert_eq!(gate(&mut processor, &feature_levels, 0.7), Some(0.7));
+
+        let mut processor = GateProcessingValues::default();
+        let flipped = |processor: &mut GateProcessingValues, level| {
+            parse_gate(
+                processor,
+                &feature_levels,
+                ModeProcessorInputType::Float(level),
+                true,
+            )
+        };
+        assert_eq!(flipped(&mut processor, 0.7), Some(1.0));
+        assert_eq!(flipped(&mut processor, 0.2), Some(0.2));
+    }
+
+    #[test]
+    fn hold_times_delay_switching() {
+        let feature_levels = levels(100.0, 200.0);
+        let start = Instant::now();
+        let after = |ms| start + Duration::from_millis(ms);
+        let mut processor = GateProcessingValues {
+            wanted_open: true,
+            ..Default::default()
+        };
+
+        // Nothing to hold on the first switch
+        assert!(switch_gate(&mut processor, &feature_levels, start));
+        assert!(processor.open);
+
+        processor.wanted_open = false;
+        assert!(!switch_gate(&mut processor, &feature_levels, after(50)));
+        assert!(processor.open);
+        assert!(switch_gate(&mut processor, &feature_levels, after(100)));
+        assert!(!processor.open);
+
+        processor.wanted_open = true;
+        assert!(!switch_gate(&mut processor, &feature_levels, after(250)));
+        assert!(switch_gate(&mut processor, &feature_levels, after(300)));
+        assert!(processor.open);
+
+        // Already where the input wants it
+        assert!(!switch_gate(&mut processor, &feature_levels, after(1000)));
+    }
+}
@@ -0,0 +1,59 @@
+use std::{sync::Arc, time::Instant};
+
+use log::debug;
+
+use crate::toy_handling::{
+    mode_processor::core::ModeProcessorInputType, patterns::Pattern,
+    toyops::PatternProcessingValues,
+};
+
+/*
+ * Starts, rescales or stops the pattern
+ * Bools play the pattern at full level, floats scale it and 0.0 stops it
+ */
+#[inline(always)]
+pub fn parse_pattern(
+    processor: &mut PatternProcessingValues,
+    pattern: Option<Arc<Pattern>>,
+    input: ModeProcessorInputType,
+) -> Option<f64> {
+    let scale = match input {
+        ModeProcessorInputType::Float(float_level) => float_level.clamp(0.0, 1.0),
+        ModeProcessorInputType::Boolean(b) => {
+            if b {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        ModeProcessorInputType::Int(_i) => return None, // Ints are mapped before mode processing
+    };
+
+    if scale <= 0.0 {
+        processor.playing = None;
+        return Some(0.0);
+    }
+
+    let Some(pattern) = pattern else {
+        debug!("Pattern mode input without a loaded pattern");
+        return None;
+    };
+
+    match processor.playing.as_mut() {
+        // Keep the same pattern going at the new scale
+        Some((playing, _, playing_scale)) if Arc::ptr_eq(playing, &pattern) => {
+            *playing_scale = scale
+        }
+        _ => processor.playing = Some((pattern, Instant::now(), scale)),
+    }
+    tick_pattern(processor)
+}
+
+// Level of the playing pattern right now
+#[inline(always)]
+pub fn tick_pattern(processor: &PatternProcessingValues) -> Option<f64> {
+    processor
+        .playing
+        .as_ref()
+        .map(|(pattern, started, scale)| pattern.level_at(started.elapsed()) * scale)
+}
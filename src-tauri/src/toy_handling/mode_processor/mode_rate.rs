@@ -1,76 +1,163 @@
+use std::time::Instant;
+
 use log::{debug, trace};
 
 use crate::toy_handling::toyops::RateProcessingValues;
 
 pub enum RateParser {
-    RateCalculated(f64, bool),
+    RateCalculated(f64),
     SkipZero,
 }
 
+/*
+ * Lowers the internal rate level by the time passed since the last decay
+ */
+#[inline(always)]
+fn decay_rate(processor: &mut RateProcessingValues, decay_per_second: f64, now: Instant) {
+    if let Some(last_decay) = processor.rate_timestamp.replace(now) {
+        let decay = decay_per_second * now.duration_since(last_decay).as_secs_f64();
+        processor.rate_saved_level = (processor.rate_saved_level - decay).clamp(0.00, 1.0);
+    }
+}
+
 #[inline(always)]
 pub fn parse_rate(
     processor: &mut RateProcessingValues,
-    decrement_rate: f64,
-    mut float_level: f64,
+    decay_per_second: f64,
+    float_level: f64,
     flip_float: bool,
 ) -> RateParser {
     // Skip because got 0 value to stop toy.
     if !flip_float && float_level <= 0.0 || flip_float && float_level >= 1.0 {
         debug!("Bypassing rate input");
-        processor.rate_saved_level = float_level;
+        processor.rate_saved_level = 0.0;
         processor.rate_saved_osc_input = float_level;
         return RateParser::SkipZero;
-    } else {
-        // Increase toy level
-
-        // Store new input then get the distance of the new input from the last input
-        // Add that distance to the internal float level
-
-        // get distance between newest input and last input
-        // Set the distance between as the new motor speed
-        if processor.rate_saved_osc_input > float_level {
-            processor.rate_saved_level +=
-                (processor.rate_saved_osc_input - float_level).clamp(0.00, 1.0);
-        } else {
-            processor.rate_saved_level +=
-                (float_level - processor.rate_saved_osc_input).clamp(0.00, 1.0);
-        }
+    }
 
-        // Dont let internal level go over 1.0
-        processor.rate_saved_level = processor.rate_saved_level.clamp(0.00, 1.00);
+    decay_rate(processor, decay_per_second, Instant::now());
 
-        // Set the newest input as the recent input
-        processor.rate_saved_osc_input = float_level;
+    // Increase toy level by the distance between the newest input and the last input
+    processor.rate_saved_level = (processor.rate_saved_level
+        + (float_level - processor.rate_saved_osc_input).abs())
+    .clamp(0.00, 1.00);
 
-        // Set the internal rate state to the float level
-        float_level = processor.rate_saved_level;
+    // Set the newest input as the recent input
+    processor.rate_saved_osc_input = float_level;
 
-        // Save the internal motor speed
-        //*rate_internal_level += *float_level;
+    trace!("float level rate increased");
+    RateParser::RateCalculated(processor.rate_saved_level)
+}
 
-        trace!("float level rate increased");
+/*
+ * A true bool sets the internal rate level to full, then it decays on the toy tick
+ * False leaves the level decaying
+ */
+#[inline(always)]
+pub fn parse_rate_trigger(
+    processor: &mut RateProcessingValues,
+    decay_per_second: f64,
+    triggered: bool,
+) -> f64 {
+    decay_rate(processor, decay_per_second, Instant::now());
+    if triggered {
+        processor.rate_saved_level = 1.0;
+    }
+    processor.rate_saved_level
+}
+
+/*
+ * Decays the internal rate level on the toy tick even when no input comes in
+ * Returns None once the level has reached 0.0
+ */
+#[inline(always)]
+pub fn tick_rate(processor: &mut RateProcessingValues, decay_per_second: f64) -> Option<f64> {
+    if processor.rate_saved_level <= 0.0 {
+        processor.rate_timestamp = None;
+        return None;
     }
 
-    // Decrement testing
-    if let Some(instant) = processor.rate_timestamp {
-        // Decrease tick
-        if instant.elapsed().as_secs_f64() >= 0.15 {
-            // Decrease the internal rate level
-            // This decrease rate should be tuneable
-            processor.rate_saved_level =
-                (processor.rate_saved_level - decrement_rate).clamp(0.00, 1.0);
-            debug!(
-                "internal level after decrement: {}",
-                processor.rate_saved_level
-            );
-
-            // Set float level to decremented internal rate
-            float_level = processor.rate_saved_level;
-
-            trace!("decrease timer reset");
-            return RateParser::RateCalculated(float_level, true);
+    decay_rate(processor, decay_per_second, Instant::now());
+    trace!(
+        "internal level after decrement: {}",
+        processor.rate_saved_level
+    );
+    Some(processor.rate_saved_level)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn rate(level: f64, last_decay: Option<Instant>) -> RateProcessingValues {
+        RateProcessingValues {
+            rate_saved_level: level,
+            rate_saved_osc_input: 0.0,
+            rate_timestamp: last_decay,
         }
     }
 
-    RateParser::RateCalculated(float_level, false)
+    #[test]
+    fn decay_is_per_second() {
+        let start = Instant::now();
+        let mut processor = rate(1.0, Some(start));
+
+        decay_rate(&mut processor, 0.5, start + Duration::from_secs(1));
+        assert_eq!(processor.rate_saved_level, 0.5);
+        decay_rate(&mut processor, 0.5, start + Duration::from_millis(1500));
+        assert_eq!(processor.rate_saved_level, 0.25);
+        decay_rate(&mut processor, 0.5, start + Duration::from_secs(3));
+        assert_eq!(processor.rate_saved_level, 0.0);
+    }
+
+    #[test]
+    fn first_decay_only_starts_the_clock() {
+        let start = Instant::now();
+        let mut processor = rate(0.8, None);
+
+        decay_rate(&mut processor, 0.5, start);
+        assert_eq!(processor.rate_saved_level, 0.8);
+        assert_eq!(processor.rate_timestamp, Some(start));
+    }
+
+    #[test]
+    fn input_changes_raise_the_level() {
+        let mut processor = rate(0.0, None);
+
+        assert!(matches!(
+            parse_rate(&mut processor, 0.0, 0.25, false),
+            RateParser::RateCalculated(level) if level == 0.25
+        ));
+        assert!(matches!(
+            parse_rate(&mut processor, 0.0, 0.75, false),
+            RateParser::RateCalculated(level) if level == 0.75
+        ));
+        assert!(matches!(
+            parse_rate(&mut processor, 0.0, 0.0, false),
+            RateParser::SkipZero
+        ));
+        assert_eq!(processor.rate_saved_level, 0.0);
+    }
+
+    #[test]
+    fn bool_trigger_fills_the_level() {
+        let mut processor = rate(0.25, None);
+
+        assert_eq!(parse_rate_trigger(&mut processor, 0.5, true), 1.0);
+        // False keeps decaying from the triggered level
+        let level = parse_rate_trigger(&mut processor, 0.5, false);
+        assert!(level <= 1.0 && level > 0.9);
+    }
+
+    #[test]
+    fn tick_stops_once_decayed() {
+        let mut processor = rate(0.5, None);
+        assert_eq!(tick_rate(&mut processor, 0.5), Some(0.5));
+
+        let mut processor = rate(0.0, Some(Instant::now()));
+        assert_eq!(tick_rate(&mut processor, 0.5), None);
+        assert_eq!(processor.rate_timestamp, None);
+    }
 }
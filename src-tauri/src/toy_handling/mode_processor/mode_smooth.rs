@@ -11,7 +11,7 @@ pub enum SmoothParser {
 #[inline(always)]
 pub fn parse_smoothing(
     smooth_queue: &mut Vec<f64>,
-    feature_levels: LevelTweaks,
+    feature_levels: &LevelTweaks,
     mut float_level: f64,
     flip_float: bool,
 ) -> SmoothParser {
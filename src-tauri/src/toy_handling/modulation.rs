@@ -0,0 +1,200 @@
+/*
+ * Modulation
+ * Moves a feature's level around its processed level so constant output does not feel mechanical.
+ * Applied by the emitter on every tick before the level is clamped to the feature's range.
+ */
+
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Lowest modulated level so modulation never turns a running feature off
+const MIN_MODULATED_LEVEL: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, Default)]
+#[ts(export)]
+pub enum ModulationKind {
+    #[default]
+    Off,
+    // Seeded gradient noise
+    Noise,
+    Sine,
+    Triangle,
+    Square,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct Modulation {
+    pub kind: ModulationKind,
+    // Most level added or taken away (0.0 - 1.0)
+    pub amplitude: f64,
+    // Waves per second, or noise changes per second
+    pub frequency_hz: f64,
+    pub seed: u32,
+}
+
+impl Default for Modulation {
+    fn default() -> Self {
+        Self {
+            kind: ModulationKind::Off,
+            amplitude: 0.2,
+            frequency_hz: 0.5,
+            seed: 0,
+        }
+    }
+}
+
+impl Modulation {
+    pub fn is_active(&self) -> bool {
+        self.kind != ModulationKind::Off && self.amplitude > 0.0 && self.frequency_hz > 0.0
+    }
+
+    /*
+     * Modulates a level at a time in seconds
+     * Stopped features (0.0, or 1.0 when flipped) stay stopped
+     */
+    pub fn apply(&self, level: f64, flip_float: bool, time_secs: f64) -> f64 {
+        if !self.is_active() || !flip_float && level <= 0.0 || flip_float && level >= 1.0 {
+            return level;
+        }
+
+        let phase = time_secs * self.frequency_hz;
+        let wave = match self.kind {
+            ModulationKind::Off => 0.0,
+            ModulationKind::Noise => gradient_noise(self.seed, phase),
+            ModulationKind::Sine => (phase * TAU).sin(),
+            ModulationKind::Triangle => 1.0 - 4.0 * (phase.fract() - 0.5).abs(),
+            ModulationKind::Square => {
+                if phase.fract() < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+
+        let modulated = level + wave * self.amplitude.clamp(0.0, 1.0);
+        if flip_float {
+            modulated.clamp(0.0, 1.0 - MIN_MODULATED_LEVEL)
+        } else {
+            modulated.clamp(MIN_MODULATED_LEVEL, 1.0)
+        }
+    }
+}
+
+/*
+ * 1D Perlin noise (-1.0 - 1.0) with a gradient per whole number of x
+ */
+fn gradient_noise(seed: u32, x: f64) -> f64 {
+    let cell = x.floor();
+    let t = x - cell;
+    let cell = cell as i64;
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let from = lattice_gradient(seed, cell) * t;
+    let to = lattice_gradient(seed, cell.wrapping_add(1)) * (t - 1.0);
+    // Gradient noise peaks at half the gradient
+    ((from + (to - from) * fade) * 2.0).clamp(-1.0, 1.0)
+}
+
+// Seeded hash of a lattice point to a gradient (-1.0 - 1.0)
+fn lattice_gradient(seed: u32, cell: i64) -> f64 {
+    let mut x = (cell as u64) ^ (u64::from(seed) << 32);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modulation(kind: ModulationKind) -> Modulation {
+        Modulation {
+            kind,
+            amplitude: 0.2,
+            frequency_hz: 1.0,
+            seed: 7,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "Expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn inactive_modulation_keeps_level() {
+        assert_eq!(modulation(ModulationKind::Off).apply(0.5, false, 0.25), 0.5);
+        let silent = Modulation {
+            amplitude: 0.0,
+            ..modulation(ModulationKind::Sine)
+        };
+        assert_eq!(silent.apply(0.5, false, 0.25), 0.5);
+    }
+
+    #[test]
+    fn waves() {
+        assert_close(
+            modulation(ModulationKind::Sine).apply(0.5, false, 0.25),
+            0.7,
+        );
+        assert_close(
+            modulation(ModulationKind::Sine).apply(0.5, false, 0.75),
+            0.3,
+        );
+        assert_close(
+            modulation(ModulationKind::Triangle).apply(0.5, false, 0.0),
+            0.3,
+        );
+        assert_close(
+            modulation(ModulationKind::Triangle).apply(0.5, false, 0.5),
+            0.7,
+        );
+        assert_close(
+            modulation(ModulationKind::Square).apply(0.5, false, 0.25),
+            0.7,
+        );
+        assert_close(
+            modulation(ModulationKind::Square).apply(0.5, false, 0.75),
+            0.3,
+        );
+    }
+
+    #[test]
+    fn stopped_levels_stay_stopped() {
+        let sine = modulation(ModulationKind::Sine);
+        assert_eq!(sine.apply(0.0, false, 0.25), 0.0);
+        assert_eq!(sine.apply(1.0, true, 0.25), 1.0);
+    }
+
+    #[test]
+    fn running_levels_are_not_turned_off() {
+        let sine = modulation(ModulationKind::Sine);
+        assert_close(sine.apply(0.1, false, 0.75), MIN_MODULATED_LEVEL);
+        assert_close(sine.apply(0.9, true, 0.25), 1.0 - MIN_MODULATED_LEVEL);
+        assert_close(sine.apply(0.9, false, 0.25), 1.0);
+    }
+
+    #[test]
+    fn noise_is_seeded() {
+        for x in [0.1, 0.5, 1.3, 2.75, 10.9] {
+            assert_eq!(gradient_noise(7, x), gradient_noise(7, x));
+            assert!((-1.0..=1.0).contains(&gradient_noise(7, x)));
+        }
+        assert_ne!(gradient_noise(7, 0.5), gradient_noise(8, 0.5));
+    }
+
+    #[test]
+    fn noise_is_zero_on_whole_numbers() {
+        for x in [0.0, 1.0, 5.0, -3.0] {
+            assert_eq!(gradient_noise(7, x), 0.0);
+        }
+    }
+}
@@ -1,13 +1,10 @@
-use std::sync::Arc;
-
-use buttplug::client::ButtplugClientDevice;
 use log::debug;
 use rosc::{OscMessage, OscType};
-use tokio::sync::watch;
+use tokio::sync::mpsc::UnboundedSender;
 
+use crate::toy_handling::patterns::PatternLibrary;
 use crate::toy_handling::runtime::toy_emitter_thread::OscParserData;
-use crate::toy_handling::toyops::ToyParameter;
-use crate::toy_handling::toyops::VCToyFeatures;
+use crate::toy_handling::toyops::{LevelTweaks, VCFeatureType, VCToyFeature, VCToyFeatures};
 
 use log::info;
 
@@ -16,13 +13,71 @@ use super::mode_processor::core::ModeProcessorInput;
 use super::mode_processor::core::ModeProcessorInputType;
 use super::toyops::ProcessingMode;
 use mode_processor::core::mode_processor;
+use mode_processor::core::mode_processor_tick;
+
+/*
+ * Converts an OSC argument to mode processor input
+ * Returns None for unsupported OSC types
+ */
+#[inline(always)]
+fn osc_type_to_input(osc_value: OscType) -> Option<ModeProcessorInputType> {
+    match osc_value {
+        // Clamp float accuracy to hundredths and cast as 64 bit float
+        OscType::Float(lvl) => Some(ModeProcessorInputType::Float(
+            ((lvl * 100.0).round() / 100.0) as f64,
+        )),
+        OscType::Double(lvl) => Some(ModeProcessorInputType::Float((lvl * 100.0).round() / 100.0)),
+        OscType::Bool(b) => Some(ModeProcessorInputType::Boolean(b)),
+        OscType::Int(i) => Some(ModeProcessorInputType::Int(i as i64)),
+        OscType::Long(l) => Some(ModeProcessorInputType::Int(l)),
+        _ => None,
+    }
+}
+
+/*
+ * Emitter data for an input processor level
+ * Linear features turn the depth into a predicted stroke timed by the input rate
+ * Only depth readings update the prediction, levels from the toy tick are followed as they are
+ */
+fn input_processor_emit(
+    feature: &mut VCToyFeature,
+    level: f64,
+    depth_reading: bool,
+) -> OscParserData {
+    if let VCFeatureType::Linear = feature.feature_type {
+        let stroke_generator = &mut feature.penetration_system.stroke_generator;
+        let (position, duration_ms) = if depth_reading {
+            stroke_generator.update(level, &feature.feature_levels)
+        } else {
+            stroke_generator.follow(level, &feature.feature_levels)
+        };
+        OscParserData::new(
+            feature.feature_type,
+            position,
+            feature.feature_index,
+            feature.flip_input_float,
+            LevelTweaks {
+                linear_position_speed: duration_ms,
+                ..feature.feature_levels.clone()
+            },
+        )
+    } else {
+        OscParserData::new(
+            feature.feature_type,
+            level,
+            feature.feature_index,
+            feature.flip_input_float,
+            feature.feature_levels.clone(),
+        )
+    }
+}
 
 #[inline(always)]
 pub async fn parse_osc_message(
-    emitter_thread_osc_tx: &watch::Sender<Option<OscParserData>>,
+    emitter_thread_osc_tx: &UnboundedSender<OscParserData>,
     msg: &mut OscMessage,
-    dev: Arc<ButtplugClientDevice>,
     vc_toy_features: &mut VCToyFeatures,
+    patterns: &PatternLibrary,
 ) {
     // Parse OSC msgs to toys commands
     //debug!("msg.addr = {} | msg.args = {:?}", msg.addr, msg.args);
@@ -34,8 +89,24 @@ pub async fn parse_osc_message(
 
     // msg args pop should go here
     //let newest_msg_addr = msg.addr.clone();
-    let newest_msg_val = msg.args.pop().unwrap();
+    let Some(newest_msg_val) = msg.args.pop() else {
+        return;
+    };
     //info!("Newest value: {:?}", newest_msg_val);
+
+    // Skip message because unsupported OSC type
+    let Some(input) = osc_type_to_input(newest_msg_val) else {
+        return;
+    };
+
+    match input {
+        ModeProcessorInputType::Float(float_level) => {
+            debug!("Received and cast float lvl: {:.5}", float_level)
+        }
+        ModeProcessorInputType::Boolean(b) => info!("Got a Bool! {} = {}", msg.addr, b),
+        ModeProcessorInputType::Int(i) => info!("Got an Int! {} = {}", msg.addr, i),
+    }
+
     /*
      * Input mode processing
      * Get all features with an enabled Input mode
@@ -48,260 +119,126 @@ pub async fn parse_osc_message(
     if let Some(input_processor_system_features) =
         vc_toy_features.get_features_with_input_processors(&msg.addr)
     {
-        match newest_msg_val {
-            OscType::Float(lvl) => {
-                for feature in input_processor_system_features {
-                    let float_level = ((lvl * 100.0).round() / 100.0) as f64;
-                    // pen_system is checked for None in get_features_with_penetration_systems method.
-                    // Give access to internal mode values here (input, internal_values)
-                    if let Some(input_processor_processed_value) = feature
-                        .penetration_system
-                        .pen_system
-                        .as_mut()
-                        .unwrap()
-                        .process(
-                            msg.addr.as_str(),
-                            ModeProcessorInputType::Float(float_level),
-                        )
-                    {
-                        // Send to mode processor if specified (Raw = no mode processing)
-                        if let ProcessingMode::Raw =
-                            feature.penetration_system.pen_system_processing_mode
-                        {
-                            /*command_toy(
-                                dev.clone(),
-                                feature.feature_type,
-                                i_mode_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            )
-                            .await;*/
-                            let osc_emit = OscParserData::new(
-                                dev.clone(),
-                                feature.feature_type,
-                                input_processor_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            );
-                            emitter_thread_osc_tx.send(Some(osc_emit));
-                        } else {
-                            // If mode processor returns a value send to toy
-                            if let Some(i) = mode_processor(
-                                ModeProcessorInput::InputProcessor((
-                                    ModeProcessorInputType::Float(input_processor_processed_value),
-                                    &mut feature
-                                        .penetration_system
-                                        .pen_system_processing_mode_values,
-                                )),
-                                feature.feature_levels,
-                                feature.flip_input_float,
-                            )
-                            .await
-                            {
-                                /*
-                                command_toy(
-                                    dev.clone(),
-                                    feature.feature_type,
-                                    i,
-                                    feature.feature_index,
-                                    feature.flip_input_float,
-                                    feature.feature_levels,
-                                )
-                                .await;*/
-                                let osc_emit = OscParserData::new(
-                                    dev.clone(),
-                                    feature.feature_type,
-                                    i,
-                                    feature.feature_index,
-                                    feature.flip_input_float,
-                                    feature.feature_levels,
-                                );
-                                emitter_thread_osc_tx.send(Some(osc_emit));
-                            }
-                        }
-                    }
-                }
-            }
-            // Boolean can be supported in the process trait method
-            OscType::Bool(b) => {
-                for feature in input_processor_system_features {
-                    // Boolean to float transformation here
-                    if let Some(input_processor_processed_value) = feature
-                        .penetration_system
-                        .pen_system
-                        .as_mut()
-                        .unwrap()
-                        .process(msg.addr.as_str(), ModeProcessorInputType::Boolean(b))
-                    {
-                        // Send to mode processor if specified (Raw = no mode processing)
-                        if let ProcessingMode::Raw =
-                            feature.penetration_system.pen_system_processing_mode
-                        {
-                            /*
-                            command_toy(
-                                dev.clone(),
-                                feature.feature_type,
-                                i_mode_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            )
-                            .await;*/
-                            let osc_emit = OscParserData::new(
-                                dev.clone(),
-                                feature.feature_type,
-                                input_processor_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            );
-                            emitter_thread_osc_tx.send(Some(osc_emit));
-                        } else if let Some(i) = mode_processor(
-                            ModeProcessorInput::InputProcessor((
-                                ModeProcessorInputType::Float(input_processor_processed_value),
-                                &mut feature.penetration_system.pen_system_processing_mode_values,
-                            )),
-                            feature.feature_levels,
-                            feature.flip_input_float,
-                        )
-                        .await
-                        {
-                            /*
-                            command_toy(
-                                dev.clone(),
-                                feature.feature_type,
-                                i,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            )
-                            .await;*/
-                            let osc_emit = OscParserData::new(
-                                dev.clone(),
-                                feature.feature_type,
-                                i,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            );
-                            emitter_thread_osc_tx.send(Some(osc_emit));
-                        }
-                    }
-                }
+        for feature in input_processor_system_features {
+            // pen_system is checked for None in get_features_with_penetration_systems method.
+            // Give access to internal mode values here (input, internal_values)
+            // Boolean and Int support is up to the process trait method
+            let Some(input_processor_processed_value) = feature
+                .penetration_system
+                .pen_system
+                .as_mut()
+                .unwrap()
+                .process(msg.addr.as_str(), input)
+            else {
+                continue;
+            };
+
+            // Send to mode processor if specified (Raw = no mode processing)
+            let processed_value = if let ProcessingMode::Raw =
+                feature.penetration_system.pen_system_processing_mode
+            {
+                Some(input_processor_processed_value)
+            } else {
+                mode_processor(
+                    ModeProcessorInput::InputProcessor((
+                        ModeProcessorInputType::Float(input_processor_processed_value),
+                        &mut feature.penetration_system.pen_system_processing_mode_values,
+                    )),
+                    &feature.feature_levels,
+                    feature.flip_input_float,
+                    patterns,
+                )
+                .await
+            };
+
+            // If mode processor returns a value send to toy
+            if let Some(i) = processed_value {
+                let _ = emitter_thread_osc_tx.send(input_processor_emit(feature, i, true));
             }
-            _ => (),
-        } // End match OscType for Input processors
+        }
     } // End Input processing
 
     if let Some(features) = vc_toy_features.get_features_from_param(&msg.addr) {
-        match newest_msg_val {
-            OscType::Float(lvl) => {
-                // Clamp float accuracy to hundredths and cast as 64 bit float
-                let float_level = ((lvl * 100.0).round() / 100.0) as f64;
-                debug!("Received and cast float lvl: {:.5}", float_level);
+        for feature in features {
+            // Get ToyParameter here
+            // If no matching toy parameter skip feature
+            let Some(param_index) = feature
+                .osc_parameters
+                .iter()
+                .position(|param| param.parameter == msg.addr)
+            else {
+                continue;
+            };
+
+            if let Some(mode_processed_value) = mode_processor(
+                ModeProcessorInput::RawInput(input, &mut feature.osc_parameters[param_index]),
+                &feature.feature_levels,
+                feature.flip_input_float,
+                patterns,
+            )
+            .await
+            {
+                // Save this parameter's level then combine it with the feature's other parameters
+                feature.osc_parameters[param_index].latest_level = Some(mode_processed_value);
+                let blended_value = feature.blend_parameter_levels();
 
-                for feature in features {
-                    // Get ToyParameter here
-                    // We unwrap here because the call to get_features_from_param guarantees the parameter exists.
-                    let mut toy_parameter = feature
-                        .osc_parameters
-                        .iter_mut()
-                        .filter_map(|param| {
-                            if param.parameter == msg.addr {
-                                Some(param)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<&mut ToyParameter>>();
+                let osc_emit = OscParserData::new(
+                    feature.feature_type,
+                    blended_value,
+                    feature.feature_index,
+                    feature.flip_input_float,
+                    feature.feature_levels.clone(),
+                );
+                let _ = emitter_thread_osc_tx.send(osc_emit);
+            }
+        }
+    }
+}
 
-                    if let Some(first_toy_param) = toy_parameter.first_mut() {
-                        if let Some(mode_processed_value) = mode_processor(
-                            ModeProcessorInput::RawInput(
-                                ModeProcessorInputType::Float(float_level),
-                                first_toy_param,
-                            ),
-                            feature.feature_levels,
-                            feature.flip_input_float,
-                        )
-                        .await
-                        {
-                            /*
-                            command_toy(
-                                dev.clone(),
-                                feature.feature_type,
-                                mode_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            )
-                            .await;*/
-                            let osc_emit = OscParserData::new(
-                                dev.clone(),
-                                feature.feature_type,
-                                mode_processed_value,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            );
-                            emitter_thread_osc_tx.send(Some(osc_emit));
-                        }
-                    } // If no matching toy parameter skip feature
-                }
+/*
+ * Advances the time based processing modes of every enabled feature
+ * Runs on the toy thread's tick. Changed feature levels go to the emitter like OSC input
+ */
+pub fn tick_toy_features(
+    emitter_thread_osc_tx: &UnboundedSender<OscParserData>,
+    vc_toy_features: &mut VCToyFeatures,
+) {
+    for feature in vc_toy_features
+        .features
+        .iter_mut()
+        .filter(|feature| feature.feature_enabled)
+    {
+        if feature.penetration_system.pen_system.is_some() {
+            if let Some(level) = mode_processor_tick(
+                &mut feature.penetration_system.pen_system_processing_mode_values,
+                &feature.feature_levels,
+            ) {
+                let _ = emitter_thread_osc_tx.send(input_processor_emit(feature, level, false));
             }
-            OscType::Bool(b) => {
-                info!("Got a Bool! {} = {}", msg.addr, b);
-                for feature in features {
-                    // Get ToyParameter here
-                    let mut toy_parameter = feature
-                        .osc_parameters
-                        .iter_mut()
-                        .filter_map(|param| {
-                            if param.parameter == msg.addr {
-                                Some(param)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<&mut ToyParameter>>();
+        }
 
-                    if let Some(first_toy_param) = toy_parameter.first_mut() {
-                        if let Some(i) = mode_processor(
-                            ModeProcessorInput::RawInput(
-                                ModeProcessorInputType::Boolean(b),
-                                first_toy_param,
-                            ),
-                            feature.feature_levels,
-                            feature.flip_input_float,
-                        )
-                        .await
-                        {
-                            /*
-                            command_toy(
-                                dev.clone(),
-                                feature.feature_type,
-                                i,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            )
-                            .await;*/
-                            let osc_emit = OscParserData::new(
-                                dev.clone(),
-                                feature.feature_type,
-                                i,
-                                feature.feature_index,
-                                feature.flip_input_float,
-                                feature.feature_levels,
-                            );
-                            emitter_thread_osc_tx.send(Some(osc_emit));
-                        }
-                    }
-                }
+        let mut changed = false;
+        for param in &mut feature.osc_parameters {
+            let Some(level) =
+                mode_processor_tick(&mut param.processing_mode_values, &feature.feature_levels)
+            else {
+                continue;
+            };
+            if param.latest_level != Some(level) {
+                param.latest_level = Some(level);
+                changed = true;
             }
-            _ => {} // Skip parameter because unsuppported OSC type
+        }
+
+        if changed {
+            let osc_emit = OscParserData::new(
+                feature.feature_type,
+                feature.blend_parameter_levels(),
+                feature.feature_index,
+                feature.flip_input_float,
+                feature.feature_levels.clone(),
+            );
+            let _ = emitter_thread_osc_tx.send(osc_emit);
         }
     }
 }
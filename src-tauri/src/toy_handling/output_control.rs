@@ -0,0 +1,147 @@
+/*
+ * Output controls shared by every toy
+ * Set through the OSC API, the emergency stop and the safety config, checked before every toy command.
+ */
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use super::safety::{RunCap, SafetyConfig};
+
+// Longest pause, longer pauses are cut to this
+pub const MAX_PAUSE: Duration = Duration::from_secs(60 * 60);
+
+struct OutputControlState {
+    // f64 bits of the global intensity multiplier
+    intensity: AtomicU64,
+    paused_until: Mutex<Option<Instant>>,
+    // Latched until re-armed
+    emergency_stopped: AtomicBool,
+    safety: Mutex<SafetyConfig>,
+    // Run time per toy (toy id, sub id), kept here so restarting a toy's emitter keeps its cooldown
+    run_caps: Mutex<HashMap<(u32, u8), RunCap>>,
+}
+
+#[derive(Clone)]
+pub struct OutputControl(Arc<OutputControlState>);
+
+impl Default for OutputControl {
+    fn default() -> Self {
+        Self(Arc::new(OutputControlState {
+            intensity: AtomicU64::new(1.0f64.to_bits()),
+            paused_until: Mutex::new(None),
+            emergency_stopped: AtomicBool::new(false),
+            safety: Mutex::new(SafetyConfig::default()),
+            run_caps: Mutex::new(HashMap::new()),
+        }))
+    }
+}
+
+impl OutputControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intensity(&self) -> f64 {
+        f64::from_bits(self.0.intensity.load(Ordering::Relaxed))
+    }
+
+    // Multiplier applied to every feature level (0.0 - 1.0). NaN and infinite intensities are ignored
+    pub fn set_intensity(&self, intensity: f64) {
+        if !intensity.is_finite() {
+            return;
+        }
+        self.0
+            .intensity
+            .store(intensity.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    // Pauses output for the duration (at most MAX_PAUSE). A zero duration resumes
+    pub fn pause_for(&self, duration: Duration) {
+        *self.0.paused_until.lock() = if duration.is_zero() {
+            None
+        } else {
+            Instant::now().checked_add(duration.min(MAX_PAUSE))
+        };
+    }
+
+    pub fn is_paused(&self) -> bool {
+        let mut paused_until = self.0.paused_until.lock();
+        match *paused_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                *paused_until = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    // Returns false if the emergency stop was already latched
+    // Latching is the only way run times and cooldowns are reset
+    pub fn latch_emergency_stop(&self) -> bool {
+        self.0.run_caps.lock().clear();
+        !self.0.emergency_stopped.swap(true, Ordering::SeqCst)
+    }
+
+    // Returns false if the emergency stop was not latched
+    pub fn rearm(&self) -> bool {
+        self.0.emergency_stopped.swap(false, Ordering::SeqCst)
+    }
+
+    pub fn is_emergency_stopped(&self) -> bool {
+        self.0.emergency_stopped.load(Ordering::SeqCst)
+    }
+
+    pub fn safety(&self) -> SafetyConfig {
+        *self.0.safety.lock()
+    }
+
+    pub fn set_safety(&self, safety: SafetyConfig) {
+        *self.0.safety.lock() = safety;
+    }
+
+    // Runs f with the run cap of a toy (toy id, sub id)
+    pub fn run_cap<T>(&self, toy: (u32, u8), f: impl FnOnce(&mut RunCap) -> T) -> T {
+        f(self.0.run_caps.lock().entry(toy).or_default())
+    }
+
+    // A toy's safety override or the global safety config
+    pub fn toy_safety(&self, toy_safety: Option<SafetyConfig>) -> SafetyConfig {
+        toy_safety.unwrap_or_else(|| self.safety())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intensity_is_clamped() {
+        let output_control = OutputControl::new();
+        output_control.set_intensity(0.5);
+        assert_eq!(output_control.intensity(), 0.5);
+        output_control.set_intensity(2.0);
+        assert_eq!(output_control.intensity(), 1.0);
+        output_control.set_intensity(-1.0);
+        assert_eq!(output_control.intensity(), 0.0);
+    }
+
+    #[test]
+    fn non_finite_intensity_is_ignored() {
+        let output_control = OutputControl::new();
+        output_control.set_intensity(0.5);
+        for intensity in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            output_control.set_intensity(intensity);
+            assert_eq!(output_control.intensity(), 0.5);
+        }
+    }
+}
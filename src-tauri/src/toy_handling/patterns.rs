@@ -0,0 +1,327 @@
+/*
+ * Patterns
+ * Named waveforms loaded from the Patterns directory next to ToyConfigs.
+ * A pattern is played by a feature parameter in the Pattern processing mode.
+ */
+
+use std::{collections::HashMap, fs, sync::Arc, time::Duration};
+
+use log::{debug, error as logerr, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    util::fs::{build_path_dir, build_path_file, file_exists, get_config_dir, path_exists},
+    vcore::host::CoreHandle,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum PatternPlayback {
+    // Starts over after the last level
+    Loop,
+    // Plays once then holds the last level
+    OneShot,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum PatternInterpolation {
+    // Holds each keyframe level until the next keyframe
+    Step,
+    Linear,
+    // Eases in and out of every keyframe
+    Smooth,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct PatternKeyframe {
+    pub time_ms: u64,
+    pub level: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub enum PatternShape {
+    // Keyframes sorted by time. The pattern ends at the last keyframe
+    Keyframes {
+        keyframes: Vec<PatternKeyframe>,
+        interpolation: PatternInterpolation,
+    },
+    // Every level is held for step_ms
+    Steps {
+        step_ms: u64,
+        levels: Vec<f64>,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Pattern {
+    pub name: String,
+    pub playback: PatternPlayback,
+    pub shape: PatternShape,
+}
+
+impl Pattern {
+    pub fn duration(&self) -> Duration {
+        match &self.shape {
+            PatternShape::Keyframes { keyframes, .. } => {
+                Duration::from_millis(keyframes.last().map_or(0, |keyframe| keyframe.time_ms))
+            }
+            PatternShape::Steps { step_ms, levels } => {
+                Duration::from_millis(step_ms * levels.len() as u64)
+            }
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let level_valid = |level: &f64| (0.0..=1.0).contains(level);
+        match &self.shape {
+            PatternShape::Keyframes { keyframes, .. } => {
+                !keyframes.is_empty()
+                    && keyframes
+                        .iter()
+                        .all(|keyframe| level_valid(&keyframe.level))
+                    && keyframes
+                        .windows(2)
+                        .all(|pair| pair[0].time_ms <= pair[1].time_ms)
+            }
+            PatternShape::Steps { step_ms, levels } => {
+                *step_ms > 0 && !levels.is_empty() && levels.iter().all(level_valid)
+            }
+        }
+    }
+
+    // Level at a time since the pattern started
+    pub fn level_at(&self, elapsed: Duration) -> f64 {
+        let duration = self.duration();
+        let elapsed = match self.playback {
+            PatternPlayback::Loop if !duration.is_zero() => {
+                Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64)
+            }
+            _ => elapsed.min(duration),
+        };
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+
+        match &self.shape {
+            PatternShape::Keyframes {
+                keyframes,
+                interpolation,
+            } => {
+                let Some(next) = keyframes
+                    .iter()
+                    .position(|keyframe| keyframe.time_ms as f64 > elapsed_ms)
+                else {
+                    return keyframes.last().map_or(0.0, |keyframe| keyframe.level);
+                };
+                if next == 0 {
+                    return keyframes[0].level;
+                }
+
+                let (from, to) = (keyframes[next - 1], keyframes[next]);
+                let t = (elapsed_ms - from.time_ms as f64) / (to.time_ms - from.time_ms) as f64;
+                let t = match interpolation {
+                    PatternInterpolation::Step => 0.0,
+                    PatternInterpolation::Linear => t,
+                    PatternInterpolation::Smooth => t * t * (3.0 - 2.0 * t),
+                };
+                from.level + (to.level - from.level) * t
+            }
+            PatternShape::Steps { step_ms, levels } => {
+                let step = (elapsed_ms / *step_ms as f64) as usize;
+                levels
+                    .get(step.min(levels.len().saturating_sub(1)))
+                    .copied()
+                    .unwrap_or(0.0)
+            }
+        }
+    }
+}
+
+fn builtin_patterns() -> Vec<Pattern> {
+    let keyframe = |time_ms, level| PatternKeyframe { time_ms, level };
+    vec![
+        Pattern {
+            name: "pulse".to_string(),
+            playback: PatternPlayback::Loop,
+            shape: PatternShape::Steps {
+                step_ms: 250,
+                levels: vec![1.0, 0.0],
+            },
+        },
+        Pattern {
+            name: "wave".to_string(),
+            playback: PatternPlayback::Loop,
+            shape: PatternShape::Keyframes {
+                keyframes: vec![keyframe(0, 0.1), keyframe(1500, 1.0), keyframe(3000, 0.1)],
+                interpolation: PatternInterpolation::Smooth,
+            },
+        },
+        Pattern {
+            name: "escalate".to_string(),
+            playback: PatternPlayback::OneShot,
+            shape: PatternShape::Keyframes {
+                keyframes: vec![keyframe(0, 0.2), keyframe(30000, 1.0)],
+                interpolation: PatternInterpolation::Linear,
+            },
+        },
+    ]
+}
+
+/*
+ * Every loaded pattern by name
+ * Cheap to clone so every toy thread gets its own handle
+ */
+#[derive(Clone, Default)]
+pub struct PatternLibrary(Arc<HashMap<String, Arc<Pattern>>>);
+
+impl PatternLibrary {
+    /*
+     * Loads every pattern in the Patterns directory
+     * Built-in patterns are written to the directory if they are missing
+     */
+    pub fn load(core_handle: &CoreHandle) -> Self {
+        let config_dir = match get_config_dir(core_handle) {
+            Ok(d) => d,
+            Err(_) => {
+                logerr!("Failed to get config directory for patterns");
+                return Self::default();
+            }
+        };
+
+        let pattern_dir = build_path_dir(&[&config_dir, "Patterns"]);
+        if !path_exists(&pattern_dir) {
+            if let Err(e) = fs::create_dir_all(&pattern_dir) {
+                logerr!("Failed to create pattern directory: {}", e);
+                return Self::default();
+            }
+            info!("Created pattern directory.");
+        }
+
+        for pattern in builtin_patterns() {
+            let pattern_path = build_path_file(&[&pattern_dir, &format!("{}.json", pattern.name)]);
+            if file_exists(&pattern_path) {
+                continue;
+            }
+            if let Ok(json_string) = serde_json::to_string_pretty(&pattern) {
+                if let Err(e) = fs::write(&pattern_path, json_string) {
+                    warn!("Failed to write built-in pattern {}: {}", pattern.name, e);
+                }
+            }
+        }
+
+        let pattern_files = match fs::read_dir(&pattern_dir) {
+            Ok(pattern_files) => pattern_files,
+            Err(e) => {
+                logerr!("Failed to read pattern directory: {}", e);
+                return Self::default();
+            }
+        };
+
+        let mut patterns = HashMap::new();
+        for f in pattern_files.filter_map(|dir| dir.ok()) {
+            if !file_exists(&f.path()) {
+                continue;
+            }
+
+            let Ok(con) = fs::read_to_string(f.path()) else {
+                continue;
+            };
+
+            match serde_json::from_str::<Pattern>(&con) {
+                Ok(pattern) if pattern.is_valid() => {
+                    debug!("Loaded pattern: {}", pattern.name);
+                    patterns.insert(pattern.name.clone(), Arc::new(pattern));
+                }
+                Ok(pattern) => warn!("Pattern {} has invalid levels or timing", pattern.name),
+                Err(e) => warn!("Failed to parse pattern {:?}: {}", f.path(), e),
+            }
+        }
+
+        info!("Loaded {} patterns", patterns.len());
+        Self(Arc::new(patterns))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Pattern>> {
+        self.0.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.0.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(playback: PatternPlayback, interpolation: PatternInterpolation) -> Pattern {
+        Pattern {
+            name: "Ramp".to_string(),
+            playback,
+            shape: PatternShape::Keyframes {
+                keyframes: vec![
+                    PatternKeyframe {
+                        time_ms: 0,
+                        level: 0.0,
+                    },
+                    PatternKeyframe {
+                        time_ms: 1000,
+                        level: 1.0,
+                    },
+                ],
+                interpolation,
+            },
+        }
+    }
+
+    fn steps(playback: PatternPlayback) -> Pattern {
+        Pattern {
+            name: "Steps".to_string(),
+            playback,
+            shape: PatternShape::Steps {
+                step_ms: 100,
+                levels: vec![0.25, 0.5, 1.0],
+            },
+        }
+    }
+
+    #[test]
+    fn keyframes_interpolate() {
+        let linear = ramp(PatternPlayback::OneShot, PatternInterpolation::Linear);
+        assert_eq!(linear.level_at(Duration::from_millis(500)), 0.5);
+
+        let step = ramp(PatternPlayback::OneShot, PatternInterpolation::Step);
+        assert_eq!(step.level_at(Duration::from_millis(500)), 0.0);
+
+        let smooth = ramp(PatternPlayback::OneShot, PatternInterpolation::Smooth);
+        assert_eq!(smooth.level_at(Duration::from_millis(500)), 0.5);
+        assert!(smooth.level_at(Duration::from_millis(250)) < 0.25);
+    }
+
+    #[test]
+    fn one_shot_holds_last_level() {
+        let pattern = ramp(PatternPlayback::OneShot, PatternInterpolation::Linear);
+        assert_eq!(pattern.level_at(Duration::from_millis(2000)), 1.0);
+        assert_eq!(
+            steps(PatternPlayback::OneShot).level_at(Duration::from_millis(1000)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let pattern = ramp(PatternPlayback::Loop, PatternInterpolation::Linear);
+        assert_eq!(pattern.level_at(Duration::from_millis(1250)), 0.25);
+        assert_eq!(
+            steps(PatternPlayback::Loop).level_at(Duration::from_millis(350)),
+            0.25
+        );
+    }
+
+    #[test]
+    fn steps_hold_each_level() {
+        let pattern = steps(PatternPlayback::OneShot);
+        assert_eq!(pattern.level_at(Duration::ZERO), 0.25);
+        assert_eq!(pattern.level_at(Duration::from_millis(150)), 0.5);
+        assert_eq!(pattern.level_at(Duration::from_millis(250)), 1.0);
+    }
+}
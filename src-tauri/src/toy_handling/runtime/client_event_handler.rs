@@ -9,8 +9,11 @@ use crate::{
         toyops::{VCToy, VCToyFeatures},
         ToyPower,
     },
+    util::bluetooth,
     vcore::{
+        config::app::ButtplugConnectorConfig,
         errors::VCError,
+        host::CoreHandle,
         ipc::{
             call_plane::{ToyManagementEvent, ToyUpdate},
             emit_plane::{emit_core_event, emit_toy_event},
@@ -19,13 +22,12 @@ use crate::{
     },
 };
 use buttplug::client::ButtplugClientEvent;
+use futures::stream::BoxStream;
 use futures::StreamExt;
 use futures_timer::Delay;
 use log::{error as logerr, info, trace, warn};
 use parking_lot::Mutex;
 use std::sync::mpsc::Sender;
-use tauri::AppHandle;
-use tauri_plugin_notification::{Notification, NotificationExt};
 use tokio::sync::mpsc::UnboundedSender;
 
 /*
@@ -36,10 +38,10 @@ use tokio::sync::mpsc::UnboundedSender;
 */
 // Uses CEH send channel
 pub async fn client_event_handler(
-    mut event_stream: impl futures::Stream<Item = ButtplugClientEvent> + std::marker::Unpin,
+    mut event_stream: BoxStream<'static, ButtplugClientEvent>,
     vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>,
     identifier: String,
-    app_handle: AppHandle,
+    core_handle: CoreHandle,
     tme_send: UnboundedSender<ToyManagementEvent>,
     _error_tx: UnboundedSender<VCError>,
 ) {
@@ -98,7 +100,7 @@ pub async fn client_event_handler(
                         device_handle: dev.clone(),
                         config: None,
                         sub_id,
-                        app_handle: app_handle.clone(),
+                        core_handle: core_handle.clone(),
                     };
 
                     // Load config with existing toy name
@@ -122,12 +124,15 @@ pub async fn client_event_handler(
 
                     {
                         let mut vc_lock = vibecheck_state_pointer.lock();
+                        // Use the profile of the avatar VRChat is already on
+                        toy.apply_avatar_profile(vc_lock.avatar_id.as_deref());
                         vc_lock
                             .core_toy_manager
                             .as_mut()
                             .unwrap()
                             .online_toys
                             .insert(toy.toy_id, toy.clone());
+                        vc_lock.osc_query_populate();
                     }
                     trace!("Toy inserted into VibeCheckState toys");
 
@@ -136,7 +141,7 @@ pub async fn client_event_handler(
                         .unwrap();
 
                     emit_toy_event(
-                        &app_handle,
+                        &core_handle,
                         FeToyEvent::Add({
                             FeVCToy {
                                 toy_id: Some(toy.toy_id),
@@ -149,6 +154,7 @@ pub async fn client_event_handler(
                                 osc_data: toy.osc_data,
                                 bt_update_rate: toy.bt_update_rate,
                                 sub_id: toy.sub_id,
+                                safety: toy.config.as_ref().unwrap().safety,
                             }
                         }),
                     );
@@ -156,15 +162,10 @@ pub async fn client_event_handler(
                     {
                         let vc_lock = vibecheck_state_pointer.lock();
                         if vc_lock.config.desktop_notifications {
-                            app_handle
-                                .notification()
-                                .builder()
-                                .title("Toy Connected")
-                                .body(
-                                    format!("{} ({})", toy.toy_name, toy.toy_power.to_string())
-                                        .as_str(),
-                                )
-                                .show();
+                            core_handle.notify(
+                                "Toy Connected",
+                                &format!("{} ({})", toy.toy_name, toy.toy_power.to_string()),
+                            );
                         }
                     }
 
@@ -184,6 +185,7 @@ pub async fn client_event_handler(
                                 .remove(&dev.index()),
                         )
                     };
+                    vibecheck_state_pointer.lock().osc_query_populate();
 
                     // Check if toy is valid
                     if let Some(toy) = toy {
@@ -192,17 +194,12 @@ pub async fn client_event_handler(
                             .send(ToyManagementEvent::Tu(ToyUpdate::RemoveToy(dev.index())))
                             .unwrap();
 
-                        emit_toy_event(&app_handle, FeToyEvent::Remove(dev.index()));
+                        emit_toy_event(&core_handle, FeToyEvent::Remove(dev.index()));
 
                         {
                             let vc_lock = vibecheck_state_pointer.lock();
                             if vc_lock.config.desktop_notifications {
-                                app_handle
-                                    .notification()
-                                    .builder()
-                                    .title("Toy Disconnected")
-                                    .body(toy.toy_name.to_string())
-                                    .show();
+                                core_handle.notify("Toy Disconnected", &toy.toy_name);
                             }
                         }
 
@@ -215,7 +212,7 @@ pub async fn client_event_handler(
                                     .spawn(vc_lock.bp_client.as_ref().unwrap().start_scanning());
                             }
 
-                            emit_core_event(&app_handle, FeCoreEvent::Scan(FeScanEvent::Start));
+                            emit_core_event(&core_handle, FeCoreEvent::Scan(FeScanEvent::Start));
                         }
                     }
                 }
@@ -236,8 +233,65 @@ pub async fn client_event_handler(
                 }
             }
         } else {
-            warn!("GOT NONE IN EVENT HANDLER: THIS SHOULD NEVER HAPPEN LOL");
+            warn!("Client event stream ended");
+            break;
         }
     }
+
+    // The server went away and took its devices with it
+    {
+        let mut vc_lock = vibecheck_state_pointer.lock();
+
+        // A connector switch already took over the client
+        if vc_lock.bp_client.take().is_none() {
+            info!("Event handler returning!");
+            return;
+        }
+        vc_lock.remove_online_toys();
+
+        info!("Reconnecting Buttplug client..");
+        let reconnect_thread = vc_lock
+            .async_rt
+            .spawn(bp_client_connect(vibecheck_state_pointer.clone()));
+        vc_lock.client_eh_thread = Some(reconnect_thread);
+    }
     info!("Event handler returning!");
 }
+
+// Time between attempts to reach an external Buttplug server
+const BP_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/*
+ * Connects a ButtplugClient with the configured connector and starts a CEH for it
+ * Websocket connectors are retried until the server is reachable
+ */
+pub async fn bp_client_connect(vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>) {
+    loop {
+        let (connector, virtual_command_log) = {
+            let vc_lock = vibecheck_state_pointer.lock();
+            (
+                vc_lock.config.bp_connector.clone(),
+                vc_lock.virtual_command_log.clone(),
+            )
+        };
+
+        match bluetooth::vc_toy_client_init("VibeCheck", false, &connector, &virtual_command_log)
+            .await
+        {
+            Ok(bp_client) => {
+                info!("Buttplug Client Initialized.");
+                vibecheck_state_pointer.lock().start_ceh(bp_client);
+                return;
+            }
+            Err(e) => {
+                if !matches!(connector, ButtplugConnectorConfig::Websocket(_)) {
+                    logerr!("Failed to initialize bpio: {:?}", e);
+                    return;
+                }
+                trace!("Buttplug server not reachable. Retrying..");
+            }
+        }
+
+        Delay::new(BP_RECONNECT_INTERVAL).await;
+    }
+}
@@ -1,26 +1,39 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use buttplug::client::ButtplugClientDevice;
-use log::debug;
+use log::{debug, warn};
 use tokio::{
-    sync::{mpsc::error::TryRecvError, mpsc::UnboundedReceiver, watch},
+    sync::{mpsc::error::TryRecvError, mpsc::UnboundedReceiver},
     time::Instant,
 };
 
-use crate::toy_handling::{
-    runtime::toy_management_handler::sleep_for_constant_rate,
-    toy_command_processor::command_toy,
-    toyops::{LevelTweaks, VCFeatureType},
+use crate::{
+    frontend::frontend_types::FeSafetyLimit,
+    osc::level_output::LevelOutput,
+    toy_handling::{
+        output_control::OutputControl,
+        runtime::toy_management_handler::sleep_for_constant_rate,
+        safety::{slew, slew_duration_ms, RunCap, SafetyConfig, SafetyReports},
+        toy_command_processor::{clamp_and_flip, command_toy_features, FeatureCommand},
+        toyops::{LevelTweaks, VCFeatureType, VCToy},
+    },
+    vcore::host::CoreHandle,
 };
 
 pub enum ToyEmitterThreadSignal {
     StopExecution,
     UpdateRate(u64),
+    // Toy's OSC Data turned on or off
+    UpdateOscData(bool),
+    // Toy's safety override changed (None uses the global safety config)
+    UpdateSafety(Option<SafetyConfig>),
 }
 
 #[derive(Debug, Clone)]
 pub struct OscParserData {
-    dev: Arc<ButtplugClientDevice>,
     feature_type: VCFeatureType,
     float_level: f64,
     feature_index: u32,
@@ -30,7 +43,6 @@ pub struct OscParserData {
 
 impl OscParserData {
     pub fn new(
-        dev: Arc<ButtplugClientDevice>,
         feature_type: VCFeatureType,
         float_level: f64,
         feature_index: u32,
@@ -38,7 +50,6 @@ impl OscParserData {
         feature_levels: LevelTweaks,
     ) -> Self {
         Self {
-            dev,
             feature_type,
             float_level,
             feature_index,
@@ -46,67 +57,525 @@ impl OscParserData {
             feature_levels,
         }
     }
+
+    /*
+     * Output level the feature should reach at a time in seconds
+     * Modulated, then shaped by the response curve, range and flip
+     */
+    fn target_output(&self, modulation_secs: f64) -> f64 {
+        let level = if self.modulated() {
+            self.feature_levels
+                .modulation
+                .apply(self.float_level, self.flip_float, modulation_secs)
+        } else {
+            self.float_level
+        };
+        clamp_and_flip(level, self.flip_float, &self.feature_levels)
+    }
+
+    // Linear positions are strokes, moving them every tick would restart the stroke
+    fn modulated(&self) -> bool {
+        self.feature_type != VCFeatureType::Linear && self.feature_levels.modulation.is_active()
+    }
+
+    fn feature_command(&self, output_level: f64) -> FeatureCommand {
+        FeatureCommand {
+            feature_type: self.feature_type,
+            output_level,
+            feature_index: self.feature_index,
+            linear_position_speed: self.feature_levels.linear_position_speed,
+        }
+    }
 }
 
 pub struct EmitterThreadData {
+    // Toy id and sub id
+    toy: (u32, u8),
     update_rate: u64,
     in_signal: UnboundedReceiver<ToyEmitterThreadSignal>,
-    in_osc_data: watch::Receiver<Option<OscParserData>>,
+    in_osc_data: UnboundedReceiver<OscParserData>,
+    dev: Arc<ButtplugClientDevice>,
+    output_control: OutputControl,
+    safety_override: Option<SafetyConfig>,
+    safety_reports: SafetyReports,
+    level_output: LevelOutput,
 }
 
 impl EmitterThreadData {
+    // Ids, update rate, safety override and name are read from the toy
     pub fn new(
         in_signal: UnboundedReceiver<ToyEmitterThreadSignal>,
-        in_osc_data: watch::Receiver<Option<OscParserData>>,
-        update_rate: u64,
+        in_osc_data: UnboundedReceiver<OscParserData>,
+        vc_toy: &VCToy,
+        dev: Arc<ButtplugClientDevice>,
+        output_control: OutputControl,
+        core_handle: CoreHandle,
+        level_output: LevelOutput,
     ) -> Self {
         Self {
-            update_rate,
+            toy: (vc_toy.toy_id, vc_toy.sub_id),
+            update_rate: vc_toy.bt_update_rate,
             in_signal,
             in_osc_data,
+            dev,
+            output_control,
+            safety_override: vc_toy.config.as_ref().and_then(|config| config.safety),
+            safety_reports: SafetyReports::new(core_handle, vc_toy.toy_name.clone()),
+            level_output,
         }
     }
 }
 
 pub async fn toy_emitter_thread(mut data: EmitterThreadData) {
+    // Latest desired level per feature
+    let mut desired_features: HashMap<(VCFeatureType, u32), OscParserData> = HashMap::new();
+    // Features whose sent level has not reached the desired level yet
+    let mut pending_features: HashSet<(VCFeatureType, u32)> = HashSet::new();
+    // Last output level sent per feature. Missing features are stopped
+    let mut sent_levels: HashMap<(VCFeatureType, u32), f64> = HashMap::new();
+    let mut sent_intensity = data.output_control.intensity();
+    let mut was_held = false;
+    let mut last_tick = Instant::now();
+    // Time base of every feature's modulation
+    let modulation_start = Instant::now();
+
     loop {
         let start = Instant::now();
+        let tick = start.duration_since(last_tick);
+        last_tick = start;
         // Logic
-        // Parse & send bluetooth command (watch)
-        if let Ok(changed) = data.in_osc_data.has_changed() {
-            if changed {
-                let osc_data = { data.in_osc_data.borrow_and_update().to_owned() };
-                if let Some(osc_data) = osc_data {
+        // Collect the latest level for every feature updated since the last tick
+        loop {
+            match data.in_osc_data.try_recv() {
+                Ok(osc_data) => {
+                    let feature = (osc_data.feature_type, osc_data.feature_index);
+                    desired_features.insert(feature, osc_data);
+                    pending_features.insert(feature);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    data.level_output.send_stopped();
+                    return;
+                }
+            }
+        }
+
+        if data.output_control.is_emergency_stopped() {
+            // Levels are dropped so nothing starts again on re-arm until new input
+            desired_features.clear();
+            pending_features.clear();
+            sent_levels.clear();
+            data.level_output.stop_all();
+            was_held = false;
+        } else {
+            if data.output_control.run_cap(data.toy, RunCap::end_cooldown) {
+                data.safety_reports.report(FeSafetyLimit::CooldownOver);
+            }
+
+            // Levels are held while paused or cooling down and sent once that is over
+            let held = data.output_control.is_paused()
+                || data
+                    .output_control
+                    .run_cap(data.toy, |run_cap| run_cap.cooling_down());
+            if held {
+                // The toy is stopped, so output ramps up from idle afterwards
+                sent_levels.clear();
+                data.level_output.stop_all();
+            } else {
+                let safety = data.output_control.toy_safety(data.safety_override);
+
+                // Toys were stopped while held or run at another intensity, send every level again
+                let intensity = data.output_control.intensity();
+                if was_held || intensity != sent_intensity {
+                    pending_features.extend(desired_features.keys().copied());
+                    sent_intensity = intensity;
+                }
+
+                // Step every pending feature's output towards its target within the slew limit
+                // so ramps start from the motor's real level, not from before the range
+                let max_step = (safety.max_change_per_second > 0.0)
+                    .then(|| safety.max_change_per_second * tick.as_secs_f64());
+                let modulation_secs = modulation_start.elapsed().as_secs_f64();
+                let mut commands = Vec::new();
+                let mut slew_limited = false;
+                pending_features.retain(|feature| {
+                    let Some(osc_data) = desired_features.get(feature) else {
+                        return false;
+                    };
+                    let target_level = osc_data.target_output(modulation_secs);
+                    let sent_level = sent_levels.get(feature).copied();
+                    let mut command = osc_data.feature_command(target_level);
+                    if osc_data.feature_type == VCFeatureType::Linear {
+                        // Linear positions are reached in one stroke that is slowed down instead
+                        let duration_ms = slew_duration_ms(
+                            sent_level.unwrap_or(0.0),
+                            target_level,
+                            command.linear_position_speed,
+                            safety.max_change_per_second,
+                        );
+                        slew_limited |= duration_ms != command.linear_position_speed;
+                        command.linear_position_speed = duration_ms;
+                    } else {
+                        command.output_level =
+                            slew(sent_level.unwrap_or(0.0), target_level, max_step);
+                    }
+                    let level = command.output_level;
+                    sent_levels.insert(*feature, level);
+                    // Modulated features stay pending but only send levels that changed
+                    let modulated = osc_data.modulated();
+                    if !modulated || sent_level != Some(level) {
+                        commands.push(command);
+                    }
+
+                    let ramping = level != target_level;
+                    slew_limited |= ramping;
+                    ramping || modulated
+                });
+
+                if slew_limited {
+                    data.safety_reports.report(FeSafetyLimit::SlewLimit);
+                }
+
+                // Parse & send one batched bluetooth command for every changed feature
+                if !commands.is_empty() {
                     debug!(
-                        "Sending {} to toy {} and feature {}",
-                        osc_data.float_level,
-                        osc_data.dev.index(),
-                        osc_data.feature_index
+                        "Sending {} feature levels to toy {}",
+                        commands.len(),
+                        data.dev.index()
                     );
-                    command_toy(
-                        osc_data.dev,
-                        osc_data.feature_type,
-                        osc_data.float_level,
-                        osc_data.feature_index,
-                        osc_data.flip_float,
-                        osc_data.feature_levels,
+                    let commanded = command_toy_features(
+                        &data.dev,
+                        &data.output_control,
+                        safety.max_level,
+                        commands,
                     )
                     .await;
+                    if commanded.ceiling_hit {
+                        data.safety_reports.report(FeSafetyLimit::Ceiling);
+                    }
+                    for (feature, level) in commanded.levels {
+                        data.level_output.update(feature, level);
+                    }
+                }
+
+                // Any feature above idle keeps the run timer going
+                let running = sent_levels.values().any(|level| *level > 0.0);
+                if data
+                    .output_control
+                    .run_cap(data.toy, |run_cap| run_cap.update(running, &safety))
+                {
+                    if let Err(e) = data.dev.stop().await {
+                        warn!("Failed to stop toy {} for run cap: {}", data.dev.index(), e);
+                    }
+                    sent_levels.clear();
+                    data.level_output.stop_all();
+                    data.safety_reports
+                        .report(FeSafetyLimit::RunCap(safety.cooldown_minutes));
                 }
             }
+            was_held = held;
         }
+        data.level_output.send(false);
 
         // Check for incoming update messages
         match data.in_signal.try_recv() {
             Ok(signal) => match signal {
-                ToyEmitterThreadSignal::StopExecution => return,
+                ToyEmitterThreadSignal::StopExecution => {
+                    data.level_output.send_stopped();
+                    return;
+                }
                 ToyEmitterThreadSignal::UpdateRate(hz) => data.update_rate = hz,
+                ToyEmitterThreadSignal::UpdateOscData(enabled) => {
+                    data.level_output.set_enabled(enabled)
+                }
+                ToyEmitterThreadSignal::UpdateSafety(safety) => data.safety_override = safety,
             },
             Err(e) => match e {
-                TryRecvError::Disconnected => return,
+                TryRecvError::Disconnected => {
+                    data.level_output.send_stopped();
+                    return;
+                }
                 TryRecvError::Empty => (),
             },
         }
         sleep_for_constant_rate(data.update_rate, start).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddrV4},
+        sync::OnceLock,
+        time::Duration,
+    };
+
+    use buttplug::client::ButtplugClient;
+    use parking_lot::Mutex;
+    use tokio::{sync::mpsc::unbounded_channel, time::sleep};
+
+    use super::*;
+    use crate::{
+        frontend::{
+            error::FrontendError,
+            frontend_types::{FeCoreEvent, FeToyEvent},
+        },
+        osc::level_output::OscLevelOutputConfig,
+        toy_handling::{
+            modulation::{Modulation, ModulationKind},
+            toyops::VCToyFeatures,
+            ToyPower,
+        },
+        util::{
+            bluetooth::vc_toy_client_virtual_init,
+            errors::UtilError,
+            virtual_devices::{VirtualCommand, VirtualCommandLog, VirtualDeviceConfig},
+        },
+        vcore::{
+            config::app::VibeCheckConfig,
+            host::VibeCheckHost,
+            state::{VCStateMutex, VibeCheckState},
+        },
+    };
+
+    // The emitter only emits events so the host has an idle state and no config
+    struct TestHost;
+
+    // Never dropped so the state's runtime is not dropped inside a test runtime
+    fn idle_state() -> &'static VCStateMutex {
+        static STATE: OnceLock<VCStateMutex> = OnceLock::new();
+        STATE.get_or_init(|| {
+            VCStateMutex(Arc::new(Mutex::new(VibeCheckState::new(
+                VibeCheckConfig::default(),
+            ))))
+        })
+    }
+
+    impl VibeCheckHost for TestHost {
+        fn config_dir(&self) -> Result<String, UtilError> {
+            Err(UtilError::ConfigDirFS)
+        }
+        fn state(&self) -> &VCStateMutex {
+            idle_state()
+        }
+        fn emit_toy_event(&self, _event: FeToyEvent) {}
+        fn emit_core_event(&self, _event: FeCoreEvent) {}
+        fn emit_error(&self, _event: FrontendError) {}
+        fn notify(&self, _title: &str, _body: &str) {}
+    }
+
+    // The client is returned so the virtual devices stay connected
+    async fn virtual_toy(
+        command_log: &VirtualCommandLog,
+    ) -> (ButtplugClient, Arc<ButtplugClientDevice>) {
+        let device = VirtualDeviceConfig {
+            name: "Virtual Toy".to_string(),
+            vibrators: 2,
+            rotators: 0,
+            scalar_rotators: 0,
+            linears: 1,
+            constrict: 0,
+            oscillate: 0,
+            inflate: 0,
+            position: 0,
+            battery: false,
+        };
+        let client = vc_toy_client_virtual_init("VibeCheck Test", vec![device], command_log)
+            .await
+            .expect("Virtual devices should connect");
+
+        for _ in 0..50 {
+            if let Some(dev) = client.devices().into_iter().next() {
+                return (client, dev);
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Virtual device was not added");
+    }
+
+    fn vc_toy(dev: &Arc<ButtplugClientDevice>) -> VCToy {
+        VCToy {
+            toy_id: dev.index(),
+            toy_name: dev.name().clone(),
+            toy_power: ToyPower::NoBattery,
+            toy_connected: true,
+            toy_features: dev.message_attributes().clone(),
+            parsed_toy_features: VCToyFeatures::new(),
+            osc_data: false,
+            listening: false,
+            bt_update_rate: 100,
+            device_handle: dev.clone(),
+            config: None,
+            sub_id: 0,
+            core_handle: CoreHandle::new(TestHost),
+        }
+    }
+
+    // Waits until at least count commands were recorded
+    async fn recorded_commands(
+        command_log: &VirtualCommandLog,
+        count: usize,
+    ) -> Vec<VirtualCommand> {
+        let mut commands = Vec::new();
+        for _ in 0..50 {
+            commands.extend(command_log.drain().into_iter().map(|record| record.command));
+            if commands.len() >= count {
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        commands
+    }
+
+    fn vibrate(feature_index: u32, level: f64) -> VirtualCommand {
+        VirtualCommand::Scalar {
+            feature_index,
+            level,
+            actuator_type: "Vibrate".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn emitter_commands_virtual_device() {
+        let command_log = VirtualCommandLog::new();
+        let (_client, dev) = virtual_toy(&command_log).await;
+        let vc_toy = vc_toy(&dev);
+
+        let output_control = OutputControl::new();
+        output_control.set_safety(SafetyConfig {
+            max_level: 0.5,
+            ..Default::default()
+        });
+
+        let (signal_tx, signal_rx) = unbounded_channel();
+        let (osc_tx, osc_rx) = unbounded_channel();
+        let level_output = LevelOutput::new(
+            OscLevelOutputConfig::default(),
+            SocketAddrV4::new(Ipv4Addr::LOCALHOST, 9000),
+            &vc_toy,
+        );
+        let emitter = tokio::spawn(toy_emitter_thread(EmitterThreadData::new(
+            signal_rx,
+            osc_rx,
+            &vc_toy,
+            dev.clone(),
+            output_control.clone(),
+            vc_toy.core_handle.clone(),
+            level_output,
+        )));
+
+        let linear_levels = LevelTweaks {
+            linear_position_speed: 400,
+            ..Default::default()
+        };
+        for osc_data in [
+            OscParserData::new(
+                VCFeatureType::Vibrator,
+                0.8,
+                0,
+                false,
+                LevelTweaks::default(),
+            ),
+            OscParserData::new(
+                VCFeatureType::Vibrator,
+                0.25,
+                1,
+                false,
+                LevelTweaks::default(),
+            ),
+            OscParserData::new(VCFeatureType::Linear, 0.75, 0, false, linear_levels),
+        ] {
+            osc_tx.send(osc_data).unwrap();
+        }
+
+        // Levels and positions are capped to the safety max level
+        let commands = recorded_commands(&command_log, 3).await;
+        assert_eq!(commands.len(), 3, "Unexpected commands: {:?}", commands);
+        assert!(commands.contains(&vibrate(0, 0.5)));
+        assert!(commands.contains(&vibrate(1, 0.25)));
+        assert!(commands.contains(&VirtualCommand::Linear {
+            feature_index: 0,
+            duration: 400,
+            position: 0.5,
+        }));
+
+        // Nothing is sent while emergency stopped
+        output_control.latch_emergency_stop();
+        osc_tx
+            .send(OscParserData::new(
+                VCFeatureType::Vibrator,
+                1.0,
+                0,
+                false,
+                LevelTweaks::default(),
+            ))
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+        assert!(command_log.drain().is_empty());
+
+        signal_tx
+            .send(ToyEmitterThreadSignal::StopExecution)
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), emitter)
+            .await
+            .expect("Emitter thread should stop")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn command_toy_features_applies_intensity_and_ceiling() {
+        let command_log = VirtualCommandLog::new();
+        let (_client, dev) = virtual_toy(&command_log).await;
+
+        let output_control = OutputControl::new();
+        output_control.set_intensity(0.5);
+        let command = |feature_index, output_level| FeatureCommand {
+            feature_type: VCFeatureType::Vibrator,
+            output_level,
+            feature_index,
+            linear_position_speed: 0,
+        };
+
+        let commanded = command_toy_features(
+            &dev,
+            &output_control,
+            0.25,
+            [command(0, 1.0), command(1, 0.25)],
+        )
+        .await;
+        assert!(commanded.ceiling_hit);
+
+        let commands = recorded_commands(&command_log, 2).await;
+        assert_eq!(commands.len(), 2, "Unexpected commands: {:?}", commands);
+        assert!(commands.contains(&vibrate(0, 0.25)));
+        assert!(commands.contains(&vibrate(1, 0.125)));
+    }
+
+    #[test]
+    fn linear_positions_are_not_modulated() {
+        let feature_levels = LevelTweaks {
+            minimum_level: 0.0,
+            maximum_level: 1.0,
+            modulation: Modulation {
+                kind: ModulationKind::Square,
+                amplitude: 0.2,
+                frequency_hz: 1.0,
+                seed: 0,
+            },
+            ..Default::default()
+        };
+        let vibrator = OscParserData::new(
+            VCFeatureType::Vibrator,
+            0.5,
+            0,
+            false,
+            feature_levels.clone(),
+        );
+        let linear = OscParserData::new(VCFeatureType::Linear, 0.5, 0, false, feature_levels);
+
+        assert!(vibrator.modulated());
+        assert!((vibrator.target_output(0.25) - 0.7).abs() < 1e-9);
+        assert!(!linear.modulated());
+        assert_eq!(linear.target_output(0.25), 0.5);
+    }
+}
@@ -12,29 +12,32 @@ use buttplug::client::ButtplugClientDevice;
 use futures_timer::Delay;
 use log::{debug, error as logerr, info, warn};
 use parking_lot::{lock_api::Mutex, RawMutex};
-use tauri::AppHandle;
 use tokio::{
     runtime::Runtime,
-    sync::{
-        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
-        watch,
-    },
+    select,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
     task::JoinHandle,
-    time::Instant,
+    time::{interval, Instant, Interval, MissedTickBehavior},
 };
 
 use crate::{
-    osc::{logic::toy_input_routine, OSCNetworking},
+    osc::{level_output::LevelOutput, logic::toy_input_routine, OSCNetworking},
     toy_handling::{
-        osc_processor::parse_osc_message,
+        funscript::{FunscriptControl, FunscriptLibrary, FunscriptPlayer},
+        osc_processor::{parse_osc_message, tick_toy_features},
+        output_control::OutputControl,
+        patterns::PatternLibrary,
         runtime::toy_emitter_thread::{
-            toy_emitter_thread, EmitterThreadData, ToyEmitterThreadSignal,
+            toy_emitter_thread, EmitterThreadData, OscParserData, ToyEmitterThreadSignal,
         },
         toy_manager::ToyManager,
-        toyops::VCToy,
+        toyops::{LevelTweaks, VCFeatureType, VCToy, VCToyFeatures},
         ToySig,
     },
-    vcore::ipc::call_plane::{TmSig, ToyManagementEvent, ToyUpdate},
+    vcore::{
+        host::CoreHandle,
+        ipc::call_plane::{TmSig, ToyManagementEvent, ToyUpdate},
+    },
 };
 
 use tokio::sync::{
@@ -55,6 +58,39 @@ pub async fn sleep_for_constant_rate(rate: u64, start: Instant) {
     }
 }
 
+fn mode_tick_interval(update_rate: u64) -> Interval {
+    let mut mode_tick = interval(Duration::from_secs_f64(1.0 / update_rate.max(1) as f64));
+    mode_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    mode_tick
+}
+
+/*
+ * Moves every enabled Linear feature to the stroke position over the stroke duration
+ * Strokes go through the emitter so pause, cooldown and the emergency stop hold them like OSC input
+ */
+fn send_funscript_stroke(
+    emitter_thread_osc_tx: &UnboundedSender<OscParserData>,
+    vc_toy_features: &VCToyFeatures,
+    (position, duration_ms): (f64, u32),
+) {
+    for feature in vc_toy_features
+        .features
+        .iter()
+        .filter(|feature| feature.feature_enabled && feature.feature_type == VCFeatureType::Linear)
+    {
+        let _ = emitter_thread_osc_tx.send(OscParserData::new(
+            feature.feature_type,
+            position,
+            feature.feature_index,
+            feature.flip_input_float,
+            LevelTweaks {
+                linear_position_speed: duration_ms,
+                ..feature.feature_levels.clone()
+            },
+        ));
+    }
+}
+
 #[inline(always)]
 fn update_toy(
     emitter_thread_tx: &UnboundedSender<ToyEmitterThreadSignal>,
@@ -70,10 +106,19 @@ fn update_toy(
     }
 
     vc_toy.parsed_toy_features = new_toy.parsed_toy_features;
+    if vc_toy.osc_data != new_toy.osc_data {
+        vc_toy.osc_data = new_toy.osc_data;
+        emitter_thread_tx.send(ToyEmitterThreadSignal::UpdateOscData(new_toy.osc_data));
+    }
     if vc_toy.bt_update_rate != new_toy.bt_update_rate {
         vc_toy.bt_update_rate = new_toy.bt_update_rate;
         emitter_thread_tx.send(ToyEmitterThreadSignal::UpdateRate(new_toy.bt_update_rate));
     }
+    let safety = new_toy.config.as_ref().and_then(|config| config.safety);
+    if vc_toy.config.as_ref().and_then(|config| config.safety) != safety {
+        emitter_thread_tx.send(ToyEmitterThreadSignal::UpdateSafety(safety));
+    }
+    vc_toy.config = new_toy.config;
 
     info!("Altered toy: {}", new_toy.toy_id);
 }
@@ -83,23 +128,34 @@ pub async fn toy_management_handler(
     mut tme_recv: UnboundedReceiver<ToyManagementEvent>,
     mut core_toy_manager: ToyManager,
     mut vc_config: OSCNetworking,
-    app_handle: AppHandle,
+    core_handle: CoreHandle,
+    output_control: OutputControl,
 ) {
     let toy_thread_function = |async_rt: Arc<Mutex<RawMutex, Option<Runtime>>>,
                                dev: Arc<ButtplugClientDevice>,
                                mut toy_bcst_rx: BReceiver<ToySig>,
-                               mut vc_toy: VCToy| {
+                               mut vc_toy: VCToy,
+                               patterns: PatternLibrary,
+                               funscripts: FunscriptLibrary,
+                               osc_networking: OSCNetworking| {
+        let output_control = output_control.clone();
+        let core_handle = core_handle.clone();
         // Read toy config here?
         async move {
             // Create in_signal channel for emitter thread
             let (emitter_thread_tx, emitter_thread_rx) =
                 unbounded_channel::<ToyEmitterThreadSignal>();
-            let (emitter_thread_osc_tx, emitter_thread_osc_rx) = watch::channel(None);
+            let (emitter_thread_osc_tx, emitter_thread_osc_rx) =
+                unbounded_channel::<OscParserData>();
 
             let tet_data = EmitterThreadData::new(
                 emitter_thread_rx,
                 emitter_thread_osc_rx,
-                vc_toy.bt_update_rate,
+                &vc_toy,
+                dev.clone(),
+                output_control.clone(),
+                core_handle,
+                LevelOutput::new(osc_networking.level_output, osc_networking.remote, &vc_toy),
             );
 
             async_rt
@@ -108,22 +164,50 @@ pub async fn toy_management_handler(
                 .unwrap()
                 .spawn(async move { toy_emitter_thread(tet_data).await });
 
+            // Advances the processing modes that change level without OSC input
+            let mut mode_tick = mode_tick_interval(vc_toy.bt_update_rate);
+            let mut funscript_player = FunscriptPlayer::default();
+
             while dev.connected() {
-                let Ok(ts) = toy_bcst_rx.recv().await else {
-                    continue;
-                };
-                match ts {
-                    ToySig::OSCMsg(mut msg) => {
-                        parse_osc_message(
-                            &emitter_thread_osc_tx,
-                            &mut msg,
-                            dev.clone(),
-                            &mut vc_toy.parsed_toy_features,
-                        )
-                        .await
+                select! {
+                    ts = toy_bcst_rx.recv() => {
+                        let Ok(ts) = ts else {
+                            continue;
+                        };
+                        match ts {
+                            ToySig::OSCMsg(mut msg) => {
+                                parse_osc_message(
+                                    &emitter_thread_osc_tx,
+                                    &mut msg,
+                                    &mut vc_toy.parsed_toy_features,
+                                    &patterns,
+                                )
+                                .await
+                            }
+                            ToySig::UpdateToy(toy) => {
+                                let update_rate = vc_toy.bt_update_rate;
+                                update_toy(&emitter_thread_tx, toy, dev.clone(), &mut vc_toy);
+                                if vc_toy.bt_update_rate != update_rate {
+                                    mode_tick = mode_tick_interval(vc_toy.bt_update_rate);
+                                }
+                            }
+                            ToySig::Funscript(control) => {
+                                funscript_player.control(control, &funscripts);
+                            }
+                        }
                     }
-                    ToySig::UpdateToy(toy) => {
-                        update_toy(&emitter_thread_tx, toy, dev.clone(), &mut vc_toy);
+                    _ = mode_tick.tick() => {
+                        tick_toy_features(&emitter_thread_osc_tx, &mut vc_toy.parsed_toy_features);
+
+                        if output_control.is_emergency_stopped() {
+                            funscript_player.control(FunscriptControl::Stop, &funscripts);
+                        } else if let Some(stroke) = funscript_player.tick() {
+                            send_funscript_stroke(
+                                &emitter_thread_osc_tx,
+                                &vc_toy.parsed_toy_features,
+                                stroke,
+                            );
+                        }
                     }
                 }
             }
@@ -183,6 +267,10 @@ pub async fn toy_management_handler(
         info!("Started listening!");
         // Recv events (listening)
 
+        // Patterns and funscripts are read again every time listening starts
+        let patterns = PatternLibrary::load(&core_handle);
+        let funscripts = FunscriptLibrary::load(&core_handle);
+
         // Toy threads
         let mut running_toy_ths: HashMap<u32, JoinHandle<()>> = HashMap::new();
 
@@ -198,6 +286,9 @@ pub async fn toy_management_handler(
                 toy.1.device_handle.clone(),
                 toy_sig_bcst_tx.subscribe(),
                 toy.1.clone(),
+                patterns.clone(),
+                funscripts.clone(),
+                vc_config.clone(),
             );
             let new_thread = {
                 toy_async_rt
@@ -218,12 +309,12 @@ pub async fn toy_management_handler(
         info!("Spawning OSC listener..");
         let vc_conf_clone = vc_config.clone();
         let tme_send_clone = tme_send.clone();
-        let app_handle_clone = app_handle.clone();
+        let core_handle_clone = core_handle.clone();
         thread::spawn(move || {
             toy_input_routine(
                 toy_bcst_tx_osc,
                 tme_send_clone,
-                app_handle_clone,
+                core_handle_clone,
                 vc_conf_clone,
             )
         });
@@ -243,6 +334,9 @@ pub async fn toy_management_handler(
                                 toy.device_handle.clone(),
                                 toy_sig_bcst_tx.subscribe(),
                                 toy.clone(),
+                                patterns.clone(),
+                                funscripts.clone(),
+                                vc_config.clone(),
                             );
                             let new_thread =
                                 {
@@ -293,6 +387,11 @@ pub async fn toy_management_handler(
                             vc_config = osc_net;
                             // Already listening
                         }
+                        TmSig::Funscript(control) => {
+                            if let Err(e) = toy_sig_bcst_tx.send(ToySig::Funscript(control)) {
+                                logerr!("Failed to send funscript control: {}", e)
+                            }
+                        }
                         TmSig::StopListening => {
                             // Stop listening on every device and clean running thread hashmap
 
@@ -0,0 +1,215 @@
+/*
+ * Safety limits
+ * Applied to every toy between the mode processors and the toy commands.
+ */
+
+use std::{
+    collections::HashMap,
+    mem::{discriminant, Discriminant},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    frontend::frontend_types::{FeCoreEvent, FeSafetyEvent, FeSafetyLimit},
+    vcore::{host::CoreHandle, ipc::emit_plane::emit_core_event},
+};
+
+// Ceiling and slew limits kick in constantly so they are reported at most this often per toy
+const LIMIT_REPORT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct SafetyConfig {
+    // Hard ceiling for every feature level (1.0 = off)
+    pub max_level: f64,
+    // Largest level change per second (0.0 = off)
+    pub max_change_per_second: f64,
+    // Minutes a toy can run without going idle before it is forced idle (0 = off)
+    pub run_cap_minutes: u32,
+    // Minutes a toy stays idle after hitting the run cap
+    pub cooldown_minutes: u32,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        Self {
+            max_level: 1.0,
+            max_change_per_second: 0.0,
+            run_cap_minutes: 0,
+            cooldown_minutes: 5,
+        }
+    }
+}
+
+impl SafetyConfig {
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.max_level)
+            && self.max_change_per_second.is_finite()
+            && self.max_change_per_second >= 0.0
+    }
+}
+
+// Moves current toward target by at most max_step
+#[inline]
+pub fn slew(current: f64, target: f64, max_step: Option<f64>) -> f64 {
+    match max_step {
+        Some(max_step) => current + (target - current).clamp(-max_step, max_step),
+        None => target,
+    }
+}
+
+// Stroke duration (ms) that moves a Linear position no faster than max_change_per_second (0.0 = off)
+#[inline]
+pub fn slew_duration_ms(
+    current: f64,
+    target: f64,
+    duration_ms: u32,
+    max_change_per_second: f64,
+) -> u32 {
+    if max_change_per_second <= 0.0 {
+        return duration_ms;
+    }
+    let slewed_ms = ((target - current).abs() / max_change_per_second * 1000.0).ceil();
+    duration_ms.max(slewed_ms.min(u32::MAX as f64) as u32)
+}
+
+/*
+ * Tracks how long a toy has been running without going idle
+ */
+#[derive(Default)]
+pub struct RunCap {
+    running_since: Option<Instant>,
+    cooldown_until: Option<Instant>,
+}
+
+impl RunCap {
+    pub fn cooling_down(&self) -> bool {
+        self.cooldown_until
+            .is_some_and(|cooldown_until| Instant::now() < cooldown_until)
+    }
+
+    // Returns true once when the cooldown is over
+    pub fn end_cooldown(&mut self) -> bool {
+        match self.cooldown_until {
+            Some(cooldown_until) if Instant::now() >= cooldown_until => {
+                self.cooldown_until = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /*
+     * Updates the run time with whether the toy is running
+     * Returns true when the toy has to be forced idle
+     */
+    pub fn update(&mut self, running: bool, config: &SafetyConfig) -> bool {
+        if !running || config.run_cap_minutes == 0 {
+            self.running_since = None;
+            return false;
+        }
+
+        let running_since = *self.running_since.get_or_insert_with(Instant::now);
+        if running_since.elapsed() < Duration::from_secs(config.run_cap_minutes as u64 * 60) {
+            return false;
+        }
+
+        self.running_since = None;
+        self.cooldown_until =
+            Some(Instant::now() + Duration::from_secs(config.cooldown_minutes as u64 * 60));
+        true
+    }
+}
+
+/*
+ * Reports safety limits kicking in for one toy to the frontend
+ */
+pub struct SafetyReports {
+    core_handle: CoreHandle,
+    toy_name: String,
+    last_reports: HashMap<Discriminant<FeSafetyLimit>, Instant>,
+}
+
+impl SafetyReports {
+    pub fn new(core_handle: CoreHandle, toy_name: String) -> Self {
+        Self {
+            core_handle,
+            toy_name,
+            last_reports: HashMap::new(),
+        }
+    }
+
+    pub fn report(&mut self, limit: FeSafetyLimit) {
+        if matches!(limit, FeSafetyLimit::Ceiling | FeSafetyLimit::SlewLimit) {
+            let now = Instant::now();
+            match self.last_reports.get(&discriminant(&limit)) {
+                Some(last_report) if now.duration_since(*last_report) < LIMIT_REPORT_INTERVAL => {
+                    return
+                }
+                _ => {
+                    self.last_reports.insert(discriminant(&limit), now);
+                }
+            }
+        }
+
+        warn!("Safety limit for {}: {:?}", self.toy_name, limit);
+        emit_core_event(
+            &self.core_handle,
+            FeCoreEvent::Safety(FeSafetyEvent {
+                toy_name: self.toy_name.clone(),
+                limit,
+            }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slew_limits_each_step() {
+        assert_eq!(slew(0.0, 1.0, Some(0.25)), 0.25);
+        assert_eq!(slew(1.0, 0.0, Some(0.25)), 0.75);
+    }
+
+    #[test]
+    fn slew_reaches_close_targets() {
+        assert_eq!(slew(0.5, 0.625, Some(0.25)), 0.625);
+        assert_eq!(slew(0.5, 0.5, Some(0.25)), 0.5);
+    }
+
+    #[test]
+    fn slew_without_limit_jumps_to_target() {
+        assert_eq!(slew(0.0, 1.0, None), 1.0);
+    }
+
+    #[test]
+    fn slew_slows_down_strokes() {
+        assert_eq!(slew_duration_ms(0.0, 1.0, 200, 2.0), 500);
+        assert_eq!(slew_duration_ms(1.0, 0.5, 200, 2.0), 250);
+        // Strokes that are already slow enough keep their duration
+        assert_eq!(slew_duration_ms(0.0, 0.25, 200, 2.0), 200);
+        assert_eq!(slew_duration_ms(0.0, 1.0, 200, 0.0), 200);
+    }
+
+    #[test]
+    fn safety_config_validation() {
+        assert!(SafetyConfig::default().is_valid());
+        assert!(!SafetyConfig {
+            max_level: 1.5,
+            ..Default::default()
+        }
+        .is_valid());
+        assert!(!SafetyConfig {
+            max_change_per_second: f64::NAN,
+            ..Default::default()
+        }
+        .is_valid());
+    }
+}
@@ -1,7 +1,9 @@
+use crate::toy_handling::output_control::OutputControl;
 use crate::toy_handling::toyops::LevelTweaks;
 use crate::toy_handling::toyops::VCFeatureType;
 
 use buttplug::client::ButtplugClientDevice;
+use buttplug::client::LinearCommand::LinearMap;
 use buttplug::client::RotateCommand::RotateMap;
 use buttplug::client::ScalarCommand::ScalarMap;
 use buttplug::core::message::ActuatorType;
@@ -12,13 +14,64 @@ use std::collections::HashMap;
 
 use std::sync::Arc;
 
+/*
+ * An output level for a single toy feature
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureCommand {
+    pub feature_type: VCFeatureType,
+    // Level after the response curve, range and flip
+    pub output_level: f64,
+    pub feature_index: u32,
+    // Stroke duration for Linear features
+    pub linear_position_speed: u32,
+}
+
+impl FeatureCommand {
+    // Shapes a desired level with the feature's level tweaks
+    pub fn shaped(
+        feature_type: VCFeatureType,
+        float_level: f64,
+        feature_index: u32,
+        flip_float: bool,
+        feature_levels: &LevelTweaks,
+    ) -> Self {
+        Self {
+            feature_type,
+            output_level: clamp_and_flip(float_level, flip_float, feature_levels),
+            feature_index,
+            linear_position_speed: feature_levels.linear_position_speed,
+        }
+    }
+}
+
+/*
+ * Levels command_toy_features sent
+ */
+#[derive(Debug, Default)]
+pub struct CommandedLevels {
+    // Level of each feature after clamping, intensity and the safety ceiling
+    pub levels: Vec<((VCFeatureType, u32), f64)>,
+    // True if the safety ceiling limited any level
+    pub ceiling_hit: bool,
+}
+
+/*
+ * Shapes a feature level with the response curve then clamps it to the feature's range
+ * Levels the curve maps to 0.0 idle like a 0.0 input
+ */
 #[inline]
-fn clamp_and_flip(value: f64, flip: bool, levels: LevelTweaks) -> f64 {
+pub fn clamp_and_flip(value: f64, flip: bool, levels: &LevelTweaks) -> f64 {
     let mut new_value;
-    if value == 0.0 {
+    let curved = if value == 0.0 {
+        0.0
+    } else {
+        levels.response_curve.apply(value)
+    };
+    if curved <= 0.0 {
         new_value = levels.idle_level;
     } else {
-        new_value = value.clamp(levels.minimum_level, levels.maximum_level);
+        new_value = curved.clamp(levels.minimum_level, levels.maximum_level);
     }
     if flip {
         new_value = flip_float64(new_value)
@@ -32,41 +85,105 @@ pub fn flip_float64(orig: f64) -> f64 {
     ((1.00 - orig) * 100.0).round() / 100.0
 }
 
-// Parse scalar levels and logic for level tweaks
+// Rotator and Linear are not included in the Scalar feature set
 #[inline]
-pub async fn scalar_parse_levels_send_toy_cmd(
+fn scalar_actuator_type(feature_type: VCFeatureType) -> Option<ActuatorType> {
+    match feature_type {
+        VCFeatureType::Vibrator => Some(ActuatorType::Vibrate),
+        VCFeatureType::Constrict => Some(ActuatorType::Constrict),
+        VCFeatureType::Oscillate => Some(ActuatorType::Oscillate),
+        VCFeatureType::Position => Some(ActuatorType::Position),
+        VCFeatureType::Inflate => Some(ActuatorType::Inflate),
+        VCFeatureType::ScalarRotator => Some(ActuatorType::Rotate),
+        VCFeatureType::Rotator | VCFeatureType::Linear => None,
+    }
+}
+
+/*
+ * Sends commands for any number of features of one toy
+ * Features are batched into one ScalarMap / RotateMap / LinearMap each
+ * Nothing is sent while the emergency stop is latched
+ */
+pub async fn command_toy_features(
     dev: &Arc<ButtplugClientDevice>,
-    scalar_level: f64,
-    feature_index: u32,
-    actuator_type: ActuatorType,
-    flip_float: bool,
-    feature_levels: LevelTweaks,
-) {
-    let new_level = clamp_and_flip(scalar_level, flip_float, feature_levels);
-    #[cfg(debug_assertions)]
-    {
-        let message_prefix = if scalar_level == 0.0 {
-            "IDLE"
+    output_control: &OutputControl,
+    max_level: f64,
+    commands: impl IntoIterator<Item = FeatureCommand>,
+) -> CommandedLevels {
+    if output_control.is_emergency_stopped() {
+        return CommandedLevels::default();
+    }
+
+    // Global intensity multiplier and safety ceiling
+    let intensity = output_control.intensity();
+    let mut ceiling_hit = false;
+    let mut limit = |level: f64| {
+        if level > max_level {
+            ceiling_hit = true;
+            max_level
         } else {
-            "SENDING"
-        };
-        info!(
-            "{} FI[{}] AT[{}] SL[{}]",
-            message_prefix, feature_index, actuator_type, new_level
-        );
+            level
+        }
+    };
+    let mut scalar_map = HashMap::new();
+    let mut rotate_map = HashMap::new();
+    let mut linear_map = HashMap::new();
+    let mut levels = Vec::new();
+
+    for command in commands {
+        let new_level = command.output_level;
+
+        match command.feature_type {
+            VCFeatureType::Rotator => {
+                let new_level = limit(new_level * intensity);
+                levels.push(((command.feature_type, command.feature_index), new_level));
+                rotate_map.insert(command.feature_index, (new_level, true));
+            }
+            VCFeatureType::Linear => {
+                let new_level = limit(new_level * intensity);
+                levels.push(((command.feature_type, command.feature_index), new_level));
+                linear_map.insert(
+                    command.feature_index,
+                    (command.linear_position_speed, new_level),
+                );
+            }
+            scalar_feature_type => {
+                let Some(actuator_type) = scalar_actuator_type(scalar_feature_type) else {
+                    continue;
+                };
+                let new_level = limit(new_level * intensity);
+                #[cfg(debug_assertions)]
+                {
+                    let message_prefix = if new_level == 0.0 { "IDLE" } else { "SENDING" };
+                    info!(
+                        "{} FI[{}] AT[{}] SL[{}]",
+                        message_prefix, command.feature_index, actuator_type, new_level
+                    );
+                }
+                levels.push(((command.feature_type, command.feature_index), new_level));
+                scalar_map.insert(command.feature_index, (new_level, actuator_type));
+            }
+        }
     }
-    match dev
-        .scalar(&ScalarMap(HashMap::from([(
-            feature_index,
-            (new_level, actuator_type),
-        )])))
-        .await
-    {
-        Ok(()) => {}
-        Err(e) => {
+
+    if !scalar_map.is_empty() {
+        if let Err(e) = dev.scalar(&ScalarMap(scalar_map)).await {
             logerr!("Send scalar to device error: {}", e);
         }
     }
+
+    if !rotate_map.is_empty() {
+        let _ = dev.rotate(&RotateMap(rotate_map)).await;
+    }
+
+    if !linear_map.is_empty() {
+        let _ = dev.linear(&LinearMap(linear_map)).await;
+    }
+
+    CommandedLevels {
+        levels,
+        ceiling_hit,
+    }
 }
 
 /*
@@ -74,100 +191,9 @@ pub async fn scalar_parse_levels_send_toy_cmd(
  */
 pub async fn command_toy(
     dev: Arc<ButtplugClientDevice>,
-    feature_type: VCFeatureType,
-    float_level: f64,
-    feature_index: u32,
-    flip_float: bool,
-    feature_levels: LevelTweaks,
+    output_control: OutputControl,
+    max_level: f64,
+    command: FeatureCommand,
 ) {
-    match feature_type {
-        VCFeatureType::Vibrator => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Vibrate,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-        // We handle Rotator differently because it is not included in the Scalar feature set
-        VCFeatureType::Rotator => {
-            let new_level = clamp_and_flip(float_level, flip_float, feature_levels);
-            let _ = dev
-                .rotate(&RotateMap(HashMap::from([(
-                    feature_index,
-                    (new_level, true),
-                )])))
-                .await;
-        }
-        VCFeatureType::Constrict => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Constrict,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-        VCFeatureType::Oscillate => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Oscillate,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-        VCFeatureType::Position => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Position,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-        VCFeatureType::Inflate => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Inflate,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-        // We handle Linear differently because it is not included in the Scalar feature set
-        VCFeatureType::Linear => {
-            let new_level = clamp_and_flip(float_level, flip_float, feature_levels);
-            let _ = dev
-                .linear(&buttplug::client::LinearCommand::LinearMap(HashMap::from(
-                    [(
-                        feature_index,
-                        (feature_levels.linear_position_speed, new_level),
-                    )],
-                )))
-                .await;
-        }
-        VCFeatureType::ScalarRotator => {
-            scalar_parse_levels_send_toy_cmd(
-                &dev,
-                float_level,
-                feature_index,
-                ActuatorType::Rotate,
-                flip_float,
-                feature_levels,
-            )
-            .await;
-        }
-    }
+    command_toy_features(&dev, &output_control, max_level, [command]).await;
 }
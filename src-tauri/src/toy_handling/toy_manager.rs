@@ -4,6 +4,7 @@ use std::fs;
 use crate::error_signal_handler::{ErrorSource, VibeCheckError};
 use crate::frontend::ToFrontend;
 use crate::util::fs::build_path_dir;
+use crate::vcore::host::CoreHandle;
 use crate::{
     config::toy::VCToyConfig,
     frontend::frontend_types::FeVCToy,
@@ -11,7 +12,6 @@ use crate::{
     util::fs::{file_exists, get_config_dir},
 };
 use log::{debug, info, trace};
-use tauri::AppHandle;
 
 use super::errors::ToyHandlingError;
 
@@ -19,11 +19,11 @@ use super::errors::ToyHandlingError;
 pub struct ToyManager {
     pub configs: HashMap<String, VCToyConfig>,
     pub online_toys: HashMap<u32, VCToy>,
-    _app_handle: AppHandle,
+    _core_handle: CoreHandle,
 }
 
 impl ToyManager {
-    pub fn new(app_handle: AppHandle) -> Result<Self, VibeCheckError> {
+    pub fn new(core_handle: CoreHandle) -> Result<Self, VibeCheckError> {
         /*
          * Read all toy configs
          * Send update to frontend
@@ -38,7 +38,7 @@ impl ToyManager {
         let mut ot = Self {
             configs: HashMap::new(),
             online_toys: HashMap::new(),
-            _app_handle: app_handle,
+            _core_handle: core_handle,
         };
 
         match ot.populate_configs() {
@@ -54,7 +54,7 @@ impl ToyManager {
     }
 
     pub fn populate_configs(&mut self) -> Result<(), ToyHandlingError> {
-        let config_dir = match get_config_dir(&self._app_handle) {
+        let config_dir = match get_config_dir(&self._core_handle) {
             Ok(d) => d,
             Err(_) => return Err(ToyHandlingError::PopulateConfigFailure),
         };
@@ -77,12 +77,13 @@ impl ToyManager {
                 Err(_e) => continue,
             };
 
-            let config: VCToyConfig = match serde_json::from_str(&con) {
+            let mut config: VCToyConfig = match serde_json::from_str(&con) {
                 Ok(vc_toy_config) => vc_toy_config,
                 Err(_) => {
                     continue;
                 }
             };
+            config.sanitize();
 
             trace!(
                 "Loaded & parsed toy config [{}] successfully!",
@@ -135,6 +136,7 @@ impl ToyManager {
                 osc_data: config.osc_data,
                 bt_update_rate: config.bt_update_rate,
                 sub_id: 255,
+                safety: config.safety,
             });
         }
 
@@ -6,7 +6,6 @@ use core::fmt;
 use log::{debug, error as logerr, info, warn};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fs, sync::Arc, time::Instant};
-use tauri::AppHandle;
 use ts_rs::TS;
 
 use crate::{
@@ -20,6 +19,7 @@ use crate::{
     toy_handling::input_processor::penetration_systems::{
         sps::SPSProcessor, tps::TPSProcessor, PenetrationSystemType,
     },
+    toy_handling::modulation::Modulation,
     util::fs::{build_path_dir, build_path_file, file_exists, get_config_dir, ConfigFileType},
     vcore::errors::{
         self,
@@ -28,8 +28,10 @@ use crate::{
 };
 
 use crate::toy_handling::input_processor::penetration_systems::PenetrationSystem;
+use crate::toy_handling::mode_processor::core::ModeProcessorInputType;
+use crate::vcore::host::CoreHandle;
 
-use super::ToyPower;
+use super::{patterns::Pattern, ToyPower};
 
 #[derive(Clone, Debug)]
 pub struct VCToy {
@@ -45,7 +47,7 @@ pub struct VCToy {
     pub device_handle: Arc<ButtplugClientDevice>,
     pub config: Option<VCToyConfig>,
     pub sub_id: u8,
-    pub app_handle: AppHandle,
+    pub core_handle: CoreHandle,
 }
 
 impl VCToy {
@@ -67,7 +69,11 @@ impl VCToy {
                                 indexer
                             ),
                             processing_mode: ProcessingMode::Raw,
+                            int_mapping: IntMapping::default(),
+                            weight: default_parameter_weight(),
+                            pattern: String::new(),
                             processing_mode_values: ProcessingModeValues::default(),
+                            latest_level: None,
                         }],
                         indexer,
                         VCFeatureType::Linear,
@@ -191,6 +197,8 @@ impl VCToy {
             osc_data: false,
             bt_update_rate: 20,
             anatomy: VCToyAnatomy::default(),
+            avatar_profiles: HashMap::new(),
+            safety: None,
         });
         info!("Set toy config populate defaults");
         // Save toy on first time add
@@ -258,30 +266,10 @@ impl VCToy {
                 }
 
                 // Feature count is the same so its probably safe to assume the toy config is intact
-                self.parsed_toy_features = conf.features.clone();
-
-                // Allocate / Instantiate new Penetration system structure based on configuration data
-                for feature in &mut self.parsed_toy_features.features {
-                    match feature.penetration_system.pen_system_type {
-                        PenetrationSystemType::None => feature.penetration_system.pen_system = None,
-                        PenetrationSystemType::Sps => {
-                            feature.penetration_system.pen_system =
-                                Some(Box::<SPSProcessor>::default())
-                        }
-                        PenetrationSystemType::Tps => {
-                            feature.penetration_system.pen_system =
-                                Some(Box::<TPSProcessor>::default())
-                        }
-                    }
-
-                    feature.penetration_system.pen_system_processing_mode_values =
-                        ProcessingModeValues::new_from(
-                            &feature.penetration_system.pen_system_processing_mode,
-                        );
-                }
-
+                let features = conf.features.clone();
                 self.osc_data = conf.osc_data;
                 self.bt_update_rate = conf.bt_update_rate;
+                self.set_features(features);
                 info!("Populated toy with loaded config from file!");
             }
             // If config is not loaded populate the toy
@@ -291,10 +279,52 @@ impl VCToy {
         }
     }
 
+    fn set_features(&mut self, features: VCToyFeatures) {
+        self.parsed_toy_features = features;
+
+        // Allocate / Instantiate new Penetration system structure based on configuration data
+        for feature in &mut self.parsed_toy_features.features {
+            match feature.penetration_system.pen_system_type {
+                PenetrationSystemType::None => feature.penetration_system.pen_system = None,
+                PenetrationSystemType::Sps => {
+                    feature.penetration_system.pen_system = Some(Box::<SPSProcessor>::default())
+                }
+                PenetrationSystemType::Tps => {
+                    feature.penetration_system.pen_system = Some(Box::<TPSProcessor>::default())
+                }
+            }
+
+            feature.penetration_system.pen_system_processing_mode_values =
+                ProcessingModeValues::new_from(
+                    &feature.penetration_system.pen_system_processing_mode,
+                );
+        }
+    }
+
+    /*
+     * Switches the features to the avatar's profile (or the default profile)
+     * Returns false if the profile does not fit the toy
+     */
+    pub fn apply_avatar_profile(&mut self, avatar_id: Option<&str>) -> bool {
+        let Some(conf) = self.config.as_ref() else {
+            return false;
+        };
+        let features = conf.profile_features(avatar_id).clone();
+        if features.features.len() != self.parsed_toy_features.features.len() {
+            warn!(
+                "Avatar profile feature count differs for toy: {}",
+                self.toy_name
+            );
+            return false;
+        }
+        self.set_features(features);
+        true
+    }
+
     pub fn load_toy_config(&mut self) -> Result<(), VibeCheckToyConfigError> {
         // Generate config path
 
-        let config_dir = match get_config_dir(&self.app_handle) {
+        let config_dir = match get_config_dir(&self.core_handle) {
             Ok(d) => d,
             Err(_) => return Err(VibeCheckToyConfigError::ConfigDirFail),
         };
@@ -309,13 +339,14 @@ impl VCToy {
         } else {
             let con = fs::read_to_string(config_path).unwrap();
 
-            let config: VCToyConfig = match serde_json::from_str(&con) {
+            let mut config: VCToyConfig = match serde_json::from_str(&con) {
                 Ok(vc_toy_config) => vc_toy_config,
                 Err(_) => {
                     self.config = None;
                     return Err(errors::backend::VibeCheckToyConfigError::DeserializeError);
                 }
             };
+            config.sanitize();
             debug!("Loaded & parsed toy config successfully!");
             self.config = Some(config);
             Ok(())
@@ -324,7 +355,7 @@ impl VCToy {
 
     // Save Toy config by name
     pub fn save_toy_config(&self) -> Result<(), VibeCheckToyConfigError> {
-        let config_dir = match get_config_dir(&self.app_handle) {
+        let config_dir = match get_config_dir(&self.core_handle) {
             Ok(d) => d,
             Err(_) => return Err(VibeCheckToyConfigError::ConfigDirFail),
         };
@@ -366,6 +397,11 @@ impl VCToy {
         }
         false
     }
+
+    // Toy name as it appears in OSC addresses
+    pub fn osc_name(&self) -> String {
+        self.toy_name.replace(' ', "_").to_lowercase()
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -377,9 +413,68 @@ pub struct SmoothProcessingValues {
 pub struct RateProcessingValues {
     pub rate_saved_level: f64,
     pub rate_saved_osc_input: f64,
+    // Last time the level decayed
     pub rate_timestamp: Option<Instant>,
 }
 
+#[derive(Clone, Default, Debug)]
+pub struct FilterProcessingValues {
+    pub level: f64,
+    // Level change per millisecond (Damped only)
+    pub velocity: f64,
+    pub target: Option<f64>,
+    pub last_update: Option<Instant>,
+    // Target reached, nothing to send until new input
+    pub settled: bool,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum EnvelopeStage {
+    #[default]
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnvelopeProcessingValues {
+    pub stage: EnvelopeStage,
+    pub level: f64,
+    // Level and time the current stage started at
+    pub stage_start_level: f64,
+    pub stage_started: Instant,
+}
+
+impl Default for EnvelopeProcessingValues {
+    fn default() -> Self {
+        Self {
+            stage: EnvelopeStage::Idle,
+            level: 0.0,
+            stage_start_level: 0.0,
+            stage_started: Instant::now(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct GateProcessingValues {
+    pub open: bool,
+    // Where the latest input wants the gate, held back by the minimum on/off times
+    pub wanted_open: bool,
+    pub switched: Option<Instant>,
+    // Latest input level and the level sent while closed
+    pub input: f64,
+    pub closed_level: f64,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct PatternProcessingValues {
+    // Pattern being played, when it started and the level it is scaled to
+    pub playing: Option<(Arc<Pattern>, Instant, f64)>,
+}
+
 #[derive(Clone, Default, Debug)]
 pub enum ProcessingModeValues {
     #[default]
@@ -387,6 +482,11 @@ pub enum ProcessingModeValues {
     Smooth(SmoothProcessingValues),
     Rate(RateProcessingValues),
     Constant,
+    Pattern(PatternProcessingValues),
+    Ema(FilterProcessingValues),
+    Damped(FilterProcessingValues),
+    Envelope(EnvelopeProcessingValues),
+    Gate(GateProcessingValues),
 }
 
 impl ProcessingModeValues {
@@ -402,6 +502,11 @@ impl ProcessingModeValues {
                 rate_saved_osc_input: 0.0,
                 rate_timestamp: None,
             }),
+            ProcessingMode::Pattern => Self::Pattern(PatternProcessingValues::default()),
+            ProcessingMode::Ema => Self::Ema(FilterProcessingValues::default()),
+            ProcessingMode::Damped => Self::Damped(FilterProcessingValues::default()),
+            ProcessingMode::Envelope => Self::Envelope(EnvelopeProcessingValues::default()),
+            ProcessingMode::Gate => Self::Gate(GateProcessingValues::default()),
         }
     }
 }
@@ -412,6 +517,34 @@ pub enum ProcessingMode {
     Smooth,
     Rate,
     Constant,
+    // Plays the parameter's pattern while triggered
+    Pattern,
+    // Exponential moving average over time
+    Ema,
+    // Critically damped smoothing
+    Damped,
+    // Attack, decay, sustain and release on a trigger
+    Envelope,
+    // Thresholds with hysteresis and minimum on/off times
+    Gate,
+}
+
+impl ProcessingMode {
+    // Modes by their OSC API Int value
+    pub fn from_api_index(index: i64) -> Option<Self> {
+        match index {
+            0 => Some(Self::Raw),
+            1 => Some(Self::Smooth),
+            2 => Some(Self::Rate),
+            3 => Some(Self::Constant),
+            4 => Some(Self::Pattern),
+            5 => Some(Self::Ema),
+            6 => Some(Self::Damped),
+            7 => Some(Self::Envelope),
+            8 => Some(Self::Gate),
+            _ => None,
+        }
+    }
 }
 
 impl ToFrontend<FeProcessingMode> for ProcessingMode {
@@ -423,6 +556,55 @@ impl ToFrontend<FeProcessingMode> for ProcessingMode {
             Self::Smooth => FeProcessingMode::Smooth,
             Self::Rate => FeProcessingMode::Rate,
             Self::Constant => FeProcessingMode::Constant,
+            Self::Pattern => FeProcessingMode::Pattern,
+            Self::Ema => FeProcessingMode::Ema,
+            Self::Damped => FeProcessingMode::Damped,
+            Self::Envelope => FeProcessingMode::Envelope,
+            Self::Gate => FeProcessingMode::Gate,
+        }
+    }
+}
+
+/*
+ * How an Int parameter (VRChat Int / OSC Int or Long) is turned into mode processor input
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, TS, PartialEq)]
+#[ts(export)]
+pub enum IntMapping {
+    // Level is value / max
+    Normalize(i32),
+    // Level is looked up per value. Unlisted values are 0.0
+    Lookup(Vec<(i32, f64)>),
+    // On (true) when value >= threshold
+    Threshold(i32),
+}
+
+impl Default for IntMapping {
+    fn default() -> Self {
+        // VRChat Int parameters range from 0 to 255
+        Self::Normalize(255)
+    }
+}
+
+impl IntMapping {
+    pub fn map_int(&self, value: i64) -> ModeProcessorInputType {
+        match self {
+            Self::Normalize(max) => {
+                if *max <= 0 {
+                    return ModeProcessorInputType::Float(0.0);
+                }
+                let level = (value as f64 / f64::from(*max)).clamp(0.0, 1.0);
+                ModeProcessorInputType::Float((level * 100.0).round() / 100.0)
+            }
+            Self::Lookup(table) => ModeProcessorInputType::Float(
+                table
+                    .iter()
+                    .find(|(table_value, _)| i64::from(*table_value) == value)
+                    .map_or(0.0, |(_, level)| level.clamp(0.0, 1.0)),
+            ),
+            Self::Threshold(threshold) => {
+                ModeProcessorInputType::Boolean(value >= i64::from(*threshold))
+            }
         }
     }
 }
@@ -431,9 +613,25 @@ impl ToFrontend<FeProcessingMode> for ProcessingMode {
 pub struct ToyParameter {
     pub parameter: String,
     pub processing_mode: ProcessingMode,
+    // Mapping used when this parameter receives an Int
+    #[serde(default)]
+    pub int_mapping: IntMapping,
+    // Weight used by the WeightedAverage blend mode
+    #[serde(default = "default_parameter_weight")]
+    pub weight: f64,
+    // Pattern name played in the Pattern processing mode
+    #[serde(default)]
+    pub pattern: String,
     // Temporary values for calculations for Processing Modes
     #[serde(skip)]
     pub processing_mode_values: ProcessingModeValues,
+    // Latest mode processed level of this parameter (None until the parameter is received)
+    #[serde(skip)]
+    pub latest_level: Option<f64>,
+}
+
+fn default_parameter_weight() -> f64 {
+    1.0
 }
 
 impl ToyParameter {
@@ -442,6 +640,25 @@ impl ToyParameter {
     }
 }
 
+/*
+ * How the latest levels of a feature's OSC parameters are combined into one feature level
+ */
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, TS, PartialEq, Default)]
+#[ts(export)]
+pub enum ParameterBlendMode {
+    // Highest level wins
+    #[default]
+    Max,
+    // Levels are added and clamped to 1.0
+    Sum,
+    // Mean of all received levels
+    Average,
+    // Mean of all received levels using each parameter's weight
+    WeightedAverage,
+    // First parameter in list order with a non zero level wins
+    Priority,
+}
+
 impl ToFrontend<Vec<FeToyParameter>> for Vec<ToyParameter> {
     type OutputType = Vec<FeToyParameter>;
 
@@ -452,6 +669,9 @@ impl ToFrontend<Vec<FeToyParameter>> for Vec<ToyParameter> {
             out.push(FeToyParameter {
                 parameter: tp.parameter.clone(),
                 processing_mode: tp.processing_mode.to_frontend(),
+                int_mapping: Some(tp.int_mapping.clone()),
+                weight: Some(tp.weight),
+                pattern: Some(tp.pattern.clone()),
             });
         }
 
@@ -466,6 +686,7 @@ pub struct FeatureProcessor {
 
     pub rate_saved_level: f64,
     pub rate_saved_osc_input: f64,
+    // Last time the level decayed
     pub rate_timestamp: Option<Instant>,
 }
 */
@@ -479,6 +700,9 @@ pub struct VCToyFeature {
     pub feature_type: VCFeatureType,
     // Assigned OSC parameters with their respective processing modes
     pub osc_parameters: Vec<ToyParameter>,
+    // How the levels of multiple OSC parameters are combined
+    #[serde(default)]
+    pub parameter_blend: ParameterBlendMode,
     // The assigned penetration system to the feature
     pub penetration_system: PenetrationSystem,
     // Should toy input be flipped
@@ -501,6 +725,7 @@ impl VCToyFeature {
             feature_enabled: true,
             feature_type,
             osc_parameters,
+            parameter_blend: ParameterBlendMode::default(),
             penetration_system: PenetrationSystem::default(),
             feature_index,
             flip_input_float: false,
@@ -528,6 +753,47 @@ impl VCToyFeature {
         None
     }
 
+    // Combine the latest level of every assigned parameter using the feature's blend mode
+    pub fn blend_parameter_levels(&self) -> f64 {
+        let received = self
+            .osc_parameters
+            .iter()
+            .filter_map(|param| param.latest_level.map(|level| (level, param.weight)));
+
+        match self.parameter_blend {
+            ParameterBlendMode::Max => received.map(|(level, _)| level).fold(0.0, f64::max),
+            ParameterBlendMode::Sum => received
+                .map(|(level, _)| level)
+                .sum::<f64>()
+                .clamp(0.0, 1.0),
+            ParameterBlendMode::Average => {
+                let (total, count) = received.fold((0.0, 0), |(total, count), (level, _)| {
+                    (total + level, count + 1)
+                });
+                if count == 0 {
+                    0.0
+                } else {
+                    total / count as f64
+                }
+            }
+            ParameterBlendMode::WeightedAverage => {
+                let (total, total_weight) =
+                    received.fold((0.0, 0.0), |(total, total_weight), (level, weight)| {
+                        (total + level * weight, total_weight + weight)
+                    });
+                if total_weight <= 0.0 {
+                    0.0
+                } else {
+                    (total / total_weight).clamp(0.0, 1.0)
+                }
+            }
+            ParameterBlendMode::Priority => received
+                .map(|(level, _)| level)
+                .find(|level| *level > 0.0)
+                .unwrap_or(0.0),
+        }
+    }
+
     /*
     pub fn from_fe(&mut self, fe_feature: FeVCToyFeature) {
         self.feature_enabled = fe_feature.feature_enabled;
@@ -553,6 +819,7 @@ impl FromFrontend<FeVCToyFeature> for VCToyFeature {
         self.flip_input_float = frontend_type.flip_input_float;
         self.osc_parameters
             .from_frontend(frontend_type.osc_parameters);
+        self.parameter_blend = frontend_type.parameter_blend;
         self.penetration_system
             .from_frontend(frontend_type.penetration_system);
         self.feature_levels.from_fe(frontend_type.feature_levels);
@@ -574,9 +841,13 @@ impl FromFrontend<Vec<FeToyParameter>> for Vec<ToyParameter> {
             self.push(ToyParameter {
                 parameter: toy_param.parameter,
                 processing_mode: toy_param.processing_mode.to_backend(),
+                int_mapping: toy_param.int_mapping.unwrap_or_default(),
+                weight: toy_param.weight.unwrap_or_else(default_parameter_weight),
+                pattern: toy_param.pattern.unwrap_or_default(),
                 processing_mode_values: ProcessingModeValues::new_from(
                     &toy_param.processing_mode.to_backend(),
                 ),
+                latest_level: None,
             });
         }
 
@@ -596,6 +867,8 @@ impl ToFrontend<HashMap<String, FeToyParameter>> for HashMap<String, ToyParamete
                 FeToyParameter {
                     parameter: p.clone(),
                     processing_mode: tp.processing_mode.to_frontend(),
+                    int_mapping: Some(tp.int_mapping.clone()),
+                    weight: Some(tp.weight),
                 },
             );
         }
@@ -619,6 +892,37 @@ pub enum VCFeatureType {
 }
 impl Eq for VCFeatureType {}
 
+impl VCFeatureType {
+    // Feature types by their OSC API name (Not case-sensitive)
+    pub fn from_api_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "vibrator" => Some(Self::Vibrator),
+            "rotator" => Some(Self::Rotator),
+            "linear" => Some(Self::Linear),
+            "oscillate" => Some(Self::Oscillate),
+            "constrict" => Some(Self::Constrict),
+            "inflate" => Some(Self::Inflate),
+            "position" => Some(Self::Position),
+            "scalarrotator" => Some(Self::ScalarRotator),
+            _ => None,
+        }
+    }
+
+    // Name used in OSC API and level output addresses
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            Self::Vibrator => "vibrator",
+            Self::Rotator => "rotator",
+            Self::Linear => "linear",
+            Self::Oscillate => "oscillate",
+            Self::Constrict => "constrict",
+            Self::Inflate => "inflate",
+            Self::Position => "position",
+            Self::ScalarRotator => "scalarrotator",
+        }
+    }
+}
+
 impl PartialEq<FeVCFeatureType> for VCFeatureType {
     fn eq(&self, other: &FeVCFeatureType) -> bool {
         *self as u32 == *other as u32
@@ -663,15 +967,93 @@ pub struct ToyConfig {
     If no config put toy to Auto params
 */
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Copy, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct LevelTweaks {
     pub minimum_level: f64,
     pub maximum_level: f64,
     pub idle_level: f64,
     pub smooth_rate: f64,
     pub linear_position_speed: u32,
-    pub rate_tune: f64,
+    // Rate mode level lost per second
+    #[serde(default = "default_rate_decay_per_second")]
+    pub rate_decay_per_second: f64,
     pub constant_level: f64,
+    // Half-life of Ema mode and smoothing time of Damped mode
+    #[serde(default = "default_smooth_half_life_ms")]
+    pub smooth_half_life_ms: f64,
+    // Envelope mode stage lengths and the level held while triggered
+    #[serde(default = "default_envelope_attack_ms")]
+    pub envelope_attack_ms: f64,
+    #[serde(default = "default_envelope_decay_ms")]
+    pub envelope_decay_ms: f64,
+    #[serde(default = "default_envelope_sustain_level")]
+    pub envelope_sustain_level: f64,
+    #[serde(default = "default_envelope_release_ms")]
+    pub envelope_release_ms: f64,
+    // Gate mode opens at the upper threshold and closes below the lower threshold
+    #[serde(default = "default_gate_lower_threshold")]
+    pub gate_lower_threshold: f64,
+    #[serde(default = "default_gate_upper_threshold")]
+    pub gate_upper_threshold: f64,
+    // Open gate sends the input when true or the fixed level when false
+    #[serde(default = "default_gate_passthrough")]
+    pub gate_passthrough: bool,
+    #[serde(default = "default_gate_fixed_level")]
+    pub gate_fixed_level: f64,
+    #[serde(default = "default_gate_min_hold_ms")]
+    pub gate_min_on_ms: f64,
+    #[serde(default = "default_gate_min_hold_ms")]
+    pub gate_min_off_ms: f64,
+    // Shapes the output level before it is clamped to the minimum / maximum level
+    #[serde(default)]
+    pub response_curve: ResponseCurve,
+    // Noise or LFO added to the processed level on every emitter tick
+    #[serde(default)]
+    pub modulation: Modulation,
+}
+
+fn default_smooth_half_life_ms() -> f64 {
+    150.
+}
+
+fn default_rate_decay_per_second() -> f64 {
+    2.5
+}
+
+fn default_envelope_attack_ms() -> f64 {
+    50.
+}
+
+fn default_envelope_decay_ms() -> f64 {
+    150.
+}
+
+fn default_envelope_sustain_level() -> f64 {
+    0.6
+}
+
+fn default_envelope_release_ms() -> f64 {
+    300.
+}
+
+fn default_gate_lower_threshold() -> f64 {
+    0.2
+}
+
+fn default_gate_upper_threshold() -> f64 {
+    0.3
+}
+
+fn default_gate_passthrough() -> bool {
+    true
+}
+
+fn default_gate_fixed_level() -> f64 {
+    0.5
+}
+
+fn default_gate_min_hold_ms() -> f64 {
+    100.
 }
 
 impl Default for LevelTweaks {
@@ -682,8 +1064,21 @@ impl Default for LevelTweaks {
             idle_level: 0.,
             smooth_rate: 2.,
             linear_position_speed: 100,
-            rate_tune: 0.4,
+            rate_decay_per_second: default_rate_decay_per_second(),
             constant_level: 0.5,
+            smooth_half_life_ms: default_smooth_half_life_ms(),
+            envelope_attack_ms: default_envelope_attack_ms(),
+            envelope_decay_ms: default_envelope_decay_ms(),
+            envelope_sustain_level: default_envelope_sustain_level(),
+            envelope_release_ms: default_envelope_release_ms(),
+            gate_lower_threshold: default_gate_lower_threshold(),
+            gate_upper_threshold: default_gate_upper_threshold(),
+            gate_passthrough: default_gate_passthrough(),
+            gate_fixed_level: default_gate_fixed_level(),
+            gate_min_on_ms: default_gate_min_hold_ms(),
+            gate_min_off_ms: default_gate_min_hold_ms(),
+            response_curve: ResponseCurve::default(),
+            modulation: Modulation::default(),
         }
     }
 }
@@ -695,8 +1090,21 @@ impl LevelTweaks {
         self.minimum_level = fe_lt.minimum_level;
         self.smooth_rate = fe_lt.smooth_rate;
         self.linear_position_speed = fe_lt.linear_position_speed;
-        self.rate_tune = fe_lt.rate_tune;
-        self.constant_level = fe_lt.constant_level
+        self.rate_decay_per_second = fe_lt.rate_decay_per_second;
+        self.constant_level = fe_lt.constant_level;
+        self.smooth_half_life_ms = fe_lt.smooth_half_life_ms;
+        self.envelope_attack_ms = fe_lt.envelope_attack_ms;
+        self.envelope_decay_ms = fe_lt.envelope_decay_ms;
+        self.envelope_sustain_level = fe_lt.envelope_sustain_level;
+        self.envelope_release_ms = fe_lt.envelope_release_ms;
+        self.gate_lower_threshold = fe_lt.gate_lower_threshold;
+        self.gate_upper_threshold = fe_lt.gate_upper_threshold;
+        self.gate_passthrough = fe_lt.gate_passthrough;
+        self.gate_fixed_level = fe_lt.gate_fixed_level;
+        self.gate_min_on_ms = fe_lt.gate_min_on_ms;
+        self.gate_min_off_ms = fe_lt.gate_min_off_ms;
+        self.response_curve = fe_lt.response_curve.sanitized();
+        self.modulation = fe_lt.modulation;
     }
 
     pub fn to_fe(&self) -> FeLevelTweaks {
@@ -706,8 +1114,106 @@ impl LevelTweaks {
             idle_level: self.idle_level,
             smooth_rate: self.smooth_rate,
             linear_position_speed: self.linear_position_speed,
-            rate_tune: self.rate_tune,
+            rate_decay_per_second: self.rate_decay_per_second,
             constant_level: self.constant_level,
+            smooth_half_life_ms: self.smooth_half_life_ms,
+            envelope_attack_ms: self.envelope_attack_ms,
+            envelope_decay_ms: self.envelope_decay_ms,
+            envelope_sustain_level: self.envelope_sustain_level,
+            envelope_release_ms: self.envelope_release_ms,
+            gate_lower_threshold: self.gate_lower_threshold,
+            gate_upper_threshold: self.gate_upper_threshold,
+            gate_passthrough: self.gate_passthrough,
+            gate_fixed_level: self.gate_fixed_level,
+            gate_min_on_ms: self.gate_min_on_ms,
+            gate_min_off_ms: self.gate_min_off_ms,
+            response_curve: self.response_curve.clone(),
+            modulation: self.modulation.clone(),
+        }
+    }
+}
+
+/*
+ * Maps a feature level (0.0 - 1.0) to the level sent to the toy
+ * Applied before the level is clamped to the minimum / maximum level
+ */
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Default)]
+#[ts(export)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    // Level raised to this exponent. Above 1.0 is softer at low levels, below 1.0 is stronger
+    Gamma(f64),
+    // Eases in and out. Strength 0.0 is linear, 1.0 is a full smoothstep
+    SCurve(f64),
+    // Levels at or below this are off, the rest is stretched over the full range
+    Deadzone(f64),
+    // User curve of (input, output) points with straight lines in between
+    Points(Vec<(f64, f64)>),
+}
+
+impl ResponseCurve {
+    /*
+     * Keeps curve parameters in range and sorts curve points by input
+     */
+    pub fn sanitized(self) -> Self {
+        match self {
+            Self::Gamma(exponent) if exponent.is_finite() => Self::Gamma(exponent.clamp(0.1, 10.0)),
+            Self::SCurve(strength) if strength.is_finite() => {
+                Self::SCurve(strength.clamp(0.0, 1.0))
+            }
+            Self::Deadzone(deadzone) if deadzone.is_finite() => {
+                Self::Deadzone(deadzone.clamp(0.0, 0.99))
+            }
+            Self::Points(points) => {
+                let mut points = points
+                    .into_iter()
+                    .filter(|(input, output)| input.is_finite() && output.is_finite())
+                    .map(|(input, output)| (input.clamp(0.0, 1.0), output.clamp(0.0, 1.0)))
+                    .collect::<Vec<(f64, f64)>>();
+                points.sort_by(|a, b| a.0.total_cmp(&b.0));
+                if points.is_empty() {
+                    Self::Linear
+                } else {
+                    Self::Points(points)
+                }
+            }
+            // Linear or a parameter that is not a number
+            _ => Self::Linear,
+        }
+    }
+
+    pub fn apply(&self, level: f64) -> f64 {
+        let level = level.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => level,
+            Self::Gamma(exponent) => level.powf(*exponent),
+            Self::SCurve(strength) => {
+                let smooth = level * level * (3.0 - 2.0 * level);
+                level + (smooth - level) * strength
+            }
+            Self::Deadzone(deadzone) => {
+                if level <= *deadzone {
+                    0.0
+                } else {
+                    (level - deadzone) / (1.0 - deadzone)
+                }
+            }
+            Self::Points(points) => {
+                let Some(upper) = points.iter().position(|(input, _)| *input >= level) else {
+                    // Past the last point keeps its output
+                    return points.last().map(|(_, output)| *output).unwrap_or(level);
+                };
+                if upper == 0 {
+                    return points[0].1;
+                }
+                let (x0, y0) = points[upper - 1];
+                let (x1, y1) = points[upper];
+                if x1 - x0 <= f64::EPSILON {
+                    return y1;
+                }
+                y0 + (level - x0) / (x1 - x0) * (y1 - y0)
+            }
         }
     }
 }
@@ -916,6 +1422,7 @@ impl ToFrontend<Vec<FeVCToyFeature>> for Vec<VCToyFeature> {
                 feature_enabled: f.feature_enabled,
                 feature_type: f.feature_type.to_fe(),
                 osc_parameters: f.osc_parameters.to_frontend(),
+                parameter_blend: f.parameter_blend,
                 penetration_system: f.penetration_system.to_frontend(),
                 feature_index: f.feature_index,
                 flip_input_float: f.flip_input_float,
@@ -960,3 +1467,151 @@ impl FromFrontend<FeVCToyFeature> for VCToyFeatures {
         success
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(input: ModeProcessorInputType) -> f64 {
+        match input {
+            ModeProcessorInputType::Float(level) => level,
+            _ => panic!("Expected a Float input"),
+        }
+    }
+
+    fn parameter(latest_level: Option<f64>, weight: f64) -> ToyParameter {
+        ToyParameter {
+            parameter: "/avatar/parameters/Test".to_string(),
+            processing_mode: ProcessingMode::Raw,
+            int_mapping: IntMapping::default(),
+            weight,
+            pattern: String::new(),
+            processing_mode_values: ProcessingModeValues::default(),
+            latest_level,
+        }
+    }
+
+    fn blended(feature: &mut VCToyFeature, blend: ParameterBlendMode) -> f64 {
+        feature.parameter_blend = blend;
+        feature.blend_parameter_levels()
+    }
+
+    #[test]
+    fn int_mapping_normalize() {
+        assert_eq!(float(IntMapping::Normalize(255).map_int(255)), 1.0);
+        assert_eq!(float(IntMapping::Normalize(255).map_int(300)), 1.0);
+        assert_eq!(float(IntMapping::Normalize(4).map_int(1)), 0.25);
+        assert_eq!(float(IntMapping::Normalize(0).map_int(10)), 0.0);
+    }
+
+    #[test]
+    fn int_mapping_lookup() {
+        let mapping = IntMapping::Lookup(vec![(1, 0.5), (2, 2.0)]);
+        assert_eq!(float(mapping.map_int(1)), 0.5);
+        assert_eq!(float(mapping.map_int(2)), 1.0);
+        assert_eq!(float(mapping.map_int(3)), 0.0);
+    }
+
+    #[test]
+    fn int_mapping_threshold() {
+        let mapping = IntMapping::Threshold(3);
+        assert!(matches!(
+            mapping.map_int(2),
+            ModeProcessorInputType::Boolean(false)
+        ));
+        assert!(matches!(
+            mapping.map_int(3),
+            ModeProcessorInputType::Boolean(true)
+        ));
+    }
+
+    #[test]
+    fn blend_modes() {
+        let mut feature = VCToyFeature::new(
+            vec![
+                parameter(Some(0.25), 1.0),
+                parameter(Some(0.75), 3.0),
+                // Not received yet so it is left out of every blend
+                parameter(None, 1.0),
+            ],
+            0,
+            VCFeatureType::Vibrator,
+        );
+
+        assert_eq!(blended(&mut feature, ParameterBlendMode::Max), 0.75);
+        assert_eq!(blended(&mut feature, ParameterBlendMode::Sum), 1.0);
+        assert_eq!(blended(&mut feature, ParameterBlendMode::Average), 0.5);
+        assert_eq!(
+            blended(&mut feature, ParameterBlendMode::WeightedAverage),
+            0.625
+        );
+        assert_eq!(blended(&mut feature, ParameterBlendMode::Priority), 0.25);
+    }
+
+    #[test]
+    fn blend_priority_skips_idle_parameters() {
+        let mut feature = VCToyFeature::new(
+            vec![parameter(Some(0.0), 1.0), parameter(Some(0.5), 1.0)],
+            0,
+            VCFeatureType::Vibrator,
+        );
+        assert_eq!(blended(&mut feature, ParameterBlendMode::Priority), 0.5);
+    }
+
+    #[test]
+    fn blend_without_received_levels_is_idle() {
+        let mut feature = VCToyFeature::new(vec![parameter(None, 1.0)], 0, VCFeatureType::Vibrator);
+        for blend in [
+            ParameterBlendMode::Max,
+            ParameterBlendMode::Sum,
+            ParameterBlendMode::Average,
+            ParameterBlendMode::WeightedAverage,
+            ParameterBlendMode::Priority,
+        ] {
+            assert_eq!(blended(&mut feature, blend), 0.0);
+        }
+    }
+
+    #[test]
+    fn response_curves() {
+        assert_eq!(ResponseCurve::Linear.apply(0.4), 0.4);
+        assert_eq!(ResponseCurve::Linear.apply(1.5), 1.0);
+        assert_eq!(ResponseCurve::Gamma(2.0).apply(0.5), 0.25);
+        assert_eq!(ResponseCurve::SCurve(0.0).apply(0.25), 0.25);
+        assert_eq!(ResponseCurve::SCurve(1.0).apply(0.25), 0.15625);
+        assert_eq!(ResponseCurve::Deadzone(0.5).apply(0.25), 0.0);
+        assert_eq!(ResponseCurve::Deadzone(0.5).apply(0.75), 0.5);
+    }
+
+    #[test]
+    fn response_curve_points() {
+        let curve = ResponseCurve::Points(vec![(0.0, 0.0), (0.5, 1.0), (1.0, 1.0)]);
+        assert_eq!(curve.apply(0.25), 0.5);
+        assert_eq!(curve.apply(0.75), 1.0);
+
+        // Outside the points keeps the nearest point's output
+        let single = ResponseCurve::Points(vec![(0.5, 0.25)]);
+        assert_eq!(single.apply(0.0), 0.25);
+        assert_eq!(single.apply(1.0), 0.25);
+    }
+
+    #[test]
+    fn response_curve_sanitize() {
+        assert_eq!(
+            ResponseCurve::Gamma(100.0).sanitized(),
+            ResponseCurve::Gamma(10.0)
+        );
+        assert_eq!(
+            ResponseCurve::SCurve(f64::NAN).sanitized(),
+            ResponseCurve::Linear
+        );
+        assert_eq!(
+            ResponseCurve::Points(vec![(1.0, 1.0), (0.0, 0.5)]).sanitized(),
+            ResponseCurve::Points(vec![(0.0, 0.5), (1.0, 1.0)])
+        );
+        assert_eq!(
+            ResponseCurve::Points(vec![]).sanitized(),
+            ResponseCurve::Linear
+        );
+    }
+}
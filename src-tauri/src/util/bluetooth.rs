@@ -1,8 +1,9 @@
 use btleplug::api::{Central, Manager as _};
 use btleplug::platform::Manager;
 use buttplug::client::ButtplugClient;
-use buttplug::core::connector::ButtplugInProcessClientConnectorBuilder;
-//new_json_ws_client_connector};
+use buttplug::core::connector::{
+    new_json_ws_client_connector, ButtplugInProcessClientConnectorBuilder,
+};
 use buttplug::server::device::hardware::communication::btleplug::BtlePlugCommunicationManagerBuilder;
 use buttplug::server::device::hardware::communication::lovense_dongle::{
     LovenseHIDDongleCommunicationManagerBuilder, LovenseSerialDongleCommunicationManagerBuilder,
@@ -13,6 +14,10 @@ use buttplug::util::device_configuration::load_protocol_configs;
 use log::{error as logerr, info, trace, warn};
 
 use crate::util::errors::UtilError;
+use crate::util::virtual_devices::{
+    VirtualCommandLog, VirtualDeviceConfig, VirtualDeviceConnector,
+};
+use crate::vcore::config::app::ButtplugConnectorConfig;
 
 #[allow(unused)]
 pub async fn detect_btle_adapter() -> bool {
@@ -34,6 +39,65 @@ pub async fn detect_btle_adapter() -> bool {
     !adapters.is_empty() // TODO is this always true?
 }
 
+/*
+ * Creates a ButtplugClient connected with the configured connector
+ */
+pub async fn vc_toy_client_init(
+    client_name: &str,
+    allow_raw_messages: bool,
+    connector: &ButtplugConnectorConfig,
+    virtual_command_log: &VirtualCommandLog,
+) -> Result<ButtplugClient, UtilError> {
+    match connector {
+        ButtplugConnectorConfig::InProcess => {
+            vc_toy_client_server_init(client_name, allow_raw_messages).await
+        }
+        ButtplugConnectorConfig::Websocket(address) => {
+            vc_toy_client_ws_init(client_name, address).await
+        }
+        ButtplugConnectorConfig::Virtual(devices) => {
+            vc_toy_client_virtual_init(client_name, devices.clone(), virtual_command_log).await
+        }
+    }
+}
+
+/*
+ * Connects to fake devices that record the commands they receive
+ */
+pub async fn vc_toy_client_virtual_init(
+    client_name: &str,
+    devices: Vec<VirtualDeviceConfig>,
+    virtual_command_log: &VirtualCommandLog,
+) -> Result<ButtplugClient, UtilError> {
+    let connector = VirtualDeviceConnector::new(devices, virtual_command_log.clone());
+
+    let client = ButtplugClient::new(client_name);
+    if let Err(e) = client.connect(connector).await {
+        logerr!("Failed to connect to virtual devices: {}", e);
+        return Err(UtilError::BPIOInit);
+    }
+    Ok(client)
+}
+
+/*
+ * Connects to an external Buttplug server (Intiface Central / Intiface Engine)
+ * The external server owns the device communication so raw message support is up to it.
+ */
+pub async fn vc_toy_client_ws_init(
+    client_name: &str,
+    address: &str,
+) -> Result<ButtplugClient, UtilError> {
+    let connector = new_json_ws_client_connector(address);
+
+    let client = ButtplugClient::new(client_name);
+    if let Err(e) = client.connect(connector).await {
+        warn!("Failed to connect to Buttplug server at {}: {}", address, e);
+        return Err(UtilError::BPIOConnect);
+    }
+    info!("Connected to Buttplug server at {}", address);
+    Ok(client)
+}
+
 pub async fn vc_toy_client_server_init(
     client_name: &str,
     allow_raw_messages: bool,
@@ -67,12 +131,6 @@ pub async fn vc_toy_client_server_init(
         Err(_) => return Err(UtilError::BPIOInit),
     };
 
-    /*
-     * Possibly add support to mutate the VibeCheck internal state to use websocket connector for Intiface Central / other websocket server implementations.
-     * Adding support for this would involve shutting down all handlers and completely recreating the internal VibeCheck state with a new ButtplugClient
-     * Making VibeCheckState initialization modular would probably be a good idea.
-     */
-
     let connector = ButtplugInProcessClientConnectorBuilder::default()
         .server(server)
         .finish();
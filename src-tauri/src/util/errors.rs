@@ -1,6 +1,7 @@
 #[derive(Debug)]
 pub enum UtilError {
     BPIOInit,
+    BPIOConnect,
     HomeDirFS,
     ConfigDirFS,
 }
@@ -1,8 +1,8 @@
 use directories::BaseDirs;
 use std::{ffi::OsStr, path::Path};
-use tauri::{AppHandle, Manager};
 
 use crate::util::errors::UtilError;
+use crate::vcore::host::CoreHandle;
 
 pub enum ConfigFileType {
     Toy,
@@ -54,16 +54,8 @@ pub fn get_user_home_dir() -> Result<String, UtilError> {
     Ok(bd.to_string())
 }
 
-pub fn get_config_dir(app_handle: &AppHandle) -> Result<String, UtilError> {
-    let pb = match app_handle.path().app_config_dir() {
-        Ok(path) => path,
-        Err(_) => return Err(UtilError::ConfigDirFS),
-    };
-
-    match pb.to_str() {
-        Some(config_dir) => Ok(config_dir.to_string()),
-        None => Err(UtilError::ConfigDirFS),
-    }
+pub fn get_config_dir(core_handle: &CoreHandle) -> Result<String, UtilError> {
+    core_handle.config_dir()
 }
 
 pub fn build_path_dir(path: &[&str]) -> String {
@@ -2,3 +2,4 @@ pub mod bluetooth;
 pub mod errors;
 pub mod fs;
 pub mod net;
+pub mod virtual_devices;
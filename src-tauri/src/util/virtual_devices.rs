@@ -0,0 +1,381 @@
+/*
+ * Virtual devices
+ * A stand-in Buttplug server that exposes fake devices with configurable feature sets.
+ * Every command the fake devices receive is recorded so the whole toy pipeline can be exercised without hardware.
+ * Naming a virtual device after a real toy makes it load that toy's config.
+ */
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+
+use buttplug::core::connector::{
+    ButtplugConnector, ButtplugConnectorError, ButtplugConnectorResultFuture,
+};
+use buttplug::core::message::{ButtplugClientMessageV3, ButtplugServerMessageV3};
+use futures::future::BoxFuture;
+use log::{info, trace, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::mpsc::Sender;
+use ts_rs::TS;
+
+// Oldest recorded commands are dropped past this
+const MAX_RECORDED_COMMANDS: usize = 10000;
+
+// Buttplug v3 spec error codes
+const ERROR_CODE_MESSAGE: u32 = 3;
+const ERROR_CODE_DEVICE: u32 = 4;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub struct VirtualDeviceConfig {
+    pub name: String,
+    #[serde(default)]
+    pub vibrators: u32,
+    #[serde(default)]
+    pub rotators: u32,
+    #[serde(default)]
+    pub scalar_rotators: u32,
+    #[serde(default)]
+    pub linears: u32,
+    #[serde(default)]
+    pub constrict: u32,
+    #[serde(default)]
+    pub oscillate: u32,
+    #[serde(default)]
+    pub inflate: u32,
+    #[serde(default)]
+    pub position: u32,
+    // Reports a full battery when set
+    #[serde(default)]
+    pub battery: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, TS)]
+#[ts(export)]
+pub enum VirtualCommand {
+    Scalar {
+        feature_index: u32,
+        level: f64,
+        actuator_type: String,
+    },
+    Rotate {
+        feature_index: u32,
+        speed: f64,
+        clockwise: bool,
+    },
+    Linear {
+        feature_index: u32,
+        duration: u32,
+        position: f64,
+    },
+    Stop,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export)]
+pub struct VirtualCommandRecord {
+    pub device_index: u32,
+    pub device_name: String,
+    // Milliseconds since the virtual server started
+    pub elapsed_ms: u64,
+    pub command: VirtualCommand,
+}
+
+/*
+ * Commands received by virtual devices
+ * Shared with VibeCheckState so they can be inspected while running
+ */
+#[derive(Clone, Default)]
+pub struct VirtualCommandLog(Arc<Mutex<VecDeque<VirtualCommandRecord>>>);
+
+impl VirtualCommandLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, record: VirtualCommandRecord) {
+        trace!("Virtual device command: {:?}", record);
+        let mut log = self.0.lock();
+        if log.len() >= MAX_RECORDED_COMMANDS {
+            log.pop_front();
+        }
+        log.push_back(record);
+    }
+
+    // Takes every command recorded so far
+    pub fn drain(&self) -> Vec<VirtualCommandRecord> {
+        self.0.lock().drain(..).collect()
+    }
+}
+
+impl VirtualDeviceConfig {
+    // v3 spec DeviceMessages for this device
+    fn device_messages(&self) -> Value {
+        let mut scalars = Vec::new();
+        for (count, actuator_type) in [
+            (self.vibrators, "Vibrate"),
+            (self.scalar_rotators, "Rotate"),
+            (self.constrict, "Constrict"),
+            (self.oscillate, "Oscillate"),
+            (self.inflate, "Inflate"),
+            (self.position, "Position"),
+        ] {
+            for _ in 0..count {
+                scalars.push(json!({
+                    "StepCount": 20,
+                    "FeatureDescriptor": "",
+                    "ActuatorType": actuator_type,
+                }));
+            }
+        }
+
+        let mut device_messages = json!({ "StopDeviceCmd": {} });
+        if !scalars.is_empty() {
+            device_messages["ScalarCmd"] = Value::Array(scalars);
+        }
+        if self.rotators > 0 {
+            device_messages["RotateCmd"] = Value::Array(
+                (0..self.rotators)
+                    .map(|_| json!({ "StepCount": 20, "FeatureDescriptor": "" }))
+                    .collect(),
+            );
+        }
+        if self.linears > 0 {
+            device_messages["LinearCmd"] = Value::Array(
+                (0..self.linears)
+                    .map(|_| json!({ "StepCount": 100, "FeatureDescriptor": "" }))
+                    .collect(),
+            );
+        }
+        if self.battery {
+            device_messages["SensorReadCmd"] = json!([{
+                "FeatureDescriptor": "Battery Level",
+                "SensorType": "Battery",
+                "SensorRange": [[0, 100]],
+            }]);
+        }
+        device_messages
+    }
+
+    fn device_info(&self, device_index: u32) -> Value {
+        json!({
+            "DeviceIndex": device_index,
+            "DeviceName": self.name,
+            "DeviceMessages": self.device_messages(),
+        })
+    }
+}
+
+/*
+ * Answers the Buttplug client in place of a real server
+ * Device indexes are the positions in the configured device list.
+ */
+pub struct VirtualDeviceConnector {
+    devices: Vec<VirtualDeviceConfig>,
+    command_log: VirtualCommandLog,
+    started: Instant,
+    server_sender: Arc<Mutex<Option<Sender<ButtplugServerMessageV3>>>>,
+}
+
+impl VirtualDeviceConnector {
+    pub fn new(devices: Vec<VirtualDeviceConfig>, command_log: VirtualCommandLog) -> Self {
+        Self {
+            devices,
+            command_log,
+            started: Instant::now(),
+            server_sender: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn record(&self, device_index: u32, command: VirtualCommand) {
+        self.command_log.record(VirtualCommandRecord {
+            device_index,
+            device_name: self.devices[device_index as usize].name.clone(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            command,
+        });
+    }
+
+    fn record_features(
+        &self,
+        device_index: u32,
+        features: &Value,
+        command: fn(&Value) -> VirtualCommand,
+    ) {
+        if let Some(features) = features.as_array() {
+            for feature in features {
+                self.record(device_index, command(feature));
+            }
+        }
+    }
+
+    /*
+     * Handles one client message
+     * Returns the replies in the order the client should receive them
+     */
+    fn handle_message(&self, msg_name: &str, body: &Value) -> Vec<Value> {
+        let id = body["Id"].as_u64().unwrap_or(0);
+
+        // Device commands need a device that exists
+        let device_index = body["DeviceIndex"].as_u64().map(|i| i as u32);
+        if let Some(device_index) = device_index {
+            if device_index as usize >= self.devices.len() {
+                return vec![error_message(
+                    id,
+                    ERROR_CODE_DEVICE,
+                    format!("No virtual device at index {}", device_index),
+                )];
+            }
+        }
+
+        match (msg_name, device_index) {
+            ("RequestServerInfo", _) => vec![json!({ "ServerInfo": {
+                "Id": id,
+                "ServerName": "VibeCheck Virtual Devices",
+                "MessageVersion": 3,
+                "MaxPingTime": 0,
+            }})],
+            ("RequestDeviceList", _) => vec![json!({ "DeviceList": {
+                "Id": id,
+                "Devices": self
+                    .devices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, device)| device.device_info(i as u32))
+                    .collect::<Vec<Value>>(),
+            }})],
+            // Virtual devices are always connected so scanning finishes right away
+            ("StartScanning", _) => {
+                vec![ok_message(id), json!({ "ScanningFinished": { "Id": 0 } })]
+            }
+            ("StopScanning", _) | ("Ping", _) => vec![ok_message(id)],
+            ("StopAllDevices", _) => {
+                for device_index in 0..self.devices.len() {
+                    self.record(device_index as u32, VirtualCommand::Stop);
+                }
+                vec![ok_message(id)]
+            }
+            ("StopDeviceCmd", Some(device_index)) => {
+                self.record(device_index, VirtualCommand::Stop);
+                vec![ok_message(id)]
+            }
+            ("ScalarCmd", Some(device_index)) => {
+                self.record_features(device_index, &body["Scalars"], |scalar| {
+                    VirtualCommand::Scalar {
+                        feature_index: scalar["Index"].as_u64().unwrap_or(0) as u32,
+                        level: scalar["Scalar"].as_f64().unwrap_or(0.0),
+                        actuator_type: scalar["ActuatorType"].as_str().unwrap_or("").to_string(),
+                    }
+                });
+                vec![ok_message(id)]
+            }
+            ("RotateCmd", Some(device_index)) => {
+                self.record_features(device_index, &body["Rotations"], |rotation| {
+                    VirtualCommand::Rotate {
+                        feature_index: rotation["Index"].as_u64().unwrap_or(0) as u32,
+                        speed: rotation["Speed"].as_f64().unwrap_or(0.0),
+                        clockwise: rotation["Clockwise"].as_bool().unwrap_or(true),
+                    }
+                });
+                vec![ok_message(id)]
+            }
+            ("LinearCmd", Some(device_index)) => {
+                self.record_features(device_index, &body["Vectors"], |vector| {
+                    VirtualCommand::Linear {
+                        feature_index: vector["Index"].as_u64().unwrap_or(0) as u32,
+                        duration: vector["Duration"].as_u64().unwrap_or(0) as u32,
+                        position: vector["Position"].as_f64().unwrap_or(0.0),
+                    }
+                });
+                vec![ok_message(id)]
+            }
+            ("SensorReadCmd", Some(device_index))
+                if self.devices[device_index as usize].battery =>
+            {
+                vec![json!({ "SensorReading": {
+                    "Id": id,
+                    "DeviceIndex": device_index,
+                    "SensorIndex": body["SensorIndex"],
+                    "SensorType": "Battery",
+                    "Data": [100],
+                }})]
+            }
+            _ => vec![error_message(
+                id,
+                ERROR_CODE_MESSAGE,
+                format!("{} is not supported by virtual devices", msg_name),
+            )],
+        }
+    }
+}
+
+fn ok_message(id: u64) -> Value {
+    json!({ "Ok": { "Id": id } })
+}
+
+fn error_message(id: u64, error_code: u32, error_message: String) -> Value {
+    warn!("Virtual device server error: {}", error_message);
+    json!({ "Error": {
+        "Id": id,
+        "ErrorCode": error_code,
+        "ErrorMessage": error_message,
+    }})
+}
+
+impl ButtplugConnector<ButtplugClientMessageV3, ButtplugServerMessageV3>
+    for VirtualDeviceConnector
+{
+    fn connect(
+        &mut self,
+        message_sender: Sender<ButtplugServerMessageV3>,
+    ) -> BoxFuture<'static, Result<(), ButtplugConnectorError>> {
+        info!(
+            "Virtual device server started with {} devices",
+            self.devices.len()
+        );
+        *self.server_sender.lock() = Some(message_sender);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn disconnect(&self) -> ButtplugConnectorResultFuture {
+        self.server_sender.lock().take();
+        Box::pin(async { Ok(()) })
+    }
+
+    fn send(&self, msg: ButtplugClientMessageV3) -> ButtplugConnectorResultFuture {
+        let Some(server_sender) = self.server_sender.lock().clone() else {
+            return Box::pin(async { Err(ButtplugConnectorError::ConnectorNotConnected) });
+        };
+
+        // Messages are handled by their spec JSON to avoid building message types by hand
+        let replies = match serde_json::to_value(&msg) {
+            Ok(Value::Object(msg)) => msg
+                .iter()
+                .flat_map(|(msg_name, body)| self.handle_message(msg_name, body))
+                .filter_map(|reply| match serde_json::from_value(reply) {
+                    Ok(reply) => Some(reply),
+                    Err(e) => {
+                        warn!("Failed to build virtual device server reply: {}", e);
+                        None
+                    }
+                })
+                .collect::<Vec<ButtplugServerMessageV3>>(),
+            _ => {
+                warn!("Failed to read client message for virtual devices");
+                Vec::new()
+            }
+        };
+
+        Box::pin(async move {
+            for reply in replies {
+                if server_sender.send(reply).await.is_err() {
+                    return Err(ButtplugConnectorError::ConnectorChannelClosed);
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,14 +1,46 @@
 use log::{error as logerr, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use tauri::AppHandle;
+use ts_rs::TS;
 
 use crate::{
+    local_api::LocalApiConfig,
     osc::OSCNetworking,
-    util::fs::{build_path_dir, build_path_file, file_exists, get_config_dir, path_exists},
-    vcore::errors::backend::VibeCheckConfigError,
+    toy_handling::safety::SafetyConfig,
+    util::{
+        fs::{build_path_dir, build_path_file, file_exists, get_config_dir, path_exists},
+        virtual_devices::VirtualDeviceConfig,
+    },
+    vcore::{errors::backend::VibeCheckConfigError, host::CoreHandle},
 };
 
+/*
+ * How the ButtplugClient connects to a Buttplug server
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default, TS)]
+#[ts(export)]
+pub enum ButtplugConnectorConfig {
+    // VibeCheck runs its own Buttplug server and owns the Bluetooth adapter
+    #[default]
+    InProcess,
+    // Connect to an external websocket server like Intiface Central (ws://127.0.0.1:12345)
+    Websocket(String),
+    // Fake devices for testing without hardware
+    Virtual(Vec<VirtualDeviceConfig>),
+}
+
+impl ButtplugConnectorConfig {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::InProcess => true,
+            Self::Websocket(address) => {
+                address.starts_with("ws://") || address.starts_with("wss://")
+            }
+            Self::Virtual(devices) => devices.iter().all(|device| !device.name.is_empty()),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct VibeCheckConfig {
     // Change networking to an enum between OSCQuery and setting bind and remote.
@@ -18,6 +50,12 @@ pub struct VibeCheckConfig {
     pub desktop_notifications: bool,
     pub show_toy_advanced: bool,
     pub show_feature_advanced: bool,
+    #[serde(default)]
+    pub bp_connector: ButtplugConnectorConfig,
+    #[serde(default)]
+    pub local_api: LocalApiConfig,
+    #[serde(default)]
+    pub safety: SafetyConfig,
 }
 
 impl Default for VibeCheckConfig {
@@ -29,12 +67,15 @@ impl Default for VibeCheckConfig {
             desktop_notifications: false,
             show_toy_advanced: false,
             show_feature_advanced: false,
+            bp_connector: ButtplugConnectorConfig::default(),
+            local_api: LocalApiConfig::default(),
+            safety: SafetyConfig::default(),
         }
     }
 }
 
-pub fn config_load(app_handle: &AppHandle) -> Result<VibeCheckConfig, VibeCheckConfigError> {
-    let vc_root_dir = match get_config_dir(app_handle) {
+pub fn config_load(core_handle: &CoreHandle) -> Result<VibeCheckConfig, VibeCheckConfigError> {
+    let vc_root_dir = match get_config_dir(core_handle) {
         Ok(d) => d,
         Err(_) => return Err(VibeCheckConfigError::ConfigDirFail),
     };
@@ -63,15 +104,7 @@ pub fn config_load(app_handle: &AppHandle) -> Result<VibeCheckConfig, VibeCheckC
     if !file_exists(&vc_config_file) {
         fs::write(
             &vc_config_file,
-            serde_json::to_string(&VibeCheckConfig {
-                networking: OSCNetworking::default(),
-                scan_on_disconnect: false,
-                minimize_on_exit: false,
-                desktop_notifications: false,
-                show_toy_advanced: false,
-                show_feature_advanced: false,
-            })
-            .unwrap(),
+            serde_json::to_string(&VibeCheckConfig::default()).unwrap(),
         )
         .unwrap();
         info!("Created VibeCheck config.");
@@ -112,14 +145,7 @@ pub fn config_load(app_handle: &AppHandle) -> Result<VibeCheckConfig, VibeCheckC
                 _e
             );
             warn!("[*] Resetting to default config.");
-            let default_conf = VibeCheckConfig {
-                networking: OSCNetworking::default(),
-                scan_on_disconnect: false,
-                minimize_on_exit: false,
-                desktop_notifications: false,
-                show_toy_advanced: false,
-                show_feature_advanced: false,
-            };
+            let default_conf = VibeCheckConfig::default();
             fs::write(
                 &vc_config_file,
                 serde_json::to_string(&default_conf).unwrap(),
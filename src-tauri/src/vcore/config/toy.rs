@@ -1,15 +1,18 @@
 use crate::{
     frontend::frontend_types::FeVCToyAnatomy,
-    toy_handling::toyops::VCToyFeatures,
+    toy_handling::{safety::SafetyConfig, toyops::VCToyFeatures},
     util::fs::{build_path_dir, build_path_file, file_exists, get_config_dir},
-    vcore::errors::{
-        self,
-        backend::{VibeCheckFSError, VibeCheckToyConfigError},
+    vcore::{
+        errors::{
+            self,
+            backend::{VibeCheckFSError, VibeCheckToyConfigError},
+        },
+        host::CoreHandle,
     },
 };
 use log::{debug, error as logerr, info, warn};
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub enum VCToyAnatomy {
@@ -141,16 +144,72 @@ pub struct VCToyConfig {
     pub osc_data: bool,
     pub bt_update_rate: u64,
     pub anatomy: VCToyAnatomy,
+    // Feature mappings keyed by VRChat avatar ID. Avatars without a profile use features
+    #[serde(default)]
+    pub avatar_profiles: HashMap<String, VCToyFeatures>,
+    // Replaces the global safety limits for this toy
+    #[serde(default)]
+    pub safety: Option<SafetyConfig>,
 }
 
 impl VCToyConfig {
+    // The avatar's profile or the default profile
+    pub fn profile_features(&self, avatar_id: Option<&str>) -> &VCToyFeatures {
+        avatar_id
+            .and_then(|id| self.avatar_profiles.get(id))
+            .unwrap_or(&self.features)
+    }
+
+    // Avatar ID of the profile the avatar uses, None for the default profile
+    pub fn profile_id<'a>(&self, avatar_id: Option<&'a str>) -> Option<&'a str> {
+        avatar_id.filter(|id| self.avatar_profiles.contains_key(*id))
+    }
+
+    // Saves to the avatar's profile if it has one, otherwise to the default profile
+    pub fn set_profile_features(&mut self, avatar_id: Option<&str>, features: VCToyFeatures) {
+        match avatar_id.and_then(|id| self.avatar_profiles.get_mut(id)) {
+            Some(profile) => *profile = features,
+            None => self.features = features,
+        }
+    }
+
+    /*
+     * Keeps hand edited values in range after loading a config from disk
+     * Response curves of every profile are sanitized and an invalid safety override is dropped
+     */
+    pub fn sanitize(&mut self) {
+        for feature in self.features.features.iter_mut().chain(
+            self.avatar_profiles
+                .values_mut()
+                .flat_map(|profile| profile.features.iter_mut()),
+        ) {
+            let levels = &mut feature.feature_levels;
+            levels.response_curve = std::mem::take(&mut levels.response_curve).sanitized();
+        }
+
+        if self.safety.is_some_and(|safety| !safety.is_valid()) {
+            warn!("Invalid safety override for toy: {}", self.toy_name);
+            self.safety = None;
+        }
+    }
+
+    /*
+     * Copies a profile to another avatar, replacing its profile
+     * A from avatar without a profile copies the default profile
+     */
+    pub fn copy_avatar_profile(&mut self, from_avatar_id: Option<&str>, to_avatar_id: &str) {
+        let features = self.profile_features(from_avatar_id).clone();
+        self.avatar_profiles
+            .insert(to_avatar_id.to_string(), features);
+    }
+
     pub fn load_offline_toy_config(
         toy_name: String,
-        app_handle: &AppHandle,
+        core_handle: &CoreHandle,
     ) -> Result<VCToyConfig, VibeCheckToyConfigError> {
         // Generate config path
 
-        let config_dir = match get_config_dir(app_handle) {
+        let config_dir = match get_config_dir(core_handle) {
             Ok(d) => d,
             Err(_) => return Err(VibeCheckToyConfigError::ConfigDirFail),
         };
@@ -163,12 +222,13 @@ impl VCToyConfig {
         } else {
             let con = std::fs::read_to_string(config_path).unwrap();
 
-            let config: VCToyConfig = match serde_json::from_str(&con) {
+            let mut config: VCToyConfig = match serde_json::from_str(&con) {
                 Ok(vc_toy_config) => vc_toy_config,
                 Err(_) => {
                     return Err(errors::backend::VibeCheckToyConfigError::DeserializeError);
                 }
             };
+            config.sanitize();
             debug!("Loaded & parsed toy config successfully!");
             Ok(config)
         }
@@ -176,9 +236,9 @@ impl VCToyConfig {
 
     pub fn save_offline_toy_config(
         &self,
-        app_handle: &AppHandle,
+        core_handle: &CoreHandle,
     ) -> Result<(), VibeCheckToyConfigError> {
-        let config_dir = match get_config_dir(app_handle) {
+        let config_dir = match get_config_dir(core_handle) {
             Ok(d) => d,
             Err(_) => return Err(VibeCheckToyConfigError::ConfigDirFail),
         };
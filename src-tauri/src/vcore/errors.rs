@@ -8,7 +8,7 @@ use serde::Serialize;
 
 #[derive(Debug, Serialize)]
 pub enum VcoreError {
-    NoAppHandle,
+    NoCoreHandle,
     NoToyManager,
     NoStatePointer,
     CehAlreadyInitialized,
@@ -47,10 +47,17 @@ pub mod frontend {
         DisableFailure,
         StartScanFailure(String),
         StopScanFailure(String),
+        NotListening,
 
         // Config Errors
         InvalidBindEndpoint,
         InvalidRemoteEndpoint,
+        InvalidButtplugConnector,
+        InvalidLocalApiBind,
+        InvalidSafetyConfig,
+        InvalidLevelOutput,
+        InvalidForwardTarget,
+        InvalidAvatarId,
         OSCQueryFailure(&'static str),
         SerializeFailure,
         WriteFailure,
@@ -0,0 +1,92 @@
+/*
+ * Host interface
+ * Everything the core needs from whatever is running it (The Tauri app or the headless daemon).
+ * The core never touches the Tauri AppHandle directly.
+ */
+
+use std::{fmt, ops::Deref, sync::Arc};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::{
+    frontend::{
+        error::FrontendError,
+        frontend_types::{FeCoreEvent, FeToyEvent},
+    },
+    util::errors::UtilError,
+    vcore::state::VCStateMutex,
+};
+
+pub trait VibeCheckHost: Send + Sync {
+    // Root directory of Config.json and ToyConfigs
+    fn config_dir(&self) -> Result<String, UtilError>;
+    fn state(&self) -> &VCStateMutex;
+    fn emit_toy_event(&self, event: FeToyEvent);
+    fn emit_core_event(&self, event: FeCoreEvent);
+    fn emit_error(&self, event: FrontendError);
+    // Desktop notification (If the host has a desktop)
+    fn notify(&self, title: &str, body: &str);
+}
+
+// Events buffered per subscriber before it starts lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/*
+ * An event emitted to the host
+ * Serialized the same way for every subscriber: {"event": <name>, "payload": <event>}
+ */
+#[derive(Serialize, Clone)]
+#[serde(tag = "event", content = "payload")]
+pub enum CoreEvent {
+    #[serde(rename = "fe_toy_event")]
+    Toy(FeToyEvent),
+    #[serde(rename = "fe_core_event")]
+    Core(FeCoreEvent),
+    #[serde(rename = "fe_error")]
+    Error(FrontendError),
+}
+
+#[derive(Clone)]
+pub struct CoreHandle {
+    host: Arc<dyn VibeCheckHost>,
+    // Copies of host events for listeners other than the host (Local API clients)
+    events: broadcast::Sender<CoreEvent>,
+}
+
+impl CoreHandle {
+    pub fn new(host: impl VibeCheckHost + 'static) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            host: Arc::new(host),
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CoreEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn publish(&self, event: CoreEvent) {
+        // No subscribers is fine
+        let _ = self.events.send(event);
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.events.receiver_count() > 0
+    }
+}
+
+impl Deref for CoreHandle {
+    type Target = dyn VibeCheckHost;
+
+    fn deref(&self) -> &Self::Target {
+        self.host.as_ref()
+    }
+}
+
+impl fmt::Debug for CoreHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CoreHandle")
+    }
+}
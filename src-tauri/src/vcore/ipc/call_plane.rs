@@ -6,24 +6,31 @@ use crate::util::fs::get_user_home_dir;
 
 use crate::{
     frontend::{
-        frontend_types::{FeToyEvent, FeVCFeatureType, FeVCToy, FeVibeCheckConfig},
-        ToFrontend,
+        frontend_types::{
+            FeAvatarParameter, FeCoreEvent, FeToyAlter, FeToyEvent, FeVCFeatureType, FeVCToy,
+            FeVibeCheckConfig,
+        },
+        FromFrontend, ToFrontend,
     },
-    osc::OSCNetworking,
+    local_api::LocalApiConfig,
+    osc::{query_client, OSCNetworking},
     toy_handling::{
         errors::HandlerErr,
-        toy_command_processor::command_toy,
+        funscript::{FunscriptControl, FunscriptLibrary},
+        patterns::PatternLibrary,
+        toy_command_processor::{command_toy, FeatureCommand},
         toyops::{VCFeatureType, VCToy},
     },
     util::fs::{build_path_file, get_config_dir},
     vcore::{
-        config::app::VibeCheckConfig,
+        config::{app::VibeCheckConfig, toy::VCToyConfig},
         errors::{
             backend::{ToyAlterError, VibeCheckConfigError, VibeCheckFSError},
             frontend::VCFeError,
             VCError, VcoreError,
         },
-        ipc::emit_plane::emit_toy_event,
+        host::CoreHandle,
+        ipc::emit_plane::{emit_core_event, emit_toy_event},
         state::{RunningState, VCStateMutex},
     },
 };
@@ -40,6 +47,8 @@ pub enum TmSig {
     StopListening,
     StartListening(OSCNetworking),
     TMHReset,
+    // Forwarded to every toy thread while listening
+    Funscript(FunscriptControl),
     /*
     Running,
     Stopped,
@@ -54,9 +63,7 @@ pub enum ToyManagementEvent {
     Sig(TmSig),
 }
 
-pub async fn native_vibecheck_disable(
-    vc_state: tauri::State<'_, VCStateMutex>,
-) -> Result<(), VCFeError> {
+pub async fn native_vibecheck_disable(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let mut vc_lock = vc_state.0.lock();
     trace!("Got vc_lock");
     if let RunningState::Stopped = vc_lock.running {
@@ -99,9 +106,7 @@ pub async fn native_vibecheck_disable(
     Ok(())
 }
 
-pub async fn native_vibecheck_enable(
-    vc_state: tauri::State<'_, VCStateMutex>,
-) -> Result<(), VCFeError> {
+pub async fn native_vibecheck_enable(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let mut vc_lock = vc_state.0.lock();
 
     if let RunningState::Running = vc_lock.running {
@@ -157,30 +162,29 @@ pub async fn native_vibecheck_enable(
     }
 }
 
-pub fn native_osc_query_start(vc_state: tauri::State<'_, VCStateMutex>) -> Result<(), VCFeError> {
+pub fn native_osc_query_start(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let mut vc_lock = vc_state.0.lock();
 
     if vc_lock.osc_query_handler.is_none() {
         vc_lock.osc_query_init();
     }
 
-    vc_lock
-        .osc_query_handler
-        .as_mut()
-        .unwrap()
-        .start_http_json();
+    // VibeCheck serves the OSCQuery HTTP JSON itself so the host tree holds its addresses
+    let async_rt = vc_lock.async_rt.handle().clone();
+    vc_lock.osc_query_host.as_mut().unwrap().start(&async_rt);
     vc_lock
         .osc_query_handler
         .as_ref()
         .unwrap()
         .register_mdns_service();
+    vc_lock.osc_query_populate();
     // This is only to attempt to auto-induce an mDNS response with separated answers.
     vc_lock.osc_query_associate();
 
     Ok(())
 }
 
-pub fn native_osc_query_stop(vc_state: tauri::State<'_, VCStateMutex>) -> Result<(), VCFeError> {
+pub fn native_osc_query_stop(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let mut vc_lock = vc_state.0.lock();
 
     if vc_lock.osc_query_handler.is_none() {
@@ -192,9 +196,7 @@ pub fn native_osc_query_stop(vc_state: tauri::State<'_, VCStateMutex>) -> Result
     Ok(())
 }
 
-pub fn native_osc_query_attempt_force(
-    vc_state: tauri::State<'_, VCStateMutex>,
-) -> Result<(), VCFeError> {
+pub fn native_osc_query_attempt_force(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let vc_lock = vc_state.0.lock();
 
     if vc_lock.osc_query_handler.is_none() {
@@ -207,13 +209,42 @@ pub fn native_osc_query_attempt_force(
     Ok(())
 }
 
-pub fn osc_query_force_populate(vc_state: tauri::State<'_, VCStateMutex>) -> Result<(), VCFeError> {
+pub fn osc_query_force_populate(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
+    let vc_lock = vc_state.0.lock();
+
+    if vc_lock.osc_query_handler.is_none() {
+        return Err(VCFeError::OSCQueryFailure("OSCQuery is not initialized"));
+    }
+
+    vc_lock.osc_query_populate();
+
     Ok(())
 }
 
-pub async fn native_vibecheck_start_bt_scan(
-    vc_state: tauri::State<'_, VCStateMutex>,
-) -> Result<(), VCFeError> {
+/*
+ * Reads the parameters of the current avatar from VRChat's OSCQuery service
+ * Runs on a blocking thread because the blocking HTTP client can't run on an async runtime thread
+ */
+pub async fn native_get_avatar_parameters() -> Result<Vec<FeAvatarParameter>, VCFeError> {
+    let fetched = tauri::async_runtime::spawn_blocking(|| {
+        let Some(host) = query_client::discover_vrchat(Duration::from_secs(3)) else {
+            return Err(VCFeError::OSCQueryFailure(
+                "VRChat OSCQuery service not found",
+            ));
+        };
+        query_client::fetch_avatar_parameters(host).map_err(|e| {
+            warn!("Failed to fetch avatar parameters from {}: {}", host, e);
+            VCFeError::OSCQueryFailure("Failed to fetch avatar parameters")
+        })
+    })
+    .await;
+
+    fetched.unwrap_or(Err(VCFeError::OSCQueryFailure(
+        "Failed to fetch avatar parameters",
+    )))
+}
+
+pub async fn native_vibecheck_start_bt_scan(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let vc_lock = vc_state.0.lock();
 
     if vc_lock.bp_client.is_none() {
@@ -250,9 +281,7 @@ pub async fn native_vibecheck_start_bt_scan(
     Ok(())
 }
 
-pub async fn native_vibecheck_stop_bt_scan(
-    vc_state: tauri::State<'_, VCStateMutex>,
-) -> Result<(), VCFeError> {
+pub async fn native_vibecheck_stop_bt_scan(vc_state: &VCStateMutex) -> Result<(), VCFeError> {
     let vc_lock = vc_state.0.lock();
 
     if vc_lock.bp_client.is_none() {
@@ -277,7 +306,7 @@ pub async fn native_vibecheck_stop_bt_scan(
     Ok(())
 }
 
-pub fn native_get_vibecheck_config(vc_state: tauri::State<'_, VCStateMutex>) -> FeVibeCheckConfig {
+pub fn native_get_vibecheck_config(vc_state: &VCStateMutex) -> FeVibeCheckConfig {
     let config = {
         let vc_lock = vc_state.0.lock();
         vc_lock.config.clone()
@@ -290,11 +319,14 @@ pub fn native_get_vibecheck_config(vc_state: tauri::State<'_, VCStateMutex>) ->
         desktop_notifications: config.desktop_notifications,
         show_toy_advanced: config.show_toy_advanced,
         show_feature_advanced: config.show_feature_advanced,
+        bp_connector: config.bp_connector,
+        local_api: config.local_api.to_fe(),
+        safety: config.safety,
     }
 }
 
 pub fn native_set_vibecheck_config(
-    vc_state: tauri::State<'_, VCStateMutex>,
+    vc_state: &VCStateMutex,
     fe_vc_config: FeVibeCheckConfig,
 ) -> Result<(), VCFeError> {
     info!("Got fe_vc_config: {:?}", fe_vc_config);
@@ -308,25 +340,72 @@ pub fn native_set_vibecheck_config(
         Err(_e) => return Err(VCFeError::InvalidRemoteEndpoint),
     };
 
+    if !fe_vc_config.bp_connector.is_valid() {
+        return Err(VCFeError::InvalidButtplugConnector);
+    }
+
+    // The Local API is never exposed beyond this machine
+    let local_api = match SocketAddrV4::from_str(&fe_vc_config.local_api.bind) {
+        Ok(sa) if sa.ip().is_loopback() => LocalApiConfig {
+            enabled: fe_vc_config.local_api.enabled,
+            bind: sa,
+        },
+        _ => return Err(VCFeError::InvalidLocalApiBind),
+    };
+
+    if !fe_vc_config.safety.is_valid() {
+        return Err(VCFeError::InvalidSafetyConfig);
+    }
+
+    if !fe_vc_config.networking.level_output.is_valid() {
+        return Err(VCFeError::InvalidLevelOutput);
+    }
+
+    if !fe_vc_config
+        .networking
+        .forwarding
+        .iter()
+        .all(|forward| forward.is_valid(bind))
+    {
+        return Err(VCFeError::InvalidForwardTarget);
+    }
+
     let config = {
         let mut vc_lock = vc_state.0.lock();
         vc_lock.config.networking.bind = bind;
         vc_lock.config.networking.remote = remote;
+        // Toy threads pick up level output changes when listening starts again
+        vc_lock.config.networking.level_output = fe_vc_config.networking.level_output;
+        // Forwarding changes apply when VibeCheck is next enabled or disabled
+        vc_lock.config.networking.forwarding = fe_vc_config.networking.forwarding;
         vc_lock.config.scan_on_disconnect = fe_vc_config.scan_on_disconnect;
         vc_lock.config.minimize_on_exit = fe_vc_config.minimize_on_exit;
         vc_lock.config.desktop_notifications = fe_vc_config.desktop_notifications;
         vc_lock.config.show_toy_advanced = fe_vc_config.show_toy_advanced;
         vc_lock.config.show_feature_advanced = fe_vc_config.show_feature_advanced;
 
+        // Reconnect through the new connector without restarting
+        if vc_lock.config.bp_connector != fe_vc_config.bp_connector {
+            vc_lock.config.bp_connector = fe_vc_config.bp_connector;
+            vc_lock.switch_bp_connector();
+        }
+
+        // The caller restarts the Local API so a Local API client gets its reply first
+        vc_lock.config.local_api = local_api;
+
+        // Toy emitters pick up the new limits on their next tick
+        vc_lock.config.safety = fe_vc_config.safety;
+        vc_lock.output_control.set_safety(fe_vc_config.safety);
+
         vc_lock.config.clone()
     };
 
-    let app_handle = {
+    let core_handle = {
         let vc_lock = vc_state.0.lock();
-        vc_lock.app_handle.clone().unwrap()
+        vc_lock.core_handle.clone().unwrap()
     };
 
-    match save_config(config, &app_handle) {
+    match save_config(config, &core_handle) {
         Ok(()) => Ok(()),
         Err(e) => match e {
             VibeCheckConfigError::SerializeError => Err(VCFeError::SerializeFailure),
@@ -338,7 +417,7 @@ pub fn native_set_vibecheck_config(
 
 fn save_config(
     config: VibeCheckConfig,
-    app_handle: &tauri::AppHandle,
+    core_handle: &CoreHandle,
 ) -> Result<(), VibeCheckConfigError> {
     let json_config_str = match serde_json::to_string(&config) {
         Ok(s) => s,
@@ -348,7 +427,7 @@ fn save_config(
         }
     };
 
-    let config_dir = match get_config_dir(app_handle) {
+    let config_dir = match get_config_dir(core_handle) {
         Ok(d) => d,
         Err(_) => return Err(VibeCheckConfigError::ConfigDirFail),
     };
@@ -366,8 +445,8 @@ fn save_config(
 }
 
 pub fn native_alter_toy(
-    vc_state: tauri::State<'_, VCStateMutex>,
-    app_handle: tauri::AppHandle,
+    vc_state: &VCStateMutex,
+    core_handle: CoreHandle,
     altered: VCToy,
 ) -> Result<(), ToyAlterError> {
     let alter_clone = altered.clone();
@@ -379,8 +458,10 @@ pub fn native_alter_toy(
         let res = vc_lock
             .tme_send_tx
             .send(ToyManagementEvent::Tu(ToyUpdate::AlterToy(altered)));
+        // Parameters may have changed
+        vc_lock.osc_query_populate();
         emit_toy_event(
-            &app_handle,
+            &core_handle,
             FeToyEvent::Update({
                 FeVCToy {
                     toy_id: Some(alter_clone.toy_id),
@@ -393,6 +474,7 @@ pub fn native_alter_toy(
                     osc_data: alter_clone.osc_data,
                     bt_update_rate: alter_clone.bt_update_rate,
                     sub_id: alter_clone.sub_id,
+                    safety: alter_clone.config.as_ref().unwrap().safety,
                 }
             }),
         );
@@ -405,6 +487,291 @@ pub fn native_alter_toy(
     }
 }
 
+/*
+ * Switches every online toy to the avatar's profile (or the default profile)
+ * Toys that use the same profile for both avatars are left as they are
+ */
+pub fn native_apply_avatar_profiles(
+    vc_state: &VCStateMutex,
+    core_handle: CoreHandle,
+    avatar_id: Option<String>,
+) {
+    let altered_toys = {
+        let mut vc_lock = vc_state.0.lock();
+        let previous_avatar_id = std::mem::replace(&mut vc_lock.avatar_id, avatar_id.clone());
+        let Some(toy_manager) = vc_lock.core_toy_manager.as_mut() else {
+            return;
+        };
+
+        toy_manager
+            .online_toys
+            .values_mut()
+            .filter(|toy| {
+                toy.config.as_ref().is_some_and(|config| {
+                    config.profile_id(previous_avatar_id.as_deref())
+                        != config.profile_id(avatar_id.as_deref())
+                })
+            })
+            .filter_map(|toy| {
+                toy.apply_avatar_profile(avatar_id.as_deref())
+                    .then(|| toy.clone())
+            })
+            .collect::<Vec<VCToy>>()
+    };
+
+    info!(
+        "Applying avatar profile {:?} to {} toys",
+        avatar_id,
+        altered_toys.len()
+    );
+    for toy in altered_toys {
+        let _ = native_alter_toy(vc_state, core_handle.clone(), toy);
+    }
+}
+
+/*
+ * Copies an avatar profile to another avatar for every online toy
+ * No from avatar copies the default profile
+ */
+pub fn native_copy_avatar_profile(
+    vc_state: &VCStateMutex,
+    from_avatar_id: Option<String>,
+    to_avatar_id: String,
+) -> Result<(), VCFeError> {
+    if to_avatar_id.is_empty() {
+        return Err(VCFeError::InvalidAvatarId);
+    }
+
+    let (core_handle, altered_toys) = {
+        let mut vc_lock = vc_state.0.lock();
+        let Some(core_handle) = vc_lock.core_handle.clone() else {
+            return Err(VCFeError::Vcore(VcoreError::NoCoreHandle));
+        };
+        // Show the copied profile right away when it is for the current avatar
+        let current_avatar = vc_lock.avatar_id.as_deref() == Some(to_avatar_id.as_str());
+        let Some(toy_manager) = vc_lock.core_toy_manager.as_mut() else {
+            return Err(VCFeError::ToyManagerNotReady);
+        };
+
+        let mut altered_toys = Vec::new();
+
+        for toy in toy_manager.online_toys.values_mut() {
+            let Some(config) = toy.config.as_mut() else {
+                continue;
+            };
+            config.copy_avatar_profile(from_avatar_id.as_deref(), &to_avatar_id);
+            if let Err(e) = config.save_offline_toy_config(&core_handle) {
+                logerr!(
+                    "Failed to save avatar profile for {}: {:?}",
+                    toy.toy_name,
+                    e
+                );
+            }
+            if current_avatar && toy.apply_avatar_profile(Some(&to_avatar_id)) {
+                altered_toys.push(toy.clone());
+            }
+        }
+        (core_handle, altered_toys)
+    };
+
+    for toy in altered_toys {
+        let _ = native_alter_toy(vc_state, core_handle.clone(), toy);
+    }
+    Ok(())
+}
+
+// Names of the patterns in the Patterns directory
+pub fn native_get_patterns(vc_state: &VCStateMutex) -> Result<Vec<String>, VCFeError> {
+    let Some(core_handle) = vc_state.0.lock().core_handle.clone() else {
+        return Err(VCFeError::Vcore(VcoreError::NoCoreHandle));
+    };
+    Ok(PatternLibrary::load(&core_handle).names())
+}
+
+// Names of the funscripts in the Funscripts directory
+pub fn native_get_funscripts(vc_state: &VCStateMutex) -> Result<Vec<String>, VCFeError> {
+    let Some(core_handle) = vc_state.0.lock().core_handle.clone() else {
+        return Err(VCFeError::Vcore(VcoreError::NoCoreHandle));
+    };
+    Ok(FunscriptLibrary::load(&core_handle).names())
+}
+
+/*
+ * Sends a funscript playback control to every listening toy
+ */
+pub fn native_funscript_control(
+    vc_state: &VCStateMutex,
+    control: FunscriptControl,
+) -> Result<(), VCFeError> {
+    let vc_lock = vc_state.0.lock();
+    if let RunningState::Stopped = vc_lock.running {
+        return Err(VCFeError::NotListening);
+    }
+
+    trace!("Funscript control: {:?}", control);
+    if vc_lock
+        .tme_send_tx
+        .send(ToyManagementEvent::Sig(TmSig::Funscript(control)))
+        .is_err()
+    {
+        logerr!("Failed to send funscript control to tmh.");
+        return Err(VCFeError::NotListening);
+    }
+    Ok(())
+}
+
+pub fn native_get_avatar_id(vc_state: &VCStateMutex) -> Option<String> {
+    vc_state.0.lock().avatar_id.clone()
+}
+
+/*
+ * Alters an online toy or the saved config of an offline toy from frontend toy data
+ */
+pub fn native_fe_alter_toy(vc_state: &VCStateMutex, mutate: FeToyAlter) -> Result<(), VCFeError> {
+    let Some(core_handle) = vc_state.0.lock().core_handle.clone() else {
+        return Err(VCFeError::Vcore(VcoreError::NoCoreHandle));
+    };
+
+    let (FeToyAlter::Connected(fe_toy) | FeToyAlter::Disconnected(fe_toy)) = &mutate;
+    if fe_toy.safety.is_some_and(|safety| !safety.is_valid()) {
+        return Err(VCFeError::InvalidSafetyConfig);
+    }
+
+    match mutate {
+        // Online toy alter
+        FeToyAlter::Connected(fe_toy) => {
+            if fe_toy.toy_connected {
+                trace!(
+                    "FeToyAlter::Connected: Altering online toy: {}",
+                    fe_toy.toy_name
+                );
+
+                let altered = {
+                    let mut vc_lock = vc_state.0.lock();
+                    let avatar_id = vc_lock.avatar_id.clone();
+                    if let Some(toy) = vc_lock
+                        .core_toy_manager
+                        .as_mut()
+                        .unwrap()
+                        .online_toys
+                        .get_mut(&fe_toy.toy_id.unwrap())
+                    {
+                        toy.osc_data = fe_toy.osc_data;
+                        toy.bt_update_rate = fe_toy.bt_update_rate;
+
+                        toy.config.as_mut().unwrap().osc_data = fe_toy.osc_data;
+                        toy.config.as_mut().unwrap().bt_update_rate = fe_toy.bt_update_rate;
+                        toy.config.as_mut().unwrap().safety = fe_toy.safety;
+                        toy.config
+                            .as_mut()
+                            .unwrap()
+                            .anatomy
+                            .from_fe(fe_toy.toy_anatomy);
+
+                        // Overwrite all features in the state handled toy.
+                        for fe_feature in fe_toy.features {
+                            if !toy.parsed_toy_features.from_frontend(fe_feature.clone()) {
+                                logerr!("Failed to convert FeVCToyFeature to VCToyFeature");
+                                return Err(VCFeError::AlterToyFailure(
+                                    ToyAlterError::NoFeatureIndex,
+                                ));
+                            } else {
+                                // If altering feature map succeeds write the data to the active profile
+                                toy.config.as_mut().unwrap().set_profile_features(
+                                    avatar_id.as_deref(),
+                                    toy.parsed_toy_features.clone(),
+                                );
+                            }
+                        }
+
+                        toy.clone()
+                    } else {
+                        return Err(VCFeError::AlterToyFailure(ToyAlterError::NoToyIndex));
+                    }
+                };
+
+                if native_alter_toy(vc_state, core_handle, altered).is_err() {
+                    return Err(VCFeError::AlterToyFailure(ToyAlterError::TMESendFailure));
+                }
+            } else {
+                return Err(VCFeError::AlterToyFailure(ToyAlterError::ToyDisconnected));
+            }
+
+            Ok(())
+        }
+        // Offline toy alter
+        FeToyAlter::Disconnected(mut fe_toy) => {
+            if !fe_toy.toy_connected {
+                trace!(
+                    "FeToyAlter::Disconnected: Altering offline toy: {}",
+                    fe_toy.toy_name
+                );
+                let mut offline_toy_config = match VCToyConfig::load_offline_toy_config(
+                    fe_toy.toy_name.clone(),
+                    &core_handle,
+                ) {
+                    Ok(toy_config) => toy_config,
+                    Err(_e) => {
+                        return Err(VCFeError::AlterToyFailure(
+                            ToyAlterError::OfflineToyNotExist,
+                        ))
+                    }
+                };
+
+                // Offline toys have no avatar, so they show and edit their default profile
+                offline_toy_config.osc_data = fe_toy.osc_data;
+                offline_toy_config.bt_update_rate = fe_toy.bt_update_rate;
+                offline_toy_config.anatomy.from_fe(fe_toy.toy_anatomy);
+                offline_toy_config.safety = fe_toy.safety;
+
+                for f in fe_toy.features {
+                    if !offline_toy_config.features.from_frontend(f) {
+                        return Err(VCFeError::AlterToyFailure(
+                            ToyAlterError::OfflineToyNoFeatureIndex,
+                        ));
+                    }
+                }
+
+                fe_toy.features = offline_toy_config.features.features.to_frontend();
+                fe_toy.osc_data = offline_toy_config.osc_data;
+                fe_toy.bt_update_rate = offline_toy_config.bt_update_rate;
+                fe_toy.toy_anatomy = offline_toy_config.anatomy.to_fe();
+                fe_toy.safety = offline_toy_config.safety;
+
+                if offline_toy_config
+                    .save_offline_toy_config(&core_handle)
+                    .is_err()
+                {
+                    return Err(VCFeError::SaveOfflineToyConfig);
+                }
+
+                emit_toy_event(&core_handle, FeToyEvent::Update(fe_toy));
+            } else {
+                return Err(VCFeError::AlterToyFailure(ToyAlterError::ToyConnected));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+pub fn native_sync_offline_toys(
+    vc_state: &VCStateMutex,
+    refresh_toys: bool,
+) -> Result<Vec<FeVCToy>, VCFeError> {
+    if let Some(toy_manager) = vc_state.0.lock().core_toy_manager.as_mut() {
+        match toy_manager.sync_frontend(refresh_toys) {
+            Ok(toys) => Ok(toys),
+            Err(e) => {
+                logerr!("Toy Manager failed to sync frontend: {}", e);
+                Err(VCFeError::ToyManager(e))
+            }
+        }
+    } else {
+        Err(VCFeError::ToyManagerNotReady)
+    }
+}
+
 #[cfg(target_os = "linux")]
 pub fn native_clear_osc_config() -> Result<(), VibeCheckFSError> {
     return Ok(());
@@ -460,13 +827,13 @@ pub fn native_clear_osc_config() -> Result<(), VibeCheckFSError> {
 }
 
 pub fn native_simulate_device_feature(
-    vc_state: tauri::State<'_, VCStateMutex>,
+    vc_state: &VCStateMutex,
     toy_id: u32,
     feature_index: u32,
     feature_type: FeVCFeatureType,
     float_level: f64,
     stop: bool,
-) {
+) -> Option<f64> {
     let vc_toys = {
         let vc_lock = vc_state.0.lock();
         vc_lock
@@ -477,11 +844,7 @@ pub fn native_simulate_device_feature(
             .clone()
     };
 
-    let toy = match vc_toys.get(&toy_id) {
-        Some(toy) => toy,
-        None => return,
-    }
-    .clone();
+    let toy = vc_toys.get(&toy_id)?.clone();
 
     // Need to filter between ScalarCmd's and non ScalarCmd's
     for feature in toy.parsed_toy_features.features {
@@ -494,34 +857,94 @@ pub fn native_simulate_device_feature(
                     && feature_type == FeVCFeatureType::Rotator)
         {
             let handle_clone = toy.device_handle.clone();
-            {
-                let vc_lock = vc_state.0.lock();
-                // Add stop flag bc FE invoke simulation: diff between stop & idle.
-                if stop {
-                    debug!("Stopping toy feature.");
-                    // Sleep to allow the Alter Toy to populate the runtime before sending the stop command
-                    thread::sleep(Duration::from_millis(500));
-                    vc_lock.async_rt.spawn(handle_clone.stop());
-                } else {
-                    debug!("Sending simulate command!");
-                    vc_lock.async_rt.spawn(command_toy(
-                        handle_clone,
-                        feature.feature_type,
-                        float_level,
-                        feature.feature_index,
-                        feature.flip_input_float,
-                        feature.feature_levels,
-                    ));
-                }
+            let vc_lock = vc_state.0.lock();
+            // Add stop flag bc FE invoke simulation: diff between stop & idle.
+            if stop {
+                debug!("Stopping toy feature.");
+                // Sleep to allow the Alter Toy to populate the runtime before sending the stop command
+                thread::sleep(Duration::from_millis(500));
+                vc_lock.async_rt.spawn(handle_clone.stop());
+                return None;
             }
-            return;
+
+            debug!("Sending simulate command!");
+            // Level the toy gets after the response curve and range
+            let command = FeatureCommand::shaped(
+                feature.feature_type,
+                float_level,
+                feature.feature_index,
+                feature.flip_input_float,
+                &feature.feature_levels,
+            );
+            let max_level = vc_lock
+                .output_control
+                .toy_safety(toy.config.as_ref().and_then(|config| config.safety))
+                .max_level;
+            vc_lock.async_rt.spawn(command_toy(
+                handle_clone,
+                vc_lock.output_control.clone(),
+                max_level,
+                command,
+            ));
+            return Some(command.output_level);
+        }
+    }
+    None
+}
+
+/*
+ * Latches the emergency stop and stops every online toy
+ * Toy commands are dropped until native_emergency_rearm
+ */
+pub fn native_emergency_stop(vc_state: &VCStateMutex) {
+    let vc_lock = vc_state.0.lock();
+    let newly_latched = vc_lock.output_control.latch_emergency_stop();
+
+    // Stop again even if already latched
+    let devices = vc_lock
+        .core_toy_manager
+        .as_ref()
+        .map(|toy_manager| {
+            toy_manager
+                .online_toys
+                .values()
+                .map(|toy| toy.device_handle.clone())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    vc_lock.async_rt.spawn(async move {
+        for dev in devices {
+            if let Err(e) = dev.stop().await {
+                logerr!("Emergency stop failed for device {}: {}", dev.index(), e);
+            }
+        }
+    });
+
+    if newly_latched {
+        warn!("Emergency stop latched");
+        if let Some(core_handle) = vc_lock.core_handle.as_ref() {
+            emit_core_event(core_handle, FeCoreEvent::EmergencyStop(true));
+        }
+    }
+}
+
+pub fn native_emergency_rearm(vc_state: &VCStateMutex) {
+    let vc_lock = vc_state.0.lock();
+    if vc_lock.output_control.rearm() {
+        info!("Emergency stop re-armed");
+        if let Some(core_handle) = vc_lock.core_handle.as_ref() {
+            emit_core_event(core_handle, FeCoreEvent::EmergencyStop(false));
         }
     }
 }
 
+pub fn native_is_emergency_stopped(vc_state: &VCStateMutex) -> bool {
+    vc_state.0.lock().output_control.is_emergency_stopped()
+}
+
 /* Leaving this here in case of future use
  *
-pub fn native_simulate_feature_osc_input(vc_state: tauri::State<'_, VCStateMutex>, simulated_param_address: String, simulated_param_value: f32) {
+pub fn native_simulate_feature_osc_input(vc_state: &VCStateMutex, simulated_param_address: String, simulated_param_value: f32) {
 
     let osc_buf = match encoder::encode(&OscPacket::Message(OscMessage {
         addr: simulated_param_address.clone(),
@@ -1,29 +1,28 @@
-use log::error as logerr;
-
-use tauri::{AppHandle, Emitter};
-
-use crate::frontend::{
-    error::FrontendError,
-    frontend_types::{FeCoreEvent, FeToyEvent},
+use crate::{
+    frontend::{
+        error::FrontendError,
+        frontend_types::{FeCoreEvent, FeToyEvent},
+    },
+    vcore::host::{CoreEvent, CoreHandle},
 };
 
-pub fn emit_toy_event(app_handle: &AppHandle, event: FeToyEvent) {
-    match app_handle.emit("fe_toy_event", &event) {
-        Ok(()) => (),
-        Err(e) => logerr!("Emit Toy Event [{}] failed: {}", event, e),
+pub fn emit_toy_event(core_handle: &CoreHandle, event: FeToyEvent) {
+    if core_handle.has_subscribers() {
+        core_handle.publish(CoreEvent::Toy(event.clone()));
     }
+    core_handle.emit_toy_event(event);
 }
 
-pub fn emit_core_event(app_handle: &AppHandle, event: FeCoreEvent) {
-    match app_handle.emit("fe_core_event", &event) {
-        Ok(()) => (),
-        Err(e) => logerr!("Emit Core Event [{}] failed: {}", event, e),
+pub fn emit_core_event(core_handle: &CoreHandle, event: FeCoreEvent) {
+    if core_handle.has_subscribers() {
+        core_handle.publish(CoreEvent::Core(event.clone()));
     }
+    core_handle.emit_core_event(event);
 }
 
-pub fn emit_error(app_handle: &AppHandle, event: FrontendError) {
-    match app_handle.emit("fe_error", &event) {
-        Ok(()) => (),
-        Err(e) => logerr!("Emit Error Event [{}] failed: {}", event, e),
+pub fn emit_error(core_handle: &CoreHandle, event: FrontendError) {
+    if core_handle.has_subscribers() {
+        core_handle.publish(CoreEvent::Error(event.clone()));
     }
+    core_handle.emit_error(event);
 }
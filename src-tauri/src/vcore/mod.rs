@@ -1,4 +1,5 @@
 pub mod config;
 pub mod errors;
+pub mod host;
 pub mod ipc;
 pub mod state;
@@ -1,9 +1,9 @@
 use buttplug::client::ButtplugClient;
-use log::{error as logerr, info, warn};
+use futures::StreamExt;
+use log::{error as logerr, info, trace, warn};
 use parking_lot::Mutex;
 use std::net::SocketAddrV4;
 use std::sync::Arc;
-use tauri::AppHandle;
 use tokio::runtime::Runtime;
 use tokio::sync::{mpsc::unbounded_channel, mpsc::UnboundedReceiver, mpsc::UnboundedSender};
 use tokio::task::JoinHandle;
@@ -11,33 +11,59 @@ use vrcoscquery::OSCQuery;
 
 use crate::error_signal_handler::state_comm::error_message_handler;
 use crate::error_signal_handler::{ErrorSource, VibeCheckError};
+use crate::frontend::frontend_types::FeToyEvent;
+use crate::local_api::server::local_api_listen;
+use crate::local_api::LocalApiConfig;
 use crate::osc::logic::{toy_refresh, vc_disabled_osc_command_listen};
-use crate::toy_handling::runtime::client_event_handler::client_event_handler;
+use crate::osc::query_host::OscQueryHost;
+use crate::osc::query_tree::{consumed_addresses, host_tree};
+use crate::toy_handling::output_control::OutputControl;
+use crate::toy_handling::runtime::client_event_handler::{bp_client_connect, client_event_handler};
 use crate::toy_handling::runtime::toy_management_handler::toy_management_handler;
 use crate::toy_handling::toy_manager::ToyManager;
 use crate::util::bluetooth;
 use crate::util::net::{find_available_tcp_port, find_available_udp_port};
+use crate::util::virtual_devices::VirtualCommandLog;
 use crate::vcore::errors::VcoreError;
 
-use super::config::app::VibeCheckConfig;
+use super::config::app::{ButtplugConnectorConfig, VibeCheckConfig};
 use super::errors::VCError;
-use super::ipc::call_plane::ToyManagementEvent;
+use super::host::CoreHandle;
+use super::ipc::call_plane::{ToyManagementEvent, ToyUpdate};
+use super::ipc::emit_plane::emit_toy_event;
 
 pub struct VCStateMutex(pub Arc<Mutex<VibeCheckState>>);
 
+// Aborts the task when dropped, so a restart that is itself stopped still stops the old server
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 pub enum RunningState {
     Running,
     Stopped,
 }
 
 pub struct VibeCheckState {
-    pub app_handle: Option<AppHandle>,
+    pub core_handle: Option<CoreHandle>,
     pub identifier: String,
 
     pub config: VibeCheckConfig,
     pub osc_query_handler: Option<OSCQuery>,
+    // Serves the host tree on the OSCQuery HTTP port
+    pub osc_query_host: Option<OscQueryHost>,
     //pub connection_modes: ConnectionModes,
     pub bp_client: Option<ButtplugClient>,
+    // Commands received by virtual devices
+    pub virtual_command_log: VirtualCommandLog,
+    // Global intensity and pause shared with the toy emitters
+    pub output_control: OutputControl,
+    // Current VRChat avatar ID for avatar toy profiles
+    pub avatar_id: Option<String>,
 
     pub running: RunningState,
     pub core_toy_manager: Option<ToyManager>,
@@ -54,6 +80,9 @@ pub struct VibeCheckState {
     pub toy_management_h_thread: Option<JoinHandle<()>>,
     // Disabled listener thread handle
     pub disabled_osc_listener_h_thread: Option<JoinHandle<()>>,
+    // Local API server and the config it was started with
+    pub local_api_h_thread: Option<JoinHandle<()>>,
+    local_api_started: Option<LocalApiConfig>,
 
     // These stay in VibeCheckState
     pub tme_recv_rx: UnboundedReceiver<ToyManagementEvent>,
@@ -90,12 +119,16 @@ impl VibeCheckState {
         ) = unbounded_channel();
 
         let state = Self {
-            app_handle: None,
+            core_handle: None,
             identifier: String::new(),
             config,
             osc_query_handler: None,
+            osc_query_host: None,
             //connection_modes,
             bp_client: None,
+            virtual_command_log: VirtualCommandLog::new(),
+            output_control: OutputControl::new(),
+            avatar_id: None,
             running: RunningState::Stopped,
             core_toy_manager: None,
             //======================================
@@ -106,6 +139,10 @@ impl VibeCheckState {
             // Disabled listener thread
             disabled_osc_listener_h_thread: None,
             //======================================
+            // Local API server
+            local_api_h_thread: None,
+            local_api_started: None,
+            //======================================
             // Client Event Handler
             client_eh_thread: None,
             //client_eh_event_rx,
@@ -138,10 +175,10 @@ impl VibeCheckState {
     }
 
     pub fn global_msg_handler_start(&mut self) -> Result<(), VibeCheckError> {
-        if self.app_handle.is_none() {
-            logerr!("global_msg_handler_start() called but no app_handle was set");
+        if self.core_handle.is_none() {
+            logerr!("global_msg_handler_start() called but no core_handle was set");
             return Err(VibeCheckError::new(
-                ErrorSource::Vcore(VcoreError::NoAppHandle),
+                ErrorSource::Vcore(VcoreError::NoCoreHandle),
                 None,
             ));
         }
@@ -151,17 +188,17 @@ impl VibeCheckState {
             unbounded_channel();
         self.error_comm_tx = Some(error_comm_tx);
         self.global_message_handler_thread = Some(self.async_rt.spawn(error_message_handler(
-            self.app_handle.as_ref().unwrap().clone(),
+            self.core_handle.as_ref().unwrap().clone(),
             error_comm_rx,
         )));
         Ok(())
     }
 
     pub fn start_tmh(&mut self) -> Result<(), VibeCheckError> {
-        if self.app_handle.is_none() {
-            logerr!("start_tmh() called but no app_handle was set");
+        if self.core_handle.is_none() {
+            logerr!("start_tmh() called but no core_handle was set");
             return Err(VibeCheckError::new(
-                ErrorSource::Vcore(VcoreError::NoAppHandle),
+                ErrorSource::Vcore(VcoreError::NoCoreHandle),
                 None,
             ));
         }
@@ -179,7 +216,8 @@ impl VibeCheckState {
             self.tme_send_rx.take().unwrap(),
             self.core_toy_manager.as_ref().unwrap().clone(),
             self.config.networking.clone(),
-            self.app_handle.as_ref().unwrap().clone(),
+            self.core_handle.as_ref().unwrap().clone(),
+            self.output_control.clone(),
         )));
         info!("TMH started");
         Ok(())
@@ -195,7 +233,7 @@ impl VibeCheckState {
 
         self.disabled_osc_listener_h_thread =
             Some(self.async_rt.spawn(vc_disabled_osc_command_listen(
-                self.app_handle.as_ref().unwrap().clone(),
+                self.core_handle.as_ref().unwrap().clone(),
                 self.config.networking.clone(),
             )));
         Ok(())
@@ -214,19 +252,94 @@ impl VibeCheckState {
         }
     }
 
+    /*
+     * Starts the Local API or restarts it with the current config
+     * A Local API client that changed the config calls this once its reply is sent
+     */
+    pub fn start_local_api(&mut self) {
+        let previous = self.local_api_h_thread.take().map(AbortOnDrop);
+        let listen = match (
+            self.config.local_api.enabled,
+            self.vibecheck_state_pointer.as_ref(),
+            self.core_handle.as_ref(),
+        ) {
+            (true, Some(vibecheck_state_pointer), Some(core_handle)) => Some(local_api_listen(
+                self.config.local_api.bind,
+                vibecheck_state_pointer.clone(),
+                core_handle.clone(),
+            )),
+            _ => None,
+        };
+        self.local_api_started = listen.is_some().then(|| self.config.local_api.clone());
+        if previous.is_none() && listen.is_none() {
+            return;
+        }
+
+        self.local_api_h_thread = Some(self.async_rt.spawn(async move {
+            if let Some(mut previous) = previous {
+                // Wait for the old listener to close before binding again
+                previous.0.abort();
+                let _ = (&mut previous.0).await;
+                info!("Local API stopped");
+            }
+            if let Some(listen) = listen {
+                listen.await;
+            }
+        }));
+    }
+
+    // True when the config changed the Local API since it was started
+    pub fn local_api_outdated(&self) -> bool {
+        let wanted = self
+            .config
+            .local_api
+            .enabled
+            .then_some(&self.config.local_api);
+        self.local_api_started.as_ref() != wanted
+    }
+
+    pub fn stop_local_api(&mut self) {
+        self.local_api_started = None;
+        if let Some(local_api_thread) = self.local_api_h_thread.take() {
+            local_api_thread.abort();
+            info!("Local API stopped");
+        }
+    }
+
     pub fn set_state_pointer(&mut self, vibecheck_state_pointer: Arc<Mutex<VibeCheckState>>) {
         self.vibecheck_state_pointer = Some(vibecheck_state_pointer);
     }
 
-    pub fn set_app_handle(&mut self, app_handle: &AppHandle) {
-        self.app_handle = Some(app_handle.clone());
+    pub fn set_core_handle(&mut self, core_handle: &CoreHandle) {
+        self.core_handle = Some(core_handle.clone());
+    }
+
+    /*
+     * Starts every core handler
+     * The state pointer and core handle have to be set first
+     */
+    pub fn start_core(&mut self) -> Result<(), VibeCheckError> {
+        self.output_control.set_safety(self.config.safety);
+        self.global_msg_handler_start()?;
+        trace!("Global message handler started");
+        self.init_toy_manager()?;
+        trace!("ToyManager initialized");
+        self.start_tmh()?;
+        trace!("Started TMH");
+        self.init_ceh()?;
+        trace!("Started CEH");
+        self.start_disabled_listener()?;
+        trace!("Started DOL");
+        self.start_local_api();
+        trace!("Started Local API");
+        Ok(())
     }
 
     pub fn init_toy_manager(&mut self) -> Result<(), VibeCheckError> {
         let toy_manager = ToyManager::new(
-            self.app_handle
+            self.core_handle
                 .as_ref()
-                .expect("Failed to get app handle")
+                .expect("Failed to get core handle")
                 .clone(),
         );
 
@@ -258,12 +371,25 @@ impl VibeCheckState {
         // Create connection mode defaults
         //let mut connection_modes = ConnectionModes { btle_enabled: true, lc_enabled: true };
 
+        // An external server may not be up yet, keep trying to connect in the background
+        if let ButtplugConnectorConfig::Websocket(_) = self.config.bp_connector {
+            self.client_eh_thread = Some(self.async_rt.spawn(bp_client_connect(
+                self.vibecheck_state_pointer.as_ref().unwrap().clone(),
+            )));
+            return Ok(());
+        }
+
         // Get ButtPlugClient with modified connection modes
 
-        let bp_client_future = bluetooth::vc_toy_client_server_init("VibeCheck", false);
+        let bp_client_future = bluetooth::vc_toy_client_init(
+            "VibeCheck",
+            false,
+            &self.config.bp_connector,
+            &self.virtual_command_log,
+        );
 
-        self.bp_client = match self.async_rt.block_on(bp_client_future) {
-            Ok(bpc) => Some(bpc),
+        let bp_client = match self.async_rt.block_on(bp_client_future) {
+            Ok(bpc) => bpc,
             Err(e) => {
                 logerr!("Failed to initialize bpio..");
                 return Err(VibeCheckError::new(
@@ -274,19 +400,78 @@ impl VibeCheckState {
         };
         info!("Buttplug Client Initialized.");
 
+        self.start_ceh(bp_client);
+        Ok(())
+    }
+
+    /*
+     * Sets the ButtplugClient and starts a CEH for its events
+     * Replaces the handle of whatever task was handling client events
+     */
+    pub fn start_ceh(&mut self, bp_client: ButtplugClient) {
         // Get event stream
-        let event_stream = self.bp_client.as_ref().unwrap().event_stream();
+        let event_stream = bp_client.event_stream().boxed();
+        self.bp_client = Some(bp_client);
 
         // Start CEH
         self.client_eh_thread = Some(self.async_rt.spawn(client_event_handler(
             event_stream,
             self.vibecheck_state_pointer.as_ref().unwrap().clone(),
             self.identifier.clone(),
-            self.app_handle.as_ref().unwrap().clone(),
+            self.core_handle.as_ref().unwrap().clone(),
             self.tme_send_tx.clone(),
             self.error_comm_tx.as_ref().unwrap().clone(),
         )));
-        Ok(())
+    }
+
+    /*
+     * Tears down the current ButtplugClient and connects with the configured connector
+     * Runs in the background so the caller doesn't block on the old server
+     */
+    pub fn switch_bp_connector(&mut self) {
+        if self.vibecheck_state_pointer.is_none() {
+            return;
+        }
+
+        // Stop handling events (or reconnecting) for the old connector
+        if let Some(ceh_thread) = self.client_eh_thread.take() {
+            ceh_thread.abort();
+        }
+        let old_client = self.bp_client.take();
+        self.remove_online_toys();
+
+        let vibecheck_state_pointer = self.vibecheck_state_pointer.as_ref().unwrap().clone();
+        self.client_eh_thread = Some(self.async_rt.spawn(async move {
+            if let Some(old_client) = old_client {
+                let _ = old_client.stop_all_devices().await;
+                if let Err(e) = old_client.disconnect().await {
+                    warn!("Failed to disconnect old Buttplug client: {}", e);
+                }
+            }
+            bp_client_connect(vibecheck_state_pointer).await;
+        }));
+        info!("Switching Buttplug connector");
+    }
+
+    /*
+     * Removes all online toys
+     * Used when the Buttplug server that owned the devices goes away
+     */
+    pub fn remove_online_toys(&mut self) {
+        let Some(toy_manager) = self.core_toy_manager.as_mut() else {
+            return;
+        };
+
+        for (toy_id, toy) in toy_manager.online_toys.drain() {
+            let _ = self
+                .tme_send_tx
+                .send(ToyManagementEvent::Tu(ToyUpdate::RemoveToy(toy_id)));
+            if let Some(core_handle) = self.core_handle.as_ref() {
+                emit_toy_event(core_handle, FeToyEvent::Remove(toy_id));
+            }
+            info!("Toy removed: {} | {}", toy.toy_name, toy_id);
+        }
+        self.osc_query_populate();
     }
 
     /*
@@ -315,13 +500,13 @@ impl VibeCheckState {
             return;
         }
 
-        if self.app_handle.is_none() {
+        if self.core_handle.is_none() {
             return;
         }
 
         self.toy_update_h_thread = Some(self.async_rt.spawn(toy_refresh(
             self.vibecheck_state_pointer.as_ref().unwrap().clone(),
-            self.app_handle.as_ref().unwrap().clone(),
+            self.core_handle.as_ref().unwrap().clone(),
         )));
         info!("TUH thread started");
     }
@@ -355,6 +540,7 @@ impl VibeCheckState {
         );
 
         self.osc_query_handler = Some(OSCQuery::new("VibeCheck".to_string(), http_net, osc_net));
+        self.osc_query_host = Some(OscQueryHost::new(http_net, osc_net));
         self.config
             .networking
             .bind
@@ -363,13 +549,35 @@ impl VibeCheckState {
 
     pub fn osc_query_fini(&mut self) {
         if self.osc_query_handler.is_some() {
-            let mut h = self.osc_query_handler.take().unwrap();
-            h.stop_http_json();
+            let h = self.osc_query_handler.take().unwrap();
+            if let Some(mut host) = self.osc_query_host.take() {
+                host.stop();
+            }
             h.unregister_mdns_service();
             h.shutdown_mdns();
         }
     }
 
+    /*
+     * Fills the OSCQuery host tree with the addresses online toys and the API consume
+     */
+    pub fn osc_query_populate(&self) {
+        let (Some(host), Some(toy_manager)) =
+            (self.osc_query_host.as_ref(), self.core_toy_manager.as_ref())
+        else {
+            return;
+        };
+
+        let addresses = consumed_addresses(
+            toy_manager
+                .online_toys
+                .values()
+                .flat_map(|toy| toy.parsed_toy_features.features.iter()),
+        );
+        trace!("OSCQuery host tree addresses: {:?}", addresses);
+        host.set_tree(host_tree(&addresses));
+    }
+
     pub fn osc_query_associate(&self) {
         if self.osc_query_handler.is_some() {
             self.osc_query_handler
@@ -47,6 +47,8 @@ export default function App() {
     toggleScan,
     isEnabled,
     toggleIsEnabled,
+    isEmergencyStopped,
+    toggleEmergencyStop,
     config,
     refreshConfig,
   } = useCoreEventContext();
@@ -141,6 +143,19 @@ export default function App() {
                 <div>Look for toys</div>
               )}
             </Button>
+            <Tooltip text={TOOLTIP.EmergencyStop.text}>
+              <button
+                onClick={toggleEmergencyStop}
+                className={cn(
+                  isEmergencyStopped
+                    ? "bg-red-600 text-zinc-50 hover:bg-red-500"
+                    : "bg-zinc-400 text-zinc-900 hover:bg-red-600 hover:text-zinc-50",
+                  "m-2 mt-0 rounded-md px-4 py-1",
+                )}
+              >
+                {isEmergencyStopped ? "Stopped - Re-arm" : "Emergency Stop"}
+              </button>
+            </Tooltip>
           </div>
           <div className="flex items-center justify-around">
             <UpdatePing canUpdate={canUpdate}>
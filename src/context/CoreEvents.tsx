@@ -1,4 +1,5 @@
 import type { FeCoreEvent } from "@bindings/FeCoreEvent";
+import type { FeSafetyEvent } from "@bindings/FeSafetyEvent";
 import { FeStateEvent } from "@bindings/FeStateEvent";
 import type { FeVibeCheckConfig } from "@bindings/FeVibeCheckConfig";
 import { invoke } from "@tauri-apps/api/core";
@@ -14,6 +15,8 @@ type CoreEventContextProps = {
   isScanning: boolean;
   isEnabled: boolean;
   toggleIsEnabled: () => Promise<void>;
+  isEmergencyStopped: boolean;
+  toggleEmergencyStop: () => Promise<void>;
   toggleScan: () => Promise<void>;
   config: FeVibeCheckConfig | undefined;
   refreshConfig: () => Promise<void>;
@@ -23,6 +26,8 @@ const CoreEventContext = createContext<CoreEventContextProps>({
   isScanning: false,
   isEnabled: false,
   toggleIsEnabled: () => new Promise(() => null),
+  isEmergencyStopped: false,
+  toggleEmergencyStop: () => new Promise(() => null),
   toggleScan: () => new Promise(() => null),
   config: undefined,
   refreshConfig: () => new Promise(() => null),
@@ -39,6 +44,7 @@ export function useCoreEventContext() {
 export function CoreEventProvider({ children }: { children: React.ReactNode }) {
   const [isEnabled, setIsEnabled] = useState(false);
   const [isScanning, setIsScanning] = useState(false);
+  const [isEmergencyStopped, setIsEmergencyStopped] = useState(false);
   const [config, setConfig] = useState<FeVibeCheckConfig | undefined>(
     undefined,
   );
@@ -97,6 +103,17 @@ export function CoreEventProvider({ children }: { children: React.ReactNode }) {
     }
   }
 
+  // State is set by the EmergencyStop core event
+  async function toggleEmergencyStop() {
+    try {
+      await invoke(
+        isEmergencyStopped ? INVOKE.EMERGENCY_REARM : INVOKE.EMERGENCY_STOP,
+      );
+    } catch (e) {
+      toast.error(`Could not toggle emergency stop!\n${JSON.stringify(e)}`);
+    }
+  }
+
   useEffect(() => {
     if (!isScanning) return;
     const i = setInterval(() => stopScan(), SCAN_LENGTH);
@@ -116,6 +133,21 @@ export function CoreEventProvider({ children }: { children: React.ReactNode }) {
     }
   }
 
+  function handleSafetyEvent(payload: FeSafetyEvent) {
+    const limit = payload.limit;
+    if (limit == "Ceiling") {
+      toast.warning(`${payload.toy_name} is limited by the safety ceiling`);
+    } else if (limit == "SlewLimit") {
+      toast.info(`${payload.toy_name} is ramping within the change limit`);
+    } else if (limit == "CooldownOver") {
+      toast.info(`${payload.toy_name} cooldown is over`);
+    } else {
+      toast.warning(
+        `${payload.toy_name} hit the run cap, idle for ${limit.RunCap} minutes`,
+      );
+    }
+  }
+
   function handleCoreEvent(payload: FeCoreEvent) {
     switch (payload.kind) {
       case "Scan":
@@ -124,6 +156,12 @@ export function CoreEventProvider({ children }: { children: React.ReactNode }) {
       case "State":
         handleStateEvent(payload.data);
         break;
+      case "EmergencyStop":
+        setIsEmergencyStopped(payload.data);
+        break;
+      case "Safety":
+        handleSafetyEvent(payload.data);
+        break;
       default:
         assertExhaustive(payload);
     }
@@ -158,6 +196,9 @@ export function CoreEventProvider({ children }: { children: React.ReactNode }) {
       }
     }
     getConfig();
+    invoke<boolean>(INVOKE.IS_EMERGENCY_STOPPED)
+      .then(setIsEmergencyStopped)
+      .catch(() => setIsEmergencyStopped(false));
   }, []);
 
   return (
@@ -166,6 +207,8 @@ export function CoreEventProvider({ children }: { children: React.ReactNode }) {
         isScanning,
         isEnabled,
         toggleIsEnabled,
+        isEmergencyStopped,
+        toggleEmergencyStop,
         toggleScan,
         config,
         refreshConfig,
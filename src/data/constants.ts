@@ -16,6 +16,15 @@ export const INVOKE = {
   SET_CONFIG: "set_vibecheck_config",
   OPEN_BROWSER: "open_default_browser",
   OFFLINE_SYNC: "sync_offline_toys",
+  EMERGENCY_STOP: "emergency_stop",
+  EMERGENCY_REARM: "emergency_rearm",
+  IS_EMERGENCY_STOPPED: "is_emergency_stopped",
+  GET_AVATAR_ID: "get_avatar_id",
+  COPY_AVATAR_PROFILE: "copy_avatar_profile",
+  GET_PATTERNS: "get_patterns",
+  GET_FUNSCRIPTS: "get_funscripts",
+  FUNSCRIPT_CONTROL: "funscript_control",
+  GET_AVATAR_PARAMETERS: "get_avatar_parameters",
 } as const;
 
 export const OSC = {
@@ -57,7 +66,7 @@ export const TOOLTIP = {
     link: "FeatureOptions",
   },
   LinearSpeed: {
-    text: "Linear positional duration speed in milliseconds. Speed is determined by the toy itself, this is only requested speed.",
+    text: "Linear positional duration speed in milliseconds. Speed is determined by the toy itself, this is only requested speed. With a penetration system this is the shortest time for a full stroke.",
     link: "FeatureOptions",
   },
   FlipInput: {
@@ -72,18 +81,50 @@ export const TOOLTIP = {
     text: "The minimum/maximum motor speed that will be sent to the feature's motor.",
     link: "FeatureOptions",
   },
+  ResponseCurve: {
+    text: "Shapes the feature level before it is limited to the range. Gamma above 1 is softer at low levels, S Curve eases in and out, Deadzone ignores low levels, Points is your own curve of input:output pairs from 0 to 100. Turn on Simulate to feel the curve.",
+    link: "",
+  },
   Smooth: {
     text: "This smooths the float input by queueing the amount set with the slider, then transforming them into one value to send instead. If you aren't sending a lot of floats rapidly over OSC you probably want this disabled completely.",
     link: "",
   },
   Rate: {
-    text: "This uses rate mode on the float input.",
+    text: "Rate mode turns parameter movement into level. This is how much level is lost every second when the parameter stops moving.",
     link: "",
   },
   Constant: {
     text: "The intensity your toy will activate when you have constant mode enabled.",
     link: "",
   },
+  SmoothTime: {
+    text: "Time in milliseconds for Ema mode to get halfway to a new level, or for Damped mode to settle on it. Output keeps moving even when the avatar stops sending.",
+    link: "",
+  },
+  Envelope: {
+    text: "Envelope mode ramps up to full over Attack, falls to the Sustain level over Decay and holds it while the contact is touched. Release is how long it takes to fade out after letting go. Times are in milliseconds.",
+    link: "",
+  },
+  GateThresholds: {
+    text: "Gate mode turns on when the parameter reaches the upper threshold and only turns off when it falls below the lower threshold, so a parameter wobbling around one value does not flicker the toy.",
+    link: "",
+  },
+  GateOutput: {
+    text: "While the gate is on, Passthrough sends the parameter's level and Fixed sends the set level.",
+    link: "",
+  },
+  GateHold: {
+    text: "Shortest time in milliseconds the gate stays on or off before it can switch again.",
+    link: "",
+  },
+  Modulation: {
+    text: "Moves the level up and down around the processed level so long sessions feel less mechanical. Noise wanders randomly, Sine/Triangle/Square are steady waves. The slider is how far the level moves. Keeps moving even when the avatar stops sending.",
+    link: "",
+  },
+  ModulationRate: {
+    text: "Waves per second, or how often the noise changes direction. The seed picks a different noise pattern.",
+    link: "",
+  },
   Simulate: {
     text: "Test feature power level.",
     link: "FeatureOptions",
@@ -100,6 +141,82 @@ export const TOOLTIP = {
     text: "OSC Send Port (Default: 127.0.0.1:9000)",
     link: "",
   },
+  EmergencyStop: {
+    text: "Stops every toy until re-armed. Also on the tray menu, Ctrl+Shift+End and the OSC API.",
+    link: "",
+  },
+  ExternalServer: {
+    text: "Connect to an external Buttplug server like Intiface Central instead of VibeCheck's own. (Default: ws://127.0.0.1:12345)",
+    link: "",
+  },
+  IntMapping: {
+    text: "How Int values of this parameter become levels. Normalize divides by the max, Lookup uses value:level (0-100) pairs and Threshold turns on at the value.",
+    link: "",
+  },
+  MissingAvatarParameter: {
+    text: "This parameter is not on your current avatar. Check it for typos.",
+    link: "",
+  },
+  OSC_Forwarding: {
+    text: "Apps that get a copy of every OSC packet VibeCheck receives, so they can run alongside VibeCheck. Set an address prefix to only forward matching packets. Applies the next time VibeCheck is enabled or disabled.",
+    link: "",
+  },
+  LevelOutput: {
+    text: "Address each feature's motor level is sent to VRChat at, for toys with OSC Data on. {toy}, {sub_id}, {feature_type} and {feature_index} are filled in for every feature.",
+    link: "",
+  },
+  LevelOutputRate: {
+    text: "Most motor level updates sent to VRChat per second for each toy. (Default: 10)",
+    link: "",
+  },
+  SafetyCeiling: {
+    text: "Hard limit for every toy feature, applied after all other settings. (Default: 1)",
+    link: "",
+  },
+  SafetyMaxChange: {
+    text: "Largest level change per second. Output ramps to new levels instead of jumping. 0 is off.",
+    link: "",
+  },
+  SafetyRunCap: {
+    text: "Forces a toy idle after running this many minutes without a break. 0 is off.",
+    link: "",
+  },
+  SafetyCooldown: {
+    text: "Minutes a toy stays idle after hitting the run cap.",
+    link: "",
+  },
+  ToySafety: {
+    text: "Use these safety limits for this toy instead of the ones in Settings.",
+    link: "",
+  },
+  AvatarProfiles: {
+    text: "Toy feature settings are saved per avatar once an avatar has a profile. Avatars without one use the default profile.",
+    link: "",
+  },
+  AvatarProfileFrom: {
+    text: "Avatar ID to copy toy settings from. Leave empty for the default profile.",
+    link: "",
+  },
+  AvatarProfileTo: {
+    text: "Avatar ID to copy toy settings to. Replaces its profile for every connected toy.",
+    link: "",
+  },
+  Funscript: {
+    text: "Plays a .funscript from the Funscripts folder on every enabled Linear feature. Scripts are loaded when OSC is enabled.",
+    link: "",
+  },
+  FunscriptSeek: {
+    text: "Jumps the playing funscript to this many seconds.",
+    link: "",
+  },
+  FunscriptSpeed: {
+    text: "Playback speed of funscripts. (0.1 - 4.0)",
+    link: "",
+  },
+  LocalApi: {
+    text: "WebSocket JSON API on this machine for stream decks, overlays and scripts. (Default: 127.0.0.1:9072)",
+    link: "",
+  },
   ScanOnDisconnect: {
     text: "Automatically start scanning when a toy disconnects.",
     link: "",
@@ -1,6 +1,10 @@
 import { FeProcessingMode } from "@bindings/FeProcessingMode";
 import { FeVCToyAnatomy } from "@bindings/FeVCToyAnatomy";
+import { IntMapping } from "@bindings/IntMapping";
+import { ModulationKind } from "@bindings/ModulationKind";
+import { ParameterBlendMode } from "@bindings/ParameterBlendMode";
 import { PenetrationSystemType } from "@bindings/PenetrationSystemType";
+import { ResponseCurve } from "@bindings/ResponseCurve";
 
 /*
 This file is a workaround because we cannot create an iterable string array from a ts_rs exported enum type
@@ -53,8 +57,62 @@ type PenTypeIsSame = StaticAssert<
   TypesAreEqual<PenetrationSystemType, (typeof PenetrationSystems)[number]>
 >;
 
-export const ProcessingModes = ["Raw", "Smooth", "Rate", "Constant"] as const;
+export const ProcessingModes = [
+  "Raw",
+  "Smooth",
+  "Rate",
+  "Constant",
+  "Pattern",
+  "Ema",
+  "Damped",
+  "Envelope",
+  "Gate",
+] as const;
 // eslint-disable-next-line @typescript-eslint/no-unused-vars
 type ProcessingModeIsSame = StaticAssert<
   TypesAreEqual<FeProcessingMode, (typeof ProcessingModes)[number]>
 >;
+
+export const ParameterBlendModes = [
+  "Max",
+  "Sum",
+  "Average",
+  "WeightedAverage",
+  "Priority",
+] as const;
+// eslint-disable-next-line @typescript-eslint/no-unused-vars
+type ParameterBlendModeIsSame = StaticAssert<
+  TypesAreEqual<ParameterBlendMode, (typeof ParameterBlendModes)[number]>
+>;
+
+export const ModulationKinds = [
+  "Off",
+  "Noise",
+  "Sine",
+  "Triangle",
+  "Square",
+] as const;
+// eslint-disable-next-line @typescript-eslint/no-unused-vars
+type ModulationKindIsSame = StaticAssert<
+  TypesAreEqual<ModulationKind, (typeof ModulationKinds)[number]>
+>;
+
+// Variant names of ResponseCurve, unit variants are strings and the rest are keys
+type VariantName<T> = T extends string ? T : keyof T;
+export const ResponseCurves = [
+  "Linear",
+  "Gamma",
+  "SCurve",
+  "Deadzone",
+  "Points",
+] as const;
+// eslint-disable-next-line @typescript-eslint/no-unused-vars
+type ResponseCurveIsSame = StaticAssert<
+  TypesAreEqual<VariantName<ResponseCurve>, (typeof ResponseCurves)[number]>
+>;
+
+export const IntMappings = ["Normalize", "Lookup", "Threshold"] as const;
+// eslint-disable-next-line @typescript-eslint/no-unused-vars
+type IntMappingIsSame = StaticAssert<
+  TypesAreEqual<VariantName<IntMapping>, (typeof IntMappings)[number]>
+>;
@@ -0,0 +1,62 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { INVOKE, TOOLTIP } from "../data/constants";
+import Button from "../layout/Button";
+import { TooltipLabel } from "../layout/Tooltip";
+
+export default function AvatarProfiles() {
+  const [avatarId, setAvatarId] = useState<string | null>(null);
+  const [fromAvatarId, setFromAvatarId] = useState("");
+  const [toAvatarId, setToAvatarId] = useState("");
+
+  useEffect(() => {
+    invoke<string | null>(INVOKE.GET_AVATAR_ID)
+      .then((id) => {
+        setAvatarId(id);
+        setToAvatarId(id ?? "");
+      })
+      .catch(() => setAvatarId(null));
+  }, []);
+
+  async function copyProfile() {
+    try {
+      await invoke(INVOKE.COPY_AVATAR_PROFILE, {
+        // Empty copies the default profile
+        fromAvatarId: fromAvatarId.trim() || null,
+        toAvatarId: toAvatarId.trim(),
+      });
+      toast.info(
+        `Copied ${fromAvatarId.trim() || "default"} profile to ${toAvatarId.trim()}`,
+      );
+    } catch (e) {
+      toast.error(`Could not copy avatar profile!\n${JSON.stringify(e)}`);
+    }
+  }
+
+  return (
+    <div className="mx-8 my-4 grid grid-cols-[minmax(10rem,4fr)_1fr_minmax(4rem,_4fr)] gap-1 text-justify text-sm">
+      <TooltipLabel text="Current Avatar" tooltip={TOOLTIP.AvatarProfiles} />
+      <div className="truncate">{avatarId ?? "None"}</div>
+      <div />
+      <TooltipLabel text="Copy From" tooltip={TOOLTIP.AvatarProfileFrom} />
+      <input
+        className="rounded-sm px-1 text-zinc-800 outline-none"
+        placeholder="default"
+        value={fromAvatarId}
+        onChange={(e) => setFromAvatarId(e.target.value)}
+      />
+      <div />
+      <TooltipLabel text="Copy To" tooltip={TOOLTIP.AvatarProfileTo} />
+      <input
+        className="rounded-sm px-1 text-zinc-800 outline-none"
+        placeholder="avtr_..."
+        value={toAvatarId}
+        onChange={(e) => setToAvatarId(e.target.value)}
+      />
+      <Button onClick={copyProfile} disabled={!toAvatarId.trim()}>
+        Copy
+      </Button>
+    </div>
+  );
+}
@@ -1,12 +1,16 @@
 import type { FeVibeCheckConfig } from "@bindings/FeVibeCheckConfig";
+import type { OscForwardTarget } from "@bindings/OscForwardTarget";
 import { invoke } from "@tauri-apps/api/core";
-import { ChangeEvent, FormEvent, useState } from "react";
+import { Plus, X } from "lucide-react";
+import { ChangeEvent, FormEvent, Fragment, useState } from "react";
 import { toast } from "sonner";
 import UpdateButton from "../components/UpdateButton";
 import { INVOKE, TOOLTIP } from "../data/constants";
 import Button from "../layout/Button";
 import Switch from "../layout/Switch";
 import { TooltipLabel } from "../layout/Tooltip";
+import AvatarProfiles from "./AvatarProfiles";
+import FunscriptPlayer from "./FunscriptPlayer";
 
 export default function Config({
   config,
@@ -35,6 +39,65 @@ export default function Config({
     });
   };
 
+  const onChangeLevelOutput = (e: ChangeEvent<HTMLInputElement>) => {
+    setNewConfig({
+      ...newConfig,
+      networking: {
+        ...newConfig.networking,
+        level_output: {
+          ...newConfig.networking.level_output,
+          [e.target.name]:
+            e.target.type == "number" ? Number(e.target.value) : e.target.value,
+        },
+      },
+    });
+  };
+
+  const setForwarding = (forwarding: OscForwardTarget[]) => {
+    setNewConfig({
+      ...newConfig,
+      networking: { ...newConfig.networking, forwarding },
+    });
+  };
+
+  const onChangeForward = (
+    e: ChangeEvent<HTMLInputElement>,
+    forwardIndex: number,
+  ) => {
+    setForwarding(
+      newConfig.networking.forwarding.map((forward, i) =>
+        i == forwardIndex
+          ? { ...forward, [e.target.name]: e.target.value }
+          : forward,
+      ),
+    );
+  };
+
+  const onChangeSafety = (e: ChangeEvent<HTMLInputElement>) => {
+    setNewConfig({
+      ...newConfig,
+      safety: {
+        ...newConfig.safety,
+        [e.target.name]: Number(e.target.value),
+      },
+    });
+  };
+
+  const externalServer =
+    typeof newConfig.bp_connector == "object" &&
+    "Websocket" in newConfig.bp_connector
+      ? newConfig.bp_connector.Websocket
+      : null;
+
+  const onCheckExternalServer = (checked: boolean) => {
+    setNewConfig({
+      ...newConfig,
+      bp_connector: checked
+        ? { Websocket: "ws://127.0.0.1:12345" }
+        : "InProcess",
+    });
+  };
+
   async function saveConfig() {
     try {
       if (
@@ -109,6 +172,209 @@ export default function Config({
                 (e.target as HTMLInputElement).setCustomValidity("")
               }
             />
+            <TooltipLabel text="Level Output" tooltip={TOOLTIP.LevelOutput} />
+            <div />
+            <input
+              name="address"
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              value={newConfig.networking.level_output.address}
+              onChange={onChangeLevelOutput}
+              pattern={String.raw`^/\S+$`}
+              onInvalid={(e) =>
+                (e.target as HTMLInputElement).setCustomValidity(
+                  "Enter an OSC address starting with /",
+                )
+              }
+              onInput={(e) =>
+                (e.target as HTMLInputElement).setCustomValidity("")
+              }
+            />
+            <TooltipLabel
+              text="Level Output Rate"
+              tooltip={TOOLTIP.LevelOutputRate}
+            />
+            <div />
+            <input
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              type="number"
+              name="rate_hz"
+              value={newConfig.networking.level_output.rate_hz}
+              onChange={onChangeLevelOutput}
+              min={1}
+              max={50}
+              step={1}
+            />
+            <TooltipLabel
+              text="OSC Forwarding"
+              tooltip={TOOLTIP.OSC_Forwarding}
+            />
+            <button
+              type="button"
+              className="flex justify-center"
+              onClick={() =>
+                setForwarding([
+                  ...newConfig.networking.forwarding,
+                  { target: "127.0.0.1:9002", address_prefix: "" },
+                ])
+              }
+            >
+              <Plus className="h-5" />
+            </button>
+            <div />
+            {newConfig.networking.forwarding.map((forward, forwardIndex) => (
+              <Fragment key={forwardIndex}>
+                <input
+                  name="target"
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  value={forward.target}
+                  onChange={(e) => onChangeForward(e, forwardIndex)}
+                  pattern={String.raw`^((25[0-5]|(2[0-4]|1\d|[1-9]|)\d)\.?\b){4}:\d{1,5}$`}
+                  onInvalid={(e) =>
+                    (e.target as HTMLInputElement).setCustomValidity(
+                      "Enter valid IP:HOST",
+                    )
+                  }
+                  onInput={(e) =>
+                    (e.target as HTMLInputElement).setCustomValidity("")
+                  }
+                />
+                <button
+                  type="button"
+                  className="flex justify-center"
+                  onClick={() =>
+                    setForwarding(
+                      newConfig.networking.forwarding.filter(
+                        (_, i) => i != forwardIndex,
+                      ),
+                    )
+                  }
+                >
+                  <X className="h-5" />
+                </button>
+                <input
+                  name="address_prefix"
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  placeholder="All addresses"
+                  value={forward.address_prefix}
+                  onChange={(e) => onChangeForward(e, forwardIndex)}
+                  pattern={String.raw`^(/\S*)?$`}
+                  onInvalid={(e) =>
+                    (e.target as HTMLInputElement).setCustomValidity(
+                      "Enter an address prefix starting with /",
+                    )
+                  }
+                  onInput={(e) =>
+                    (e.target as HTMLInputElement).setCustomValidity("")
+                  }
+                />
+              </Fragment>
+            ))}
+            <TooltipLabel
+              text="External Buttplug Server"
+              tooltip={TOOLTIP.ExternalServer}
+            />
+            <Switch
+              checked={externalServer != null}
+              onCheckedChange={onCheckExternalServer}
+              size="small"
+            />
+            {externalServer != null ? (
+              <input
+                className="rounded-sm px-1 text-zinc-800 outline-none"
+                value={externalServer}
+                onChange={(e) =>
+                  setNewConfig({
+                    ...newConfig,
+                    bp_connector: { Websocket: e.target.value },
+                  })
+                }
+                pattern={String.raw`^wss?://.+$`}
+                onInvalid={(e) =>
+                  (e.target as HTMLInputElement).setCustomValidity(
+                    "Enter valid ws://HOST:PORT",
+                  )
+                }
+                onInput={(e) =>
+                  (e.target as HTMLInputElement).setCustomValidity("")
+                }
+              />
+            ) : (
+              <div />
+            )}
+            <TooltipLabel text="Local API" tooltip={TOOLTIP.LocalApi} />
+            <Switch
+              checked={newConfig.local_api.enabled}
+              onCheckedChange={(checked) =>
+                setNewConfig({
+                  ...newConfig,
+                  local_api: { ...newConfig.local_api, enabled: checked },
+                })
+              }
+              size="small"
+            />
+            {newConfig.local_api.enabled ? (
+              <input
+                className="rounded-sm px-1 text-zinc-800 outline-none"
+                value={newConfig.local_api.bind}
+                onChange={(e) =>
+                  setNewConfig({
+                    ...newConfig,
+                    local_api: { ...newConfig.local_api, bind: e.target.value },
+                  })
+                }
+                pattern={String.raw`^127\.((25[0-5]|(2[0-4]|1\d|[1-9]|)\d)\.?\b){3}:\d{1,5}$`}
+                onInvalid={(e) =>
+                  (e.target as HTMLInputElement).setCustomValidity(
+                    "Enter valid 127.x.x.x:PORT",
+                  )
+                }
+                onInput={(e) =>
+                  (e.target as HTMLInputElement).setCustomValidity("")
+                }
+              />
+            ) : (
+              <div />
+            )}
+            <TooltipLabel text="Safety Ceiling" tooltip={TOOLTIP.SafetyCeiling} />
+            <input
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              type="number"
+              name="max_level"
+              value={newConfig.safety.max_level}
+              onChange={onChangeSafety}
+              min={0} max={1} step={0.01}
+            />
+            <div />
+            <TooltipLabel text="Max Change" tooltip={TOOLTIP.SafetyMaxChange} />
+            <input
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              type="number"
+              name="max_change_per_second"
+              value={newConfig.safety.max_change_per_second}
+              onChange={onChangeSafety}
+              min={0} step={0.1}
+            />
+            <div>/s</div>
+            <TooltipLabel text="Run Cap" tooltip={TOOLTIP.SafetyRunCap} />
+            <input
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              type="number"
+              name="run_cap_minutes"
+              value={newConfig.safety.run_cap_minutes}
+              onChange={onChangeSafety}
+              min={0} step={1}
+            />
+            <div>min</div>
+            <TooltipLabel text="Cooldown" tooltip={TOOLTIP.SafetyCooldown} />
+            <input
+              className="rounded-sm px-1 text-zinc-800 outline-none"
+              type="number"
+              name="cooldown_minutes"
+              value={newConfig.safety.cooldown_minutes}
+              onChange={onChangeSafety}
+              min={0} step={1}
+            />
+            <div>min</div>
             <TooltipLabel
               text="Scan On Disconnect"
               tooltip={TOOLTIP.ScanOnDisconnect}
@@ -171,6 +437,8 @@ export default function Config({
             <div />
           </div>
         </form>
+        <AvatarProfiles />
+        <FunscriptPlayer />
         <div className="flex justify-around">
           <Button type="submit" form="config">
             Save
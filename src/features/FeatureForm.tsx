@@ -1,14 +1,28 @@
 import { FreeTextOptions } from "@/components/ui/FreeTextOptions";
 import { useCoreEventContext } from "@/context/CoreEvents";
-import { PenetrationSystems, ProcessingModes } from "@/data/stringArrayTypes";
+import {
+  IntMappings,
+  PenetrationSystems,
+  ModulationKinds,
+  ParameterBlendModes,
+  ProcessingModes,
+  ResponseCurves,
+} from "@/data/stringArrayTypes";
 import { Select } from "@/layout/Select";
 import { cn } from "@/lib/utils";
+import { FeAvatarParameter } from "@bindings/FeAvatarParameter";
 import { FeLevelTweaks } from "@bindings/FeLevelTweaks";
 import { FeProcessingMode } from "@bindings/FeProcessingMode";
 import { FeToyParameter } from "@bindings/FeToyParameter";
 import { FeVCToy } from "@bindings/FeVCToy";
 import type { FeVCToyFeature } from "@bindings/FeVCToyFeature";
+import { IntMapping } from "@bindings/IntMapping";
+import { Modulation as FeatureModulation } from "@bindings/Modulation";
+import { ModulationKind } from "@bindings/ModulationKind";
+import { ParameterBlendMode } from "@bindings/ParameterBlendMode";
+import { ResponseCurve } from "@bindings/ResponseCurve";
 import { ScrollArea } from "@radix-ui/react-scroll-area";
+import { invoke } from "@tauri-apps/api/core";
 import { DebouncedFunc, debounce } from "lodash";
 import { Plus, X } from "lucide-react";
 import {
@@ -20,11 +34,12 @@ import {
   createContext,
   useCallback,
   useContext,
+  useEffect,
   useState,
 } from "react";
 import FourPanel from "../components/FourPanel";
 import FourPanelContainer from "../components/FourPanelContainer";
-import { OSC, TOOLTIP } from "../data/constants";
+import { INVOKE, OSC, TOOLTIP } from "../data/constants";
 import useSimulate from "../hooks/useSimulate";
 import { handleFeatureAlter as handleToyFeatureAlter } from "../hooks/useToys";
 import Slider from "../layout/Slider";
@@ -38,6 +53,7 @@ type FeatureFormContextProps = {
   handleFeatureAlter: (f: FeVCToyFeature) => void;
   handleBool: (checked: boolean, name: keyof FeVCToyFeature) => void;
   handleLevels: (key: keyof FeLevelTweaks, value: number) => void;
+  avatarParameters: FeAvatarParameter[];
 };
 
 const FeatureFormContext = createContext<FeatureFormContextProps>({
@@ -47,6 +63,7 @@ const FeatureFormContext = createContext<FeatureFormContextProps>({
   handleFeatureAlter: () => null,
   handleBool: () => null,
   handleLevels: () => null,
+  avatarParameters: [],
 });
 
 const useFeatureFormContext = () => {
@@ -60,11 +77,14 @@ const useFeatureFormContext = () => {
 type ToyFeatureFormProps = {
   toy: FeVCToy;
   selectedIndex: number;
+  // Fetched once by the toy view instead of for every feature
+  avatarParameters: FeAvatarParameter[];
 };
 
 export default function FeatureForm({
   toy,
   selectedIndex,
+  avatarParameters,
 }: ToyFeatureFormProps) {
   const [feature, setToyFeature] = useState(
     toy.features[selectedIndex] ?? toy.features[0],
@@ -117,6 +137,7 @@ export default function FeatureForm({
         handleFeatureAlter,
         handleBool,
         handleLevels,
+        avatarParameters,
       }}
     >
       <div className="rounded-md bg-zinc-700 p-4">
@@ -129,11 +150,18 @@ export default function FeatureForm({
             {config?.show_feature_advanced && (
               <>
                 <Idle />
+                <Curve />
+                {feature.feature_type != "Linear" && <Modulation />}
                 <FlipInput />
                 {feature.feature_type == "Linear" && <Linear />}
                 {tweakSliders.has("Smooth") && <Smooth />}
                 {tweakSliders.has("Rate") && <Rate />}
                 {tweakSliders.has("Constant") && <Constant />}
+                {(tweakSliders.has("Ema") || tweakSliders.has("Damped")) && (
+                  <SmoothTime />
+                )}
+                {tweakSliders.has("Envelope") && <Envelope />}
+                {tweakSliders.has("Gate") && <Gate />}
                 <Simulate toy={toy} />
               </>
             )}
@@ -204,7 +232,8 @@ function InputProcessor() {
             onChange={(e) => {
               handleInputProcessor(e);
             }}
-            options={ProcessingModes}
+            // Patterns are played by parameters
+            options={ProcessingModes.filter((mode) => mode != "Pattern")}
           />
         </div>
       }
@@ -213,8 +242,21 @@ function InputProcessor() {
 }
 
 function Parameters() {
-  const { feature, setToyFeature, handleFeatureAlter, debouncedAlter } =
-    useFeatureFormContext();
+  const {
+    feature,
+    setToyFeature,
+    handleFeatureAlter,
+    debouncedAlter,
+    avatarParameters,
+  } = useFeatureFormContext();
+  const [patterns, setPatterns] = useState<string[]>([]);
+  const { config } = useCoreEventContext();
+
+  useEffect(() => {
+    invoke<string[]>(INVOKE.GET_PATTERNS)
+      .then(setPatterns)
+      .catch(() => setPatterns([]));
+  }, []);
 
   function removeParam(parameter: string) {
     setToyFeature((f) => {
@@ -294,13 +336,89 @@ function Parameters() {
     });
   }
 
+  function handleOscParamPattern(
+    e: ChangeEvent<HTMLSelectElement>,
+    paramIndex: number,
+  ) {
+    setToyFeature((f) => {
+      const newParams = [...f.osc_parameters];
+      newParams[paramIndex].pattern = e.target.value;
+      const newF = {
+        ...f,
+        osc_parameters: newParams,
+      };
+      handleFeatureAlter(newF);
+      return newF;
+    });
+  }
+
+  function handleParamChange(
+    paramIndex: number,
+    change: Partial<FeToyParameter>,
+    debounced = false,
+  ) {
+    setToyFeature((f) => {
+      const newParams = [...f.osc_parameters];
+      newParams[paramIndex] = { ...newParams[paramIndex], ...change };
+      const newF = {
+        ...f,
+        osc_parameters: newParams,
+      };
+      if (debounced) debouncedAlter(newF);
+      else handleFeatureAlter(newF);
+      return newF;
+    });
+  }
+
+  function handleBlend(e: ChangeEvent<HTMLSelectElement>) {
+    setToyFeature((f) => {
+      const newF = {
+        ...f,
+        parameter_blend: e.target.value as ParameterBlendMode,
+      };
+      handleFeatureAlter(newF);
+      return newF;
+    });
+  }
+
   function normalizeOscParameter(p: string) {
     return `${OSC.PARAM_PREFIX}${p.replaceAll(" ", "_")}`;
   }
 
+  // Only warn when VRChat told us which parameters the avatar has
+  function isMissingFromAvatar(parameter: string) {
+    return (
+      avatarParameters.length > 0 &&
+      !avatarParameters.some((p) => p.address == parameter)
+    );
+  }
+
   return (
     <>
       {/* <div className="grid grid-cols-[minmax(6rem,20fr),minmax(6rem,6fr),minmax(1rem,1fr)] gap-x-6 gap-y-2 text-justify text-sm"> */}
+      <datalist id="avatar_parameters">
+        {avatarParameters
+          .filter((p) => p.address.startsWith(OSC.PARAM_PREFIX))
+          .map((p) => (
+            <option
+              key={p.address}
+              value={p.address.replace(OSC.PARAM_PREFIX, "")}
+              label={p.param_type}
+            />
+          ))}
+      </datalist>
+      {feature.osc_parameters.length > 1 && (
+        <>
+          <div className="col-span-1 text-right md:col-span-2">Blend</div>
+          <Select
+            name="parameter_blend"
+            value={feature.parameter_blend}
+            onChange={handleBlend}
+            options={ParameterBlendModes}
+          />
+          <div />
+        </>
+      )}
       {feature.osc_parameters.map((param, paramIndex) => {
         // TODO: Using index is generally an anti-pattern, but I think it's required in this specific scenario
         // If we key on a parameter or other identifiers, typing the parameter name would trigger a refresh from the backend
@@ -309,8 +427,18 @@ function Parameters() {
           <Fragment key={paramIndex}>
             {/* Adding debounce on this makes it more complex b/c separate state, plus parent key on index */}
             <input
-              className="col-span-1 w-full rounded-sm px-4 text-zinc-800 outline-none md:col-span-2"
+              className={cn(
+                "col-span-1 w-full rounded-sm px-4 text-zinc-800 outline-none md:col-span-2",
+                isMissingFromAvatar(param.parameter) &&
+                  "outline-1 outline-red-500",
+              )}
               name="osc_parameter"
+              list="avatar_parameters"
+              title={
+                isMissingFromAvatar(param.parameter)
+                  ? TOOLTIP.MissingAvatarParameter.text
+                  : undefined
+              }
               value={param.parameter.replace(OSC.PARAM_PREFIX, "")}
               onChange={(e) => handleOscParam(e, paramIndex)}
             />
@@ -328,6 +456,52 @@ function Parameters() {
             >
               <X className="h-5" />
             </button>
+            {param.processing_mode == "Pattern" && (
+              <>
+                <div className="col-span-1 text-right md:col-span-2">
+                  Pattern
+                </div>
+                <Select
+                  name="osc_parameter_pattern"
+                  value={param.pattern ?? ""}
+                  onChange={(e) => handleOscParamPattern(e, paramIndex)}
+                  options={["", ...patterns]}
+                />
+                <div />
+              </>
+            )}
+            {feature.parameter_blend == "WeightedAverage" &&
+              feature.osc_parameters.length > 1 && (
+                <>
+                  <div className="col-span-1 text-right md:col-span-2">
+                    Weight
+                  </div>
+                  <input
+                    className="w-full rounded-sm px-1 text-zinc-800 outline-none"
+                    type="number"
+                    name="osc_parameter_weight"
+                    min={0}
+                    step={0.1}
+                    value={param.weight ?? 1}
+                    onChange={(e) =>
+                      handleParamChange(
+                        paramIndex,
+                        { weight: Math.max(0, Number(e.target.value)) },
+                        true,
+                      )
+                    }
+                  />
+                  <div />
+                </>
+              )}
+            {config?.show_feature_advanced && (
+              <ParamIntMapping
+                mapping={param.int_mapping ?? defaultIntMappings.Normalize}
+                onMappingChange={(int_mapping, debounced) =>
+                  handleParamChange(paramIndex, { int_mapping }, debounced)
+                }
+              />
+            )}
           </Fragment>
         );
       })}
@@ -341,6 +515,116 @@ function Parameters() {
   );
 }
 
+type IntMappingName = (typeof IntMappings)[number];
+
+// VRChat Int parameters range from 0 to 255
+const defaultIntMappings: Record<IntMappingName, IntMapping> = {
+  Normalize: { Normalize: 255 },
+  Lookup: {
+    Lookup: [
+      [0, 0],
+      [1, 1],
+    ],
+  },
+  Threshold: { Threshold: 1 },
+};
+
+function intMappingName(mapping: IntMapping): IntMappingName {
+  return Object.keys(mapping)[0] as IntMappingName;
+}
+
+function lookupToText(table: [number, number][]) {
+  return table
+    .map(([value, level]) => `${value}:${round0.format(level * 100)}`)
+    .join(", ");
+}
+
+// "value:level" pairs with levels from 0 to 100, pairs that are not numbers are dropped
+function textToLookup(text: string): [number, number][] {
+  return text
+    .split(",")
+    .map((pair) => pair.split(":"))
+    .map(([value, level]) => [parseInt(value), parseFloat(level) / 100])
+    .filter(
+      (pair): pair is [number, number] =>
+        pair.length == 2 && !isNaN(pair[0]) && !isNaN(pair[1]),
+    );
+}
+
+// How a parameter's Int values become levels
+function ParamIntMapping({
+  mapping,
+  onMappingChange,
+}: {
+  mapping: IntMapping;
+  onMappingChange: (mapping: IntMapping, debounced: boolean) => void;
+}) {
+  const [lookupText, setLookupText] = useState(
+    "Lookup" in mapping ? lookupToText(mapping.Lookup) : "",
+  );
+
+  function handleName(e: ChangeEvent<HTMLSelectElement>) {
+    const newMapping = defaultIntMappings[e.target.value as IntMappingName];
+    if ("Lookup" in newMapping) setLookupText(lookupToText(newMapping.Lookup));
+    onMappingChange(newMapping, false);
+  }
+
+  function handleLookup(e: ChangeEvent<HTMLInputElement>) {
+    setLookupText(e.target.value);
+    const table = textToLookup(e.target.value);
+    if (table.length > 0) onMappingChange({ Lookup: table }, true);
+  }
+
+  let value: ReactNode;
+  if ("Lookup" in mapping) {
+    value = (
+      <input
+        className="w-full rounded-sm px-1 text-zinc-800 outline-none"
+        name="osc_parameter_int_lookup"
+        value={lookupText}
+        onChange={handleLookup}
+      />
+    );
+  } else {
+    const isNormalize = "Normalize" in mapping;
+    value = (
+      <input
+        className="w-full rounded-sm px-1 text-zinc-800 outline-none"
+        type="number"
+        name="osc_parameter_int_value"
+        step={1}
+        value={isNormalize ? mapping.Normalize : mapping.Threshold}
+        onChange={(e) => {
+          const n = parseInt(e.target.value);
+          if (isNaN(n)) return;
+          onMappingChange(
+            isNormalize ? { Normalize: n } : { Threshold: n },
+            true,
+          );
+        }}
+      />
+    );
+  }
+
+  return (
+    <>
+      <div
+        className="col-span-1 text-right md:col-span-2"
+        title={TOOLTIP.IntMapping.text}
+      >
+        Int Mapping
+      </div>
+      <Select
+        name="osc_parameter_int_mapping"
+        value={intMappingName(mapping)}
+        onChange={handleName}
+        options={IntMappings}
+      />
+      {value}
+    </>
+  );
+}
+
 function InputFilter() {
   const { feature, setToyFeature, handleFeatureAlter } =
     useFeatureFormContext();
@@ -463,6 +747,237 @@ function Range() {
   );
 }
 
+type ResponseCurveName = (typeof ResponseCurves)[number];
+
+const defaultCurves: Record<ResponseCurveName, ResponseCurve> = {
+  Linear: "Linear",
+  Gamma: { Gamma: 2 },
+  SCurve: { SCurve: 1 },
+  Deadzone: { Deadzone: 0.1 },
+  Points: {
+    Points: [
+      [0, 0],
+      [0.5, 0.25],
+      [1, 1],
+    ],
+  },
+};
+
+function curveName(curve: ResponseCurve): ResponseCurveName {
+  return typeof curve == "string"
+    ? curve
+    : (Object.keys(curve)[0] as ResponseCurveName);
+}
+
+function pointsToText(points: [number, number][]) {
+  return points
+    .map(([x, y]) => `${round0.format(x * 100)}:${round0.format(y * 100)}`)
+    .join(", ");
+}
+
+// "input:output" pairs from 0 to 100, pairs that are not numbers are dropped
+function textToPoints(text: string): [number, number][] {
+  return text
+    .split(",")
+    .map((pair) => pair.split(":").map((n) => parseFloat(n) / 100))
+    .filter(
+      (pair): pair is [number, number] =>
+        pair.length == 2 && !isNaN(pair[0]) && !isNaN(pair[1]),
+    );
+}
+
+function Curve() {
+  const { feature, setToyFeature, handleFeatureAlter, debouncedAlter } =
+    useFeatureFormContext();
+  const curve = feature.feature_levels.response_curve;
+  const name = curveName(curve);
+  const [pointsText, setPointsText] = useState(
+    typeof curve != "string" && "Points" in curve
+      ? pointsToText(curve.Points)
+      : "",
+  );
+
+  function setCurve(response_curve: ResponseCurve) {
+    const newF = {
+      ...feature,
+      feature_levels: { ...feature.feature_levels, response_curve },
+    };
+    setToyFeature(newF);
+    return newF;
+  }
+
+  function handleCurveName(e: ChangeEvent<HTMLSelectElement>) {
+    const newCurve = defaultCurves[e.target.value as ResponseCurveName];
+    if (typeof newCurve != "string" && "Points" in newCurve) {
+      setPointsText(pointsToText(newCurve.Points));
+    }
+    handleFeatureAlter(setCurve(newCurve));
+  }
+
+  function handlePoints(e: ChangeEvent<HTMLInputElement>) {
+    setPointsText(e.target.value);
+    const points = textToPoints(e.target.value);
+    if (points.length > 0) debouncedAlter(setCurve({ Points: points }));
+  }
+
+  function curveSlider(
+    value: number,
+    min: number,
+    max: number,
+    step: number,
+    toCurve: (value: number) => ResponseCurve,
+  ) {
+    return (
+      <Slider
+        min={min}
+        max={max}
+        step={step}
+        value={[value]}
+        onValueChange={(e) => setCurve(toCurve(e[0]))}
+        onValueCommit={() => handleFeatureAlter(feature)}
+      />
+    );
+  }
+
+  let three: ReactNode = null;
+  let four: ReactNode = null;
+  if (typeof curve != "string") {
+    if ("Gamma" in curve) {
+      three = curveSlider(curve.Gamma, 0.2, 5, 0.05, (v) => ({ Gamma: v }));
+      four = curve.Gamma.toFixed(2);
+    } else if ("SCurve" in curve) {
+      three = curveSlider(curve.SCurve, 0, 1, 0.01, (v) => ({ SCurve: v }));
+      four = round0.format(curve.SCurve * 100);
+    } else if ("Deadzone" in curve) {
+      three = curveSlider(curve.Deadzone, 0, 0.9, 0.01, (v) => ({
+        Deadzone: v,
+      }));
+      four = round0.format(curve.Deadzone * 100);
+    } else {
+      three = (
+        <input
+          className="w-full rounded-sm px-1 text-zinc-800 outline-none"
+          name="response_curve_points"
+          value={pointsText}
+          onChange={handlePoints}
+        />
+      );
+    }
+  }
+
+  return (
+    <FourPanel
+      text="Response Curve"
+      tooltip={TOOLTIP.ResponseCurve}
+      two={
+        <Select
+          name="response_curve"
+          value={name}
+          onChange={handleCurveName}
+          options={ResponseCurves}
+        />
+      }
+      three={three}
+      four={four}
+    />
+  );
+}
+
+function Modulation() {
+  const { feature, setToyFeature, handleFeatureAlter } =
+    useFeatureFormContext();
+  const modulation = feature.feature_levels.modulation;
+
+  function setModulation(changes: Partial<FeatureModulation>) {
+    const newF = {
+      ...feature,
+      feature_levels: {
+        ...feature.feature_levels,
+        modulation: { ...modulation, ...changes },
+      },
+    };
+    setToyFeature(newF);
+    return newF;
+  }
+
+  return (
+    <>
+      <FourPanel
+        text="Modulation"
+        tooltip={TOOLTIP.Modulation}
+        two={
+          <Select
+            name="modulation_kind"
+            value={modulation.kind}
+            onChange={(e) =>
+              handleFeatureAlter(
+                setModulation({ kind: e.target.value as ModulationKind }),
+              )
+            }
+            options={ModulationKinds}
+          />
+        }
+        three={
+          modulation.kind != "Off" && (
+            <Slider
+              multiply={100}
+              min={0}
+              max={0.5}
+              step={0.01}
+              value={[modulation.amplitude]}
+              onValueChange={(e) => setModulation({ amplitude: e[0] })}
+              onValueCommit={() => handleFeatureAlter(feature)}
+            />
+          )
+        }
+        four={
+          modulation.kind != "Off" &&
+          round0.format(modulation.amplitude * 100)
+        }
+      />
+      {modulation.kind != "Off" && (
+        <FourPanel
+          text="Modulation Rate"
+          tooltip={TOOLTIP.ModulationRate}
+          two={
+            modulation.kind == "Noise" && (
+              <input
+                className="w-full rounded-sm px-1 text-zinc-800 outline-none"
+                type="number"
+                name="modulation_seed"
+                value={modulation.seed}
+                min={0}
+                step={1}
+                onChange={(e) =>
+                  handleFeatureAlter(
+                    setModulation({
+                      seed: Math.max(
+                        0,
+                        Math.floor(e.target.valueAsNumber || 0),
+                      ),
+                    }),
+                  )
+                }
+              />
+            )
+          }
+          three={
+            <Slider
+              min={0.05}
+              max={5}
+              step={0.05}
+              value={[modulation.frequency_hz]}
+              onValueChange={(e) => setModulation({ frequency_hz: e[0] })}
+              onValueCommit={() => handleFeatureAlter(feature)}
+            />
+          }
+          four={`${modulation.frequency_hz.toFixed(2)}Hz`}
+        />
+      )}
+    </>
+  );
+}
+
 function FlipInput() {
   const { feature, handleBool } = useFeatureFormContext();
   return (
@@ -507,19 +1022,19 @@ function Rate() {
   const levels = feature.feature_levels;
   return (
     <FourPanel
-      text="Rate Level"
+      text="Rate Decay"
       tooltip={TOOLTIP.Rate}
       three={
         <Slider
           min={0.1}
-          max={2}
-          step={0.05}
-          value={[levels.rate_tune]}
-          onValueChange={(e) => handleLevels("rate_tune", e[0])}
+          max={5}
+          step={0.1}
+          value={[levels.rate_decay_per_second]}
+          onValueChange={(e) => handleLevels("rate_decay_per_second", e[0])}
           onValueCommit={() => handleFeatureAlter(feature)}
         />
       }
-      four={levels.rate_tune.toString()}
+      four={levels.rate_decay_per_second.toString()}
     />
   );
 }
@@ -546,11 +1061,187 @@ function Constant() {
   );
 }
 
+function SmoothTime() {
+  const { feature, handleLevels, handleFeatureAlter } = useFeatureFormContext();
+  const levels = feature.feature_levels;
+  return (
+    <FourPanel
+      text="Smooth Time"
+      tooltip={TOOLTIP.SmoothTime}
+      three={
+        <Slider
+          min={10}
+          max={2000}
+          step={10}
+          value={[levels.smooth_half_life_ms]}
+          onValueChange={(e) => handleLevels("smooth_half_life_ms", e[0])}
+          onValueCommit={() => handleFeatureAlter(feature)}
+        />
+      }
+      four={levels.smooth_half_life_ms.toString()}
+    />
+  );
+}
+
+function Envelope() {
+  const { feature, handleLevels, handleFeatureAlter } = useFeatureFormContext();
+  const levels = feature.feature_levels;
+  const stages: [string, keyof FeLevelTweaks, number][] = [
+    ["Attack", "envelope_attack_ms", levels.envelope_attack_ms],
+    ["Decay", "envelope_decay_ms", levels.envelope_decay_ms],
+    ["Release", "envelope_release_ms", levels.envelope_release_ms],
+  ];
+  return (
+    <>
+      {stages.map(([text, key, value]) => (
+        <FourPanel
+          key={key}
+          text={text}
+          tooltip={TOOLTIP.Envelope}
+          three={
+            <Slider
+              min={0}
+              max={2000}
+              step={10}
+              value={[value]}
+              onValueChange={(e) => handleLevels(key, e[0])}
+              onValueCommit={() => handleFeatureAlter(feature)}
+            />
+          }
+          four={value.toString()}
+        />
+      ))}
+      <FourPanel
+        text="Sustain"
+        tooltip={TOOLTIP.Envelope}
+        three={
+          <Slider
+            multiply={100}
+            min={0}
+            max={1}
+            step={0.01}
+            value={[levels.envelope_sustain_level]}
+            onValueChange={(e) => handleLevels("envelope_sustain_level", e[0])}
+            onValueCommit={() => handleFeatureAlter(feature)}
+          />
+        }
+        four={round0.format(levels.envelope_sustain_level * 100)}
+      />
+    </>
+  );
+}
+
+const GateOutputs = ["Passthrough", "Fixed"] as const;
+
+function Gate() {
+  const { feature, setToyFeature, handleLevels, handleFeatureAlter } =
+    useFeatureFormContext();
+  const levels = feature.feature_levels;
+
+  function handleGateOutput(e: ChangeEvent<HTMLSelectElement>) {
+    const newF = {
+      ...feature,
+      feature_levels: {
+        ...levels,
+        gate_passthrough: e.target.value == "Passthrough",
+      },
+    };
+    setToyFeature(newF);
+    handleFeatureAlter(newF);
+  }
+
+  const holds: [string, keyof FeLevelTweaks, number][] = [
+    ["Gate Min On", "gate_min_on_ms", levels.gate_min_on_ms],
+    ["Gate Min Off", "gate_min_off_ms", levels.gate_min_off_ms],
+  ];
+  return (
+    <>
+      <FourPanel
+        text="Gate Thresholds"
+        tooltip={TOOLTIP.GateThresholds}
+        three={
+          <Slider
+            multiply={100}
+            min={0}
+            max={1}
+            step={0.01}
+            value={[levels.gate_lower_threshold, levels.gate_upper_threshold]}
+            onValueChange={(e) => {
+              setToyFeature((f) => {
+                return {
+                  ...f,
+                  feature_levels: {
+                    ...levels,
+                    gate_lower_threshold: e[0],
+                    gate_upper_threshold: e[1],
+                  },
+                };
+              });
+            }}
+            onValueCommit={() => handleFeatureAlter(feature)}
+          />
+        }
+        four={`${round0.format(
+          levels.gate_lower_threshold * 100,
+        )}-${round0.format(levels.gate_upper_threshold * 100)}`}
+      />
+      <FourPanel
+        text="Gate Output"
+        tooltip={TOOLTIP.GateOutput}
+        two={
+          <Select
+            name="gate_output"
+            value={levels.gate_passthrough ? "Passthrough" : "Fixed"}
+            onChange={handleGateOutput}
+            options={GateOutputs}
+          />
+        }
+        three={
+          !levels.gate_passthrough && (
+            <Slider
+              multiply={100}
+              min={0}
+              max={1}
+              step={0.01}
+              value={[levels.gate_fixed_level]}
+              onValueChange={(e) => handleLevels("gate_fixed_level", e[0])}
+              onValueCommit={() => handleFeatureAlter(feature)}
+            />
+          )
+        }
+        four={
+          !levels.gate_passthrough &&
+          round0.format(levels.gate_fixed_level * 100)
+        }
+      />
+      {holds.map(([text, key, value]) => (
+        <FourPanel
+          key={key}
+          text={text}
+          tooltip={TOOLTIP.GateHold}
+          three={
+            <Slider
+              min={0}
+              max={2000}
+              step={10}
+              value={[value]}
+              onValueChange={(e) => handleLevels(key, e[0])}
+              onValueCommit={() => handleFeatureAlter(feature)}
+            />
+          }
+          four={value.toString()}
+        />
+      ))}
+    </>
+  );
+}
+
 function Simulate({ toy }: { toy: FeVCToy }) {
   const { feature } = useFeatureFormContext();
   const {
     simulateEnabled,
     simulateLevel,
+    simulateOutputLevel,
     toggleSimulate,
     simulateOnValueChange,
     simulateOnValueCommit,
@@ -581,7 +1272,13 @@ function Simulate({ toy }: { toy: FeVCToy }) {
               onValueCommit={() => simulateOnValueCommit()}
             />
           }
-          four={round0.format(simulateLevel * 100)}
+          four={
+            simulateEnabled && simulateOutputLevel !== null
+              ? `${round0.format(simulateLevel * 100)} → ${round0.format(
+                  simulateOutputLevel * 100,
+                )}`
+              : round0.format(simulateLevel * 100)
+          }
         />
       )}
     </>
@@ -0,0 +1,74 @@
+import type { FunscriptControl } from "@bindings/FunscriptControl";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { INVOKE, TOOLTIP } from "../data/constants";
+import Button from "../layout/Button";
+import { Select } from "../layout/Select";
+import { TooltipLabel } from "../layout/Tooltip";
+
+export default function FunscriptPlayer() {
+  const [funscripts, setFunscripts] = useState<string[]>([]);
+  const [funscript, setFunscript] = useState("");
+  const [seekSeconds, setSeekSeconds] = useState(0);
+  const [speed, setSpeed] = useState(1);
+
+  useEffect(() => {
+    invoke<string[]>(INVOKE.GET_FUNSCRIPTS)
+      .then((names) => {
+        setFunscripts(names);
+        setFunscript(names.at(0) ?? "");
+      })
+      .catch(() => setFunscripts([]));
+  }, []);
+
+  async function control(control: FunscriptControl) {
+    try {
+      await invoke(INVOKE.FUNSCRIPT_CONTROL, { control });
+    } catch (e) {
+      toast.error(`Funscript playback needs OSC enabled!\n${JSON.stringify(e)}`);
+    }
+  }
+
+  return (
+    <div className="mx-8 my-4 grid grid-cols-[minmax(10rem,4fr)_1fr_minmax(4rem,_4fr)] gap-1 text-justify text-sm">
+      <TooltipLabel text="Funscript" tooltip={TOOLTIP.Funscript} />
+      <Select
+        name="funscript"
+        value={funscript}
+        onChange={(e) => setFunscript(e.target.value)}
+        options={funscripts}
+      />
+      <div className="flex gap-1">
+        <Button
+          onClick={() => control({ Play: funscript })}
+          disabled={!funscript}
+        >
+          Play
+        </Button>
+        <Button onClick={() => control("Stop")}>Stop</Button>
+      </div>
+      <TooltipLabel text="Seek" tooltip={TOOLTIP.FunscriptSeek} />
+      <input
+        className="rounded-sm px-1 text-zinc-800 outline-none"
+        type="number"
+        value={seekSeconds}
+        onChange={(e) => setSeekSeconds(e.target.valueAsNumber || 0)}
+        min={0}
+        step={1}
+      />
+      <Button onClick={() => control({ Seek: seekSeconds })}>Seek</Button>
+      <TooltipLabel text="Speed" tooltip={TOOLTIP.FunscriptSpeed} />
+      <input
+        className="rounded-sm px-1 text-zinc-800 outline-none"
+        type="number"
+        value={speed}
+        onChange={(e) => setSpeed(e.target.valueAsNumber || 1)}
+        min={0.1}
+        max={4}
+        step={0.1}
+      />
+      <Button onClick={() => control({ Speed: speed })}>Set</Button>
+    </div>
+  );
+}
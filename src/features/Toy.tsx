@@ -7,6 +7,7 @@ import lovenseLogo from "../assets/Lovense.png";
 import BatteryIcon from "../components/BatteryIcon";
 import Tooltip from "../layout/Tooltip";
 import { cn } from "../lib/utils";
+import { useAvatarParameters } from "../hooks/useAvatarParameters";
 import FeatureForm from "./FeatureForm";
 import ToySettings from "./ToySettings";
 
@@ -15,6 +16,7 @@ export default function Toy({ toy }: { toy: FeVCToy }) {
   const nameInfo = NameInfo(toy);
 
   const { config } = useCoreEventContext();
+  const { avatarParameters } = useAvatarParameters();
   const clampedIndex =
     toy.features.length === 0
       ? 0
@@ -57,6 +59,7 @@ export default function Toy({ toy }: { toy: FeVCToy }) {
           <FeatureForm
             toy={toy}
             selectedIndex={clampedIndex}
+            avatarParameters={avatarParameters}
             key={toyFeatureKey(toy, toy.features[clampedIndex])}
           />
         ) : (
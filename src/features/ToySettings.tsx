@@ -1,7 +1,8 @@
 import type { FeVCToy } from "@bindings/FeVCToy";
 import { FeVCToyAnatomy } from "@bindings/FeVCToyAnatomy";
+import type { SafetyConfig } from "@bindings/SafetyConfig";
 import { ClipboardCopy } from "lucide-react";
-import { useEffect, useState } from "react";
+import { ChangeEvent, useEffect, useState } from "react";
 import { toast } from "sonner";
 import FourPanel from "../components/FourPanel";
 import FourPanelContainer from "../components/FourPanelContainer";
@@ -17,6 +18,12 @@ export default function ToySettings({ toy }: { toy: FeVCToy }) {
   const BT_UPDATE_DEFAULT = 20;
   const BT_UPDATE_MIN = 1;
   const BT_UPDATE_MAX = 500;
+  const SAFETY_DEFAULT: SafetyConfig = {
+    max_level: 1,
+    max_change_per_second: 0,
+    run_cap_minutes: 0,
+    cooldown_minutes: 5,
+  };
 
   function clampUpdateRate(rate: number) {
     return Math.min(BT_UPDATE_MAX, Math.max(BT_UPDATE_MIN, rate));
@@ -42,6 +49,17 @@ export default function ToySettings({ toy }: { toy: FeVCToy }) {
     handleToyAlter({ ...toy, bt_update_rate: BigInt(nextRate) });
   }
 
+  const [safety, setSafety] = useState(toy.safety);
+
+  useEffect(() => {
+    setSafety(toy.safety);
+  }, [toy.safety]);
+
+  function handleSafetyChange(e: ChangeEvent<HTMLInputElement>) {
+    if (!safety) return;
+    setSafety({ ...safety, [e.target.name]: Number(e.target.value) });
+  }
+
   const parsed_toy_name = toy.toy_name.replaceAll(" ", "_").toLowerCase();
   const osc_data_addr = `${OSC.DATA_PREFIX}${parsed_toy_name}/${toy.sub_id}/battery`;
 
@@ -110,6 +128,94 @@ export default function ToySettings({ toy }: { toy: FeVCToy }) {
           }
           four={`${btUpdateRate}hz`}
         />
+        <FourPanel
+          text="Safety Override"
+          tooltip={TOOLTIP.ToySafety}
+          two={
+            <Switch
+              size="small"
+              checked={toy.safety != null}
+              onCheckedChange={(checked) =>
+                handleToyAlter({
+                  ...toy,
+                  safety: checked ? SAFETY_DEFAULT : null,
+                })
+              }
+            />
+          }
+        />
+        {safety && (
+          <>
+            <FourPanel
+              text="Safety Ceiling"
+              tooltip={TOOLTIP.SafetyCeiling}
+              three={
+                <input
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  type="number"
+                  name="max_level"
+                  value={safety.max_level}
+                  onChange={handleSafetyChange}
+                  onBlur={() => handleToyAlter({ ...toy, safety })}
+                  min={0}
+                  max={1}
+                  step={0.01}
+                />
+              }
+            />
+            <FourPanel
+              text="Max Change"
+              tooltip={TOOLTIP.SafetyMaxChange}
+              three={
+                <input
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  type="number"
+                  name="max_change_per_second"
+                  value={safety.max_change_per_second}
+                  onChange={handleSafetyChange}
+                  onBlur={() => handleToyAlter({ ...toy, safety })}
+                  min={0}
+                  step={0.1}
+                />
+              }
+              four="/s"
+            />
+            <FourPanel
+              text="Run Cap"
+              tooltip={TOOLTIP.SafetyRunCap}
+              three={
+                <input
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  type="number"
+                  name="run_cap_minutes"
+                  value={safety.run_cap_minutes}
+                  onChange={handleSafetyChange}
+                  onBlur={() => handleToyAlter({ ...toy, safety })}
+                  min={0}
+                  step={1}
+                />
+              }
+              four="min"
+            />
+            <FourPanel
+              text="Cooldown"
+              tooltip={TOOLTIP.SafetyCooldown}
+              three={
+                <input
+                  className="rounded-sm px-1 text-zinc-800 outline-none"
+                  type="number"
+                  name="cooldown_minutes"
+                  value={safety.cooldown_minutes}
+                  onChange={handleSafetyChange}
+                  onBlur={() => handleToyAlter({ ...toy, safety })}
+                  min={0}
+                  step={1}
+                />
+              }
+              four="min"
+            />
+          </>
+        )}
       </FourPanelContainer>
     </div>
   );
@@ -0,0 +1,28 @@
+import { FeAvatarParameter } from "@bindings/FeAvatarParameter";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { INVOKE } from "../data/constants";
+
+// Empty when VRChat could not be found over OSCQuery
+export function useAvatarParameters() {
+  const [avatarParameters, setAvatarParameters] = useState<
+    FeAvatarParameter[]
+  >([]);
+
+  async function refreshAvatarParameters() {
+    try {
+      const parameters = await invoke<FeAvatarParameter[]>(
+        INVOKE.GET_AVATAR_PARAMETERS,
+      );
+      setAvatarParameters(parameters);
+    } catch {
+      setAvatarParameters([]);
+    }
+  }
+
+  useEffect(() => {
+    refreshAvatarParameters();
+  }, []);
+
+  return { avatarParameters, refreshAvatarParameters };
+}
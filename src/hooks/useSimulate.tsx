@@ -11,6 +11,8 @@ export default function useSimulate(
 ) {
   const [simulateEnabled, setSimulateEnabled] = useState(false);
   const [level, setLevel] = useState(0.5);
+  // Level the toy got after the feature's response curve and range
+  const [outputLevel, setOutputLevel] = useState<number | null>(null);
 
   function toggleSimulate() {
     setSimulateEnabled((b) => !b);
@@ -29,6 +31,7 @@ export default function useSimulate(
   useEffect(() => {
     setSimulateEnabled(false);
     setLevel(0.5);
+    setOutputLevel(null);
     return () => {
       if (feature.feature_levels.idle_level == 0) {
         invokeSimulation(0);
@@ -46,13 +49,17 @@ export default function useSimulate(
   async function invokeSimulation(floatLevel: number) {
     if (toyId == null) return;
     try {
-      await invoke(INVOKE.SIMULATE_TOY_FEATURE, {
-        toyId,
-        featureIndex: feature.feature_index,
-        featureType: feature.feature_type,
-        floatLevel,
-        stop: false,
-      });
+      const sentLevel = await invoke<number | null>(
+        INVOKE.SIMULATE_TOY_FEATURE,
+        {
+          toyId,
+          featureIndex: feature.feature_index,
+          featureType: feature.feature_type,
+          floatLevel,
+          stop: false,
+        },
+      );
+      setOutputLevel(sentLevel);
     } catch (e) {
       toast.error(`Could not simulate device feature!\n${JSON.stringify(e)}`);
     }
@@ -62,6 +69,7 @@ export default function useSimulate(
     return {
       simulateEnabled: null,
       simulateLevel: null,
+      simulateOutputLevel: null,
       toggleSimulate: () => null,
       simultaeOnValueChange: () => null,
       simultaeOnValueCommit: () => null,
@@ -70,6 +78,7 @@ export default function useSimulate(
     return {
       simulateEnabled,
       simulateLevel: level,
+      simulateOutputLevel: outputLevel,
       toggleSimulate,
       simulateOnValueChange,
       simulateOnValueCommit,